@@ -0,0 +1,35 @@
+use guitar_scale::audio::{Sampler, SampleStatus};
+use guitar_scale::error::Result;
+use std::env;
+
+/// Cents from the note above which a sample is out of tune
+const TOLERANCE: f64 = 15.0;
+
+/// Detect the pitch of each sample of ./temp_audio (or of the folder
+/// given) and report the mislabeled and out of tune files. With `--rename`
+/// the mislabeled files are renamed after their pitch, e.g.
+/// `cargo run --example check_audio ./temp_audio --rename`
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let rename = args.iter().any(|a| a == "--rename");
+    let dir = args
+        .iter()
+        .find(|a| !a.starts_with("--"))
+        .map_or("./temp_audio", |a| a.as_str());
+    let checks = Sampler::check(dir)?;
+    for check in &checks {
+        let status = match check.status(TOLERANCE) {
+            SampleStatus::InTune => "ok",
+            SampleStatus::OutOfTune => "out of tune",
+            SampleStatus::Mislabeled => "mislabeled",
+            SampleStatus::Unpitched => "no pitch",
+        };
+        println!("{:<12} {}", status, check);
+    }
+    if rename {
+        for (from, to) in Sampler::relabel(dir, &checks)? {
+            println!("{} renamed {}", from, to);
+        }
+    }
+    Ok(())
+}
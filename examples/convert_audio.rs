@@ -0,0 +1,23 @@
+use guitar_scale::audio::{convert_dir, AudioFormat, ExportSettings};
+use guitar_scale::error::Result;
+use std::env;
+
+/// Convert the wav files of a directory to Ogg Vorbis, without external
+/// encoder, e.g. `cargo run --example convert_audio ./temp_audio 128`
+/// writes ./temp_audio/ogg/*.ogg at 128 kbit/s
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let input = args.next().unwrap_or_else(|| "./temp_audio".to_string());
+    let mut settings = ExportSettings {
+        format: AudioFormat::OggVorbis,
+        ..ExportSettings::default()
+    };
+    if let Some(kbps) = args.next().and_then(|a| a.parse::<u32>().ok()) {
+        settings.bitrate = kbps * 1000;
+    }
+    let output = format!("{}/ogg", input.trim_end_matches('/'));
+    for file in convert_dir(&input, &output, settings)? {
+        println!("{}", file.display());
+    }
+    Ok(())
+}
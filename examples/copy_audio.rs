@@ -1,42 +1,34 @@
-use std::io;
+use std::env;
 use std::fs;
-use guitar_scale::note::{Note, TraitGenerate};
-use std::str::FromStr;
 use std::path::Path;
+use guitar_scale::audio::SampleBank;
+use guitar_scale::error::Result;
+use guitar_scale::tuning::Tuning;
 
-/// Copy assets from renoise output folder to ./temp audio
-fn main() -> io::Result<()> {
-    let vec_note = ["C","C#","D","D#","E","F","F#","G","G#","A","A#","B",];
-    let mut idx: u8 = 0 as u8;
-    for pitch in 0 as u8..3 as u8 {
-        for x in vec_note.to_vec().iter().filter(|&&x_str| if pitch == 0 {
-            if x_str == "C" || x_str == "C#" || x_str == "D" || x_str == "D#" {
-                false
-            } else {
-                true
-            }
-        } else {
-            true
-        } ) {
-            idx += 1;
-            copy(*x, pitch, idx)?;
-        }
-    }
-    Ok(())
-}
+const ORIGIN: &str = "./renoise_guitar_split/output";
+const MANIFEST: &str = "manifest.json";
+const DEST: &str = "./temp_audio";
 
-/// Copy assets from renoise output folder to ./temp_audio
-fn copy(note: &str, pitch: u8, i: u8) -> io::Result<()> {
-    let note= match Note::from_str(&note) {
-        Ok(ok) => ok,
-        Err(err) => {
-            panic!("{}", err)
-        }
+/// Copy assets from renoise output folder (or from the folder given, e.g.
+/// written by `slice_audio`) to ./temp_audio, as described by the manifest
+/// of the folder. Without manifest in the renoise folder, the bank rendered
+/// by `renoise_guitar_split` is assumed and its manifest is written.
+/// `cargo run --example copy_audio --features serde`
+fn main() -> Result<()> {
+    let origin = env::args().nth(1).unwrap_or_else(|| ORIGIN.to_string());
+    let manifest = Path::new(&origin).join(MANIFEST);
+    let bank = if manifest.is_file() || origin != ORIGIN {
+        SampleBank::open(&manifest)?
+    } else {
+        let bank = SampleBank::renoise_guitar_split(Tuning::E);
+        bank.save(&manifest)?;
+        bank
     };
-    let origin_string: String = String::from(format!("./renoise_guitar_split/output/guitar_split_Seq{:02}.wav", i));
-    let origin = Path::new(&origin_string);
-    let dest_string: String = String::from(format!("./temp_audio/guitar_{}_{}.wav",note.data_name(), pitch));
-    let dest = Path::new(&dest_string);
-    fs::copy(origin, dest)?;
+    let report =
+        bank.validate(Tuning::E, SampleBank::guitar_range(Tuning::E));
+    eprint!("{}", report);
+    for (file, dest) in bank.data_file_names() {
+        fs::copy(Path::new(&origin).join(file), Path::new(DEST).join(dest))?;
+    }
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,16 @@
+use std::env;
+use guitar_scale::error::Result;
+use guitar_scale::svg_draw::DrawArpeggio;
+
+/// Generate the arpeggio svg of a chord across the neck and in each CAGED
+/// shape, e.g. `cargo run --example generate_arpeggio Am7`
+fn main() -> Result<()> {
+    let chord = env::args().nth(1).unwrap_or_else(|| "Am7".to_string());
+    let mut draw = DrawArpeggio::from_names(&chord, "E", "L")?;
+    draw.save("temp/arpeggio.svg")?;
+    for (i, shape) in draw.arpeggio.get_shapes().into_iter().enumerate() {
+        draw.shape = Some(shape);
+        draw.save(format!("temp/arpeggio_{}_{:?}.svg", i, shape.caged))?;
+    }
+    Ok(())
+}
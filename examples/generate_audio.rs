@@ -0,0 +1,30 @@
+use guitar_scale::audio::{Pluck, Sampler, TraitInstrument, SAMPLE_RATE};
+use guitar_scale::error::Result;
+use guitar_scale::midi::MidiSettings;
+use guitar_scale::scale::Scale;
+use std::env;
+
+/// Render a scale run with the sample bank of ./temp_audio, or with the
+/// plucked string synthesis without sample bank, e.g.
+/// `cargo run --example generate_audio A "minor pentatonic"`
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let tonic = args.next().unwrap_or_else(|| "A".to_string());
+    let scale_type = args.next().unwrap_or_else(|| "minor".to_string());
+    let scale = Scale::from_names(&tonic, &scale_type, "E")?;
+    let sampler = Sampler::load("./temp_audio")?;
+    let pluck = Pluck::default();
+    let instrument: &dyn TraitInstrument = if sampler.samples.is_empty() {
+        &pluck
+    } else {
+        &sampler
+    };
+    let midi = scale.to_midi(MidiSettings::default());
+    let path = format!(
+        "temp/{}_{}.wav",
+        scale.tonic.to_string().to_lowercase(),
+        scale.scale_type.get_name_short().to_lowercase()
+    );
+    instrument.render(&midi, SAMPLE_RATE).save(path)?;
+    Ok(())
+}
@@ -0,0 +1,10 @@
+use guitar_scale::error::Result;
+use guitar_scale::svg_draw::DrawHtml;
+use guitar_scale::tuning::Tuning;
+
+/// Generate a standalone html page to browse every scale in every key,
+/// e.g. `cargo run --example generate_html`
+fn main() -> Result<()> {
+    DrawHtml::new(Tuning::E).save("temp/guitar_scales.html")?;
+    Ok(())
+}
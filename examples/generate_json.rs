@@ -1,8 +1,10 @@
+use guitar_scale::capo::Capo;
 use std::str::FromStr;
 use std::fs::File;
 use guitar_scale::tuning::Tuning;
 use strum::IntoEnumIterator;
 use std::io::Write;
+use std::error::Error;
 use guitar_scale::note::{Note, TraitGenerate};
 use guitar_scale::scale::Scale;
 use guitar_scale::scale::ScaleType;
@@ -34,33 +36,29 @@ enum ThemeIos {
 }
 
 /// Generate scales svg
-fn main() -> std::io::Result<()> {
+fn main() -> Result<(), Box<dyn Error>> {
     let vec_note = ["C","C#","D","D#","E","F","F#","G","G#","A","A#","B"];
     //let serialized: Vec<String> = vec_note.to_vec().iter().map(|x| serde_json::to_string(&note(*x))).collect();
     let mut v: Vec<GuitarScale> = Vec::new();
     for x in vec_note.to_vec().iter() {
-        v = GuitarScale::note(*x).into_iter().chain(v).collect()
+        v = GuitarScale::note(x)?.into_iter().chain(v).collect()
     }
-    let serialized = serde_json::to_string(&v).unwrap();
+    let serialized = serde_json::to_string(&v)?;
     let mut buffer = File::create("export.json")?;
-    buffer.write_all(&serialized.as_bytes())?;
+    buffer.write_all(serialized.as_bytes())?;
     Ok(())
 }
 
 impl GuitarScale {
     /// Generate scales svg for a specified tonic
-    fn note(note: &str) -> Vec<Self> {
-        let tonic = match Note::from_str(&note) {
-            Ok(ok) => ok,
-            Err(err) => {
-                panic!("{}", err)
-            }
-        };
+    fn note(note: &str) -> guitar_scale::error::Result<Vec<Self>> {
+        let tonic = Note::from_str(note)?;
         let vec_scale: Vec<Scale> = ScaleType::iter().map(|x| {
             Scale {
                 scale_type: x,
                 tuning: Tuning::E,
-                tonic
+                tonic,
+                capo: Capo::default(),
             }
         }).collect();
         let mut vec_guitar_current: Vec<GuitarScale> = Vec::new();
@@ -100,6 +98,6 @@ impl GuitarScale {
                 svg
             });
         }
-        vec_guitar_current.into_iter().collect()
+        Ok(vec_guitar_current)
     }
 }
\ No newline at end of file
@@ -0,0 +1,20 @@
+use guitar_scale::error::Result;
+use guitar_scale::midi::MidiSettings;
+use guitar_scale::scale::Scale;
+use std::env;
+
+/// Generate the MIDI file of a scale run over two octaves, e.g.
+/// `cargo run --example generate_midi A "minor pentatonic"`
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let tonic = args.next().unwrap_or_else(|| "A".to_string());
+    let scale_type = args.next().unwrap_or_else(|| "minor".to_string());
+    let scale = Scale::from_names(&tonic, &scale_type, "E")?;
+    let path = format!(
+        "temp/{}_{}.mid",
+        scale.tonic.to_string().to_lowercase(),
+        scale.scale_type.get_name_short().to_lowercase()
+    );
+    scale.to_midi(MidiSettings::default()).save(path)?;
+    Ok(())
+}
@@ -1,57 +1,43 @@
+use guitar_scale::capo::Capo;
 use std::str::FromStr;
-use std::fs::File;
+use guitar_scale::error::Result;
 use guitar_scale::tuning::Tuning;
 use strum::IntoEnumIterator;
-use std::io::Write;
 use guitar_scale::note::{Note, TraitGenerate};
 use guitar_scale::scale::Scale;
 use guitar_scale::scale::ScaleType;
 use guitar_scale::svg_draw::{DrawScale, Theme};
 
 /// Generate scales svg
-fn main() -> std::io::Result<()> {
+fn main() -> Result<()> {
     let vec_note = ["C","C#","D","D#","E","F","F#","G","G#","A","A#","B"];
     for x in vec_note.to_vec().iter() {
-        note(*x)?;
+        note(x)?;
     }
     Ok(())
 }
 
 /// Generate scales svg for a specified tonic
-fn note(note: &str) -> std::io::Result<()> {
-    let tonic = match Note::from_str(&note) {
-        Ok(ok) => ok,
-        Err(err) => {
-            panic!("{}", err)
-        }
-    };
+fn note(note: &str) -> Result<()> {
+    let tonic = Note::from_str(note)?;
     let vec_scale: Vec<Scale> = ScaleType::iter().map(|x| {
         Scale {
             scale_type: x,
             tuning: Tuning::E,
-            tonic
+            tonic,
+            capo: Capo::default(),
         }
     }).collect();
     for v_s in vec_scale {
         let draw: DrawScale = DrawScale::new(v_s, Theme::Light);
 
-        let svg = format!("{}",draw.draw_base_vertical());
-        let mut buffer = File::create(format!("temp_scales_svg/scale_v_light_{}_{}.svg", v_s.scale_type.get_name_short(), &tonic.data_name()))?;
-        buffer.write_all(&svg.as_bytes())?;
-
-        let svg = format!("{}",draw.draw_base());
-        let mut buffer = File::create(format!("temp_scales_svg/scale_h_light_{}_{}.svg", v_s.scale_type.get_name_short(), &tonic.data_name()))?;
-        buffer.write_all(&svg.as_bytes())?;
+        draw.save_vertical(format!("temp_scales_svg/scale_v_light_{}_{}.svg", v_s.scale_type.get_name_short(), tonic.data_name()))?;
+        draw.save(format!("temp_scales_svg/scale_h_light_{}_{}.svg", v_s.scale_type.get_name_short(), tonic.data_name()))?;
 
         let draw: DrawScale = DrawScale::new(v_s, Theme::Dark);
 
-        let svg = format!("{}",draw.draw_base_vertical());
-        let mut buffer = File::create(format!("temp_scales_svg/scale_v_dark_{}_{}.svg", v_s.scale_type.get_name_short(), &tonic.data_name()))?;
-        buffer.write_all(&svg.as_bytes())?;
-
-        let svg = format!("{}",draw.draw_base());
-        let mut buffer = File::create(format!("temp_scales_svg/scale_h_dark_{}_{}.svg", v_s.scale_type.get_name_short(), &tonic.data_name()))?;
-        buffer.write_all(&svg.as_bytes())?;
+        draw.save_vertical(format!("temp_scales_svg/scale_v_dark_{}_{}.svg", v_s.scale_type.get_name_short(), tonic.data_name()))?;
+        draw.save(format!("temp_scales_svg/scale_h_dark_{}_{}.svg", v_s.scale_type.get_name_short(), tonic.data_name()))?;
     }
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,21 @@
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::str::FromStr;
+use guitar_scale::error::Result;
+use guitar_scale::progression::Progression;
+use guitar_scale::svg_draw::{DrawProgression, Theme};
+
+/// Generate the scales svg of a chord progression, e.g.
+/// `cargo run --example generate_progression "Dm7 G7 Cmaj7 A7"`
+fn main() -> Result<()> {
+    let progression_str = env::args()
+        .nth(1)
+        .unwrap_or_else(|| "Dm7 G7 Cmaj7 A7".to_string());
+    let progression = Progression::from_str(&progression_str)?;
+    let draw = DrawProgression::new(progression, Theme::Light);
+    let svg = format!("{}", draw.draw_base());
+    let mut buffer = File::create("temp/progression.svg")?;
+    buffer.write_all(svg.as_bytes())?;
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use guitar_scale::midi::MidiSettings;
+use guitar_scale::scale::Scale;
+use guitar_scale::svg_draw::{DrawScale, Theme};
+use std::env;
+use std::error::Error;
+use std::fs;
+
+/// Generate the timeline (json) of a scale run, each note with the id of
+/// its circle, and the svg animated along it, e.g.
+/// `cargo run --features serde --example generate_timeline A "minor pentatonic"`
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = env::args().skip(1);
+    let tonic = args.next().unwrap_or_else(|| "A".to_string());
+    let scale_type = args.next().unwrap_or_else(|| "minor".to_string());
+    let scale = Scale::from_names(&tonic, &scale_type, "E")?;
+    let timeline = scale.to_timeline(MidiSettings::default());
+    let name = format!(
+        "temp/{}_{}",
+        scale.tonic.to_string().to_lowercase(),
+        scale.scale_type.get_name_short().to_lowercase()
+    );
+    fs::write(
+        format!("{}.json", name),
+        serde_json::to_string_pretty(&timeline)?,
+    )?;
+    DrawScale::new(scale, Theme::Light)
+        .save_animated(format!("{}_animated.svg", name), &timeline)?;
+    Ok(())
+}
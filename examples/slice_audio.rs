@@ -0,0 +1,39 @@
+use guitar_scale::audio::{SampleBank, SliceSettings, Wav};
+use guitar_scale::error::Result;
+use guitar_scale::tuning::Tuning;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const DEST: &str = "./temp_audio/slices";
+
+/// Cut a recording of single notes, played on each string from the lowest,
+/// fret by fret from the first fret given to the second one, into one
+/// sample per note with its manifest, e.g.
+/// `cargo run --example slice_audio --features serde recording.wav 0 4`
+/// then `cargo run --example copy_audio --features serde ./temp_audio/slices`
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    let recording = args.next().unwrap_or_else(|| "recording.wav".to_string());
+    let mut fret = || args.next().and_then(|a| a.parse::<usize>().ok());
+    let frets = fret().unwrap_or(0)..=fret().unwrap_or(4);
+    let mut bank = SampleBank::chromatic(Tuning::E, frets);
+    let slices = Wav::open(&recording)?.slices(SliceSettings::default());
+    if slices.len() != bank.samples.len() {
+        eprintln!(
+            "{} notes found, {} expected",
+            slices.len(),
+            bank.samples.len()
+        );
+    }
+    bank.samples.truncate(slices.len());
+    let first = bank.samples.first().map_or(0, |e| e.pitch.midi());
+    let last = bank.samples.last().map_or(0, |e| e.pitch.midi());
+    eprint!("{}", bank.validate(Tuning::E, first..=last));
+    fs::create_dir_all(DEST)?;
+    for (slice, entry) in slices.iter().zip(&bank.samples) {
+        slice.save(Path::new(DEST).join(&entry.file))?;
+    }
+    bank.save(Path::new(DEST).join("manifest.json"))?;
+    Ok(())
+}
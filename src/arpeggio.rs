@@ -0,0 +1,331 @@
+use crate::capo::Capo;
+use crate::fretboard::NUMBER_POSITION;
+use crate::interval::{Interval, TraitChordTone};
+use crate::midi::{
+    MidiFile, MidiNote, NoteValue, TICKS_PER_BEAT, VELOCITY_DEFAULT,
+};
+use crate::note::Note;
+use crate::pitch::Pitch;
+use crate::scale::{
+    get_degree, get_open_strings, get_string_combination, DegreeAllStrings,
+};
+use crate::tuning::Tuning;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use ukebox::{Chord, PitchClass, Semitones};
+
+/// Custom error for strings that cannot be parsed into chords.
+#[derive(Debug)]
+pub struct ParseChordError {
+    pub name: String,
+}
+
+impl Error for ParseChordError {}
+
+impl fmt::Display for ParseChordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not parse chord \"{}\"", self.name)
+    }
+}
+
+/// Frets covered by a shape
+const SHAPE_FRETS: usize = 4;
+
+/// The five open chord shapes moved up the neck
+/// https://en.wikipedia.org/wiki/CAGED_system
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CagedShape {
+    C,
+    A,
+    G,
+    E,
+    D,
+}
+
+impl CagedShape {
+    /// String of the lowest root, begin at 1 for the lowest string
+    fn root_string(self) -> usize {
+        match self {
+            CagedShape::G | CagedShape::E => 1,
+            CagedShape::C | CagedShape::A => 2,
+            CagedShape::D => 3,
+        }
+    }
+
+    /// Frets of the shape below its lowest root
+    fn frets_below_root(self) -> usize {
+        match self {
+            CagedShape::C | CagedShape::G => SHAPE_FRETS - 1,
+            CagedShape::A | CagedShape::E | CagedShape::D => 0,
+        }
+    }
+}
+
+/// A CAGED shape at its place on the neck, from fret `low` to fret `high`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ArpeggioShape {
+    pub caged: CagedShape,
+    pub low: usize,
+    pub high: usize,
+}
+
+impl ArpeggioShape {
+    pub fn contains(self, position: usize) -> bool {
+        (self.low..=self.high).contains(&position)
+    }
+}
+
+/// The tones of a chord, or of any set of intervals, across the neck
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Arpeggio {
+    pub root: Note,
+    /// Intervals from the root, beginning with the root itself
+    pub intervals: Vec<Interval>,
+    /// Tones of the chord, in the order of `intervals`
+    pub notes: Vec<Note>,
+    /// Index of the tone in the bass, 0 for the root position
+    pub inversion: usize,
+    pub tuning: Tuning,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub capo: Capo,
+}
+
+impl Arpeggio {
+    pub fn new(root: Note, intervals: Vec<Interval>, tuning: Tuning) -> Self {
+        let notes = intervals.iter().map(|&i| root + i).collect();
+        Self {
+            root,
+            intervals,
+            notes,
+            inversion: 0,
+            tuning,
+            capo: Capo::default(),
+        }
+    }
+
+    /// Arpeggio of the tones of a chord of ukebox
+    pub fn from_chord(chord: &Chord, tuning: Tuning) -> Self {
+        Self {
+            root: Note::from(chord.root),
+            intervals: chord.chord_type.intervals().collect(),
+            notes: chord.notes().map(Note::from).collect(),
+            inversion: 0,
+            tuning,
+            capo: Capo::default(),
+        }
+    }
+
+    /// Arpeggio of a chord symbol (e.g. "Am7") in a tuning
+    pub fn from_names(
+        chord: &str,
+        tuning: &str,
+    ) -> Result<Self, crate::error::Error> {
+        let tuning = Tuning::from_str(tuning)?;
+        match Chord::from_str(chord) {
+            Ok(chord) => Ok(Self::from_chord(&chord, tuning)),
+            Err(_) => Err(ParseChordError {
+                name: chord.to_string(),
+            }
+            .into()),
+        }
+    }
+
+    /// Tone in the bass
+    pub fn bass(&self) -> Note {
+        self.notes[self.inversion % self.notes.len()]
+    }
+
+    /// Name of the chord tone of a pitch class ("R", "3", "b7", ...), `None`
+    /// if it is not in the chord
+    pub fn chord_tone(&self, pitch_class: PitchClass) -> Option<&'static str> {
+        self.notes
+            .iter()
+            .zip(&self.intervals)
+            .find(|(n, _)| n.pitch_class == pitch_class)
+            .map(|(_, i)| i.to_chord_tone())
+    }
+
+    /// Get the chord tones for each string (begin at 1 (not 0))
+    pub fn get_string_combination(&self) -> Vec<DegreeAllStrings> {
+        let pitch_classes: Vec<PitchClass> =
+            self.notes.iter().map(|n| n.pitch_class).collect();
+        let degree = get_degree(&pitch_classes, self.root.pitch_class);
+        get_string_combination(self.tuning, self.capo, &degree)
+    }
+
+    /// Pitches of the strings played at the capo, from the lowest string
+    pub fn get_open_strings(&self) -> Vec<Pitch> {
+        get_open_strings(self.tuning, self.capo)
+    }
+
+    /// Every CAGED shape drawn on the neck, from the nut (or the capo)
+    pub fn get_shapes(&self) -> Vec<ArpeggioShape> {
+        let strings = self.get_string_combination();
+        let mut shapes: Vec<ArpeggioShape> = Vec::new();
+        for caged in CagedShape::iter() {
+            let string_number = caged.root_string();
+            let open = self.capo.get_fret(string_number) as usize;
+            let roots = strings[string_number - 1]
+                .degree_single_string
+                .iter()
+                .filter(|v| v.sw_tonic)
+                .filter_map(|v| {
+                    v.position.checked_sub(caged.frets_below_root())
+                });
+            for low in roots {
+                let high = low + SHAPE_FRETS - 1;
+                if low >= open && high < NUMBER_POSITION as usize {
+                    shapes.push(ArpeggioShape { caged, low, high });
+                }
+            }
+        }
+        shapes.sort_by_key(|s| s.low);
+        shapes
+    }
+
+    /// MIDI file of the tones of `shape` at `tempo` beats per minute, from
+    /// the lowest one an eighth apart, each ringing until one beat after the
+    /// last one begins, as when the notes are let ring
+    pub fn to_midi(&self, shape: ArpeggioShape, tempo: u16) -> MidiFile {
+        let mut keys: Vec<Semitones> = self
+            .get_string_combination()
+            .iter()
+            .flat_map(|s| s.degree_single_string.iter())
+            .filter(|v| shape.contains(v.position) && !v.sw_behind_capo)
+            .map(|v| v.pitch.midi())
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+        let step = NoteValue::Eighth.ticks();
+        let end =
+            keys.len().saturating_sub(1) as u32 * step + TICKS_PER_BEAT as u32;
+        let mut midi = MidiFile::new(tempo);
+        midi.notes = keys
+            .iter()
+            .enumerate()
+            .map(|(i, &key)| MidiNote {
+                start: i as u32 * step,
+                duration: end - i as u32 * step,
+                key,
+                velocity: VELOCITY_DEFAULT,
+            })
+            .collect();
+        midi
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    #[rstest(
+        chord,
+        tones,
+        case("C", vec!["R", "3", "5"]),
+        case("Am7", vec!["R", "b3", "5", "b7"]),
+        case("Bdim7", vec!["R", "b3", "b5", "bb7"]),
+        case("Ebmaj7", vec!["R", "3", "5", "7"])
+    )]
+    fn test_from_chord(chord: &str, tones: Vec<&str>) {
+        let chord = Chord::from_str(chord).unwrap();
+        let arpeggio = Arpeggio::from_chord(&chord, Tuning::E);
+        let by_intervals =
+            Arpeggio::new(arpeggio.root, arpeggio.intervals.clone(), Tuning::E);
+        let names: Vec<String> =
+            arpeggio.notes.iter().map(|n| n.to_string()).collect();
+        let names_by_intervals: Vec<String> =
+            by_intervals.notes.iter().map(|n| n.to_string()).collect();
+        assert_eq!(names, names_by_intervals);
+        let result: Vec<&str> = arpeggio
+            .notes
+            .iter()
+            .map(|n| arpeggio.chord_tone(n.pitch_class).unwrap())
+            .collect();
+        assert_eq!(result, tones);
+    }
+
+    #[test]
+    fn test_from_names() {
+        let arpeggio = Arpeggio::from_names("F#m7b5", "E").unwrap();
+        assert_eq!(arpeggio.notes.len(), 4);
+        let err = Arpeggio::from_names("Hm", "E").unwrap_err();
+        assert_eq!(err.to_string(), "Could not parse chord \"Hm\"");
+    }
+
+    #[test]
+    fn test_string_combination() {
+        let chord = Chord::from_str("G7").unwrap();
+        let arpeggio = Arpeggio::from_chord(&chord, Tuning::E);
+        let set: Vec<PitchClass> =
+            arpeggio.notes.iter().map(|n| n.pitch_class).collect();
+        for degree_all_strings in arpeggio.get_string_combination() {
+            for v in degree_all_strings.degree_single_string {
+                assert!(set.contains(&v.note.pitch_class));
+                assert_eq!(v.sw_tonic, v.note.pitch_class == PitchClass::G);
+            }
+        }
+        assert_eq!(arpeggio.chord_tone(PitchClass::A), None);
+    }
+
+    #[test]
+    fn test_shapes() {
+        let chord = Chord::from_str("C").unwrap();
+        let arpeggio = Arpeggio::from_chord(&chord, Tuning::E);
+        let shapes: Vec<(CagedShape, usize)> = arpeggio
+            .get_shapes()
+            .iter()
+            .map(|s| (s.caged, s.low))
+            .collect();
+        use CagedShape::*;
+        assert_eq!(
+            shapes,
+            vec![(C, 0), (A, 3), (G, 5), (E, 8), (D, 10), (C, 12), (A, 15)]
+        );
+    }
+
+    #[test]
+    fn test_shapes_capo() {
+        let chord = Chord::from_str("C").unwrap();
+        let mut arpeggio = Arpeggio::from_chord(&chord, Tuning::E);
+        arpeggio.capo = Capo::new(2).unwrap();
+        let shapes = arpeggio.get_shapes();
+        assert_eq!(shapes[0].caged, CagedShape::A);
+        assert!(shapes.iter().all(|s| s.low >= 2));
+    }
+
+    #[test]
+    fn test_to_midi() {
+        let chord = Chord::from_str("C").unwrap();
+        let arpeggio = Arpeggio::from_chord(&chord, Tuning::E);
+        let shape = arpeggio.get_shapes()[0];
+        let midi = arpeggio.to_midi(shape, 100);
+        assert_eq!(midi.tempo, 100);
+        let keys: Vec<Semitones> = midi.notes.iter().map(|n| n.key).collect();
+        // Open C chord, with the open strings
+        assert_eq!(keys, vec![40, 43, 48, 52, 55, 60, 64, 67]);
+        // The notes overlap and end together
+        let end = 7 * 240 + 480;
+        for (i, note) in midi.notes.iter().enumerate() {
+            assert_eq!(note.start, i as u32 * 240);
+            assert_eq!(note.start + note.duration, end);
+        }
+    }
+
+    #[test]
+    fn test_inversion() {
+        let chord = Chord::from_str("Dm").unwrap();
+        let mut arpeggio = Arpeggio::from_chord(&chord, Tuning::E);
+        assert_eq!(arpeggio.bass().to_string(), "D");
+        arpeggio.inversion = 1;
+        assert_eq!(arpeggio.bass().to_string(), "F");
+        arpeggio.inversion = 2;
+        assert_eq!(arpeggio.bass().to_string(), "A");
+    }
+}
@@ -0,0 +1,368 @@
+use super::sampler::Sampler;
+#[cfg(feature = "serde")]
+use crate::error::Result;
+use crate::fretboard::NUMBER_POSITION;
+use crate::pitch::Pitch;
+use crate::tuning::Tuning;
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::fs;
+use std::ops::RangeInclusive;
+#[cfg(feature = "serde")]
+use std::path;
+use ukebox::Semitones;
+
+/// Notes rendered by `renoise_guitar_split`, chromatic from E2
+const RENOISE_FIRST: Semitones = 40;
+const RENOISE_COUNT: Semitones = 32;
+
+/// A file of the sample bank and the note it plays
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SampleEntry {
+    /// File name, e.g. as rendered by Renoise
+    pub file: String,
+    pub pitch: Pitch,
+    /// Begin at 1 for the lowest string
+    pub string_number: usize,
+    pub fret: usize,
+    /// Velocity layer, 0 being the softest
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub velocity_layer: u8,
+}
+
+impl SampleEntry {
+    /// Name of the file in `temp_audio`, as loaded by the `Sampler`. The
+    /// layers above 0 end with `_v<layer>`. `None` below the octave of the
+    /// low E string.
+    pub fn data_file_name(&self) -> Option<String> {
+        let name = Sampler::file_name(self.pitch.midi())?;
+        Some(match self.velocity_layer {
+            0 => name,
+            layer => name.replace(".wav", &format!("_v{}.wav", layer)),
+        })
+    }
+}
+
+/// Manifest of the sample bank, to be written as json next to the samples
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SampleBank {
+    pub samples: Vec<SampleEntry>,
+}
+
+/// Problems found in a sample bank
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BankReport {
+    /// Notes of the range without sample, with their velocity layer
+    pub gaps: Vec<(u8, Pitch)>,
+    /// Notes with more than one sample in the same velocity layer
+    pub duplicates: Vec<(u8, Pitch)>,
+    /// Files whose string and fret do not play their pitch
+    pub mismatches: Vec<String>,
+}
+
+impl SampleBank {
+    /// Read the json manifest at `path`
+    #[cfg(feature = "serde")]
+    pub fn open<P: AsRef<path::Path>>(path: P) -> Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Write the manifest as json to `path`
+    #[cfg(feature = "serde")]
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The bank rendered by `renoise_guitar_split`: `guitar_split_Seq01.wav`
+    /// to `guitar_split_Seq32.wav`, chromatic from E2 to B4, each note being
+    /// played nearest to the nut
+    pub fn renoise_guitar_split(tuning: Tuning) -> Self {
+        let open_strings = tuning.get_open_strings();
+        let samples = (0..RENOISE_COUNT)
+            .map(|i| {
+                let key = RENOISE_FIRST + i;
+                let string_number = tuning.get_string_number(key);
+                let open = open_strings[string_number - 1].midi();
+                SampleEntry {
+                    file: format!("guitar_split_Seq{:02}.wav", i + 1),
+                    pitch: Pitch::from_midi(key),
+                    string_number,
+                    fret: (key - open) as usize,
+                    velocity_layer: 0,
+                }
+            })
+            .collect();
+        Self { samples }
+    }
+
+    /// The bank of a recording playing each string from the lowest, fret by
+    /// fret over `frets`, as cut by `Wav::slices`: `slice_01_E2.wav`,
+    /// `slice_02_F2.wav`, ...
+    pub fn chromatic(tuning: Tuning, frets: RangeInclusive<usize>) -> Self {
+        let samples = tuning
+            .get_open_strings()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, open)| {
+                frets.clone().map(move |fret| (i + 1, open.midi(), fret))
+            })
+            .enumerate()
+            .map(|(i, (string_number, open, fret))| {
+                let pitch = Pitch::from_midi(open + fret as Semitones);
+                SampleEntry {
+                    file: format!("slice_{:02}_{}.wav", i + 1, pitch),
+                    pitch,
+                    string_number,
+                    fret,
+                    velocity_layer: 0,
+                }
+            })
+            .collect();
+        Self { samples }
+    }
+
+    /// Notes of the guitar, from the lowest open string to the last fret
+    /// drawn on the highest string
+    pub fn guitar_range(tuning: Tuning) -> RangeInclusive<Semitones> {
+        let open_strings = tuning.get_open_strings();
+        let low = open_strings.first().map_or(0, |p| p.midi());
+        let high = open_strings.last().map_or(0, |p| p.midi());
+        low..=high + NUMBER_POSITION as Semitones - 1
+    }
+
+    /// Check that every note of `range` has a sample in each velocity layer,
+    /// once, and that the strings and frets match the pitches
+    pub fn validate(
+        &self,
+        tuning: Tuning,
+        range: RangeInclusive<Semitones>,
+    ) -> BankReport {
+        let open_strings = tuning.get_open_strings();
+        let mut count: BTreeMap<(u8, Semitones), usize> = BTreeMap::new();
+        let mut mismatches: Vec<String> = Vec::new();
+        for entry in &self.samples {
+            let key = entry.pitch.midi();
+            *count.entry((entry.velocity_layer, key)).or_insert(0) += 1;
+            let played = open_strings
+                .get(entry.string_number.wrapping_sub(1))
+                .map(|p| p.midi() as usize + entry.fret);
+            if played != Some(key as usize) {
+                mismatches.push(entry.file.clone());
+            }
+        }
+        let mut layers: Vec<u8> =
+            count.keys().map(|&(layer, _)| layer).collect();
+        layers.dedup();
+        if layers.is_empty() {
+            layers.push(0);
+        }
+        let gaps = layers
+            .iter()
+            .flat_map(|&layer| {
+                range
+                    .clone()
+                    .filter(|&key| !count.contains_key(&(layer, key)))
+                    .map(move |key| (layer, Pitch::from_midi(key)))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        let duplicates = count
+            .iter()
+            .filter(|(_, &n)| n > 1)
+            .map(|(&(layer, key), _)| (layer, Pitch::from_midi(key)))
+            .collect();
+        BankReport {
+            gaps,
+            duplicates,
+            mismatches,
+        }
+    }
+
+    /// Files to copy to `temp_audio`: the name in the bank and the name in
+    /// `temp_audio`
+    pub fn data_file_names(&self) -> Vec<(&str, String)> {
+        self.samples
+            .iter()
+            .filter_map(|e| Some((e.file.as_str(), e.data_file_name()?)))
+            .collect()
+    }
+}
+
+impl BankReport {
+    /// Return `true` without gap, duplicate or mismatch
+    pub fn is_valid(&self) -> bool {
+        self.gaps.is_empty()
+            && self.duplicates.is_empty()
+            && self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for BankReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (layer, pitch) in &self.gaps {
+            writeln!(f, "Missing sample of {} (layer {})", pitch, layer)?;
+        }
+        for (layer, pitch) in &self.duplicates {
+            writeln!(f, "More than one sample of {} (layer {})", pitch, layer)?;
+        }
+        for file in &self.mismatches {
+            writeln!(f, "String and fret of \"{}\" do not match", file)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn entry(
+        file: &str,
+        pitch: &str,
+        string_number: usize,
+        fret: usize,
+    ) -> SampleEntry {
+        SampleEntry {
+            file: file.to_string(),
+            pitch: Pitch::from_str(pitch).unwrap(),
+            string_number,
+            fret,
+            velocity_layer: 0,
+        }
+    }
+
+    #[test]
+    fn test_renoise_guitar_split() {
+        let bank = SampleBank::renoise_guitar_split(Tuning::E);
+        let names = bank.data_file_names();
+        assert_eq!(names.len(), 32);
+        // The names written by the index counter of `copy_audio`
+        assert_eq!(
+            names[0],
+            ("guitar_split_Seq01.wav", "guitar_e_0.wav".to_string())
+        );
+        assert_eq!(
+            names[8],
+            ("guitar_split_Seq09.wav", "guitar_c_1.wav".to_string())
+        );
+        assert_eq!(
+            names[31],
+            ("guitar_split_Seq32.wav", "guitar_b_2.wav".to_string())
+        );
+        let c4 = &bank.samples[20];
+        assert_eq!((c4.string_number, c4.fret), (5, 1));
+        let report = bank.validate(Tuning::E, 40..=71);
+        assert!(report.is_valid(), "{}", report);
+    }
+
+    #[test]
+    fn test_chromatic() {
+        let bank = SampleBank::chromatic(Tuning::E, 0..=4);
+        assert_eq!(bank.samples.len(), 30);
+        assert_eq!(bank.samples[0].file, "slice_01_E2.wav");
+        assert_eq!(bank.samples[5].file, "slice_06_A2.wav");
+        let last = &bank.samples[29];
+        assert_eq!((last.string_number, last.fret), (6, 4));
+        // The fourth fret of the G string is the open B string
+        let report = bank.validate(Tuning::E, 40..=68);
+        assert!(report.gaps.is_empty());
+        assert!(report.mismatches.is_empty());
+        assert_eq!(
+            report.duplicates,
+            vec![(0, Pitch::from_str("B3").unwrap())]
+        );
+    }
+
+    #[test]
+    fn test_guitar_range() {
+        assert_eq!(SampleBank::guitar_range(Tuning::E), 40..=82);
+        let bank = SampleBank::renoise_guitar_split(Tuning::E);
+        let report =
+            bank.validate(Tuning::E, SampleBank::guitar_range(Tuning::E));
+        // C5 to A#5 are left to the resampling
+        assert_eq!(report.gaps.len(), 11);
+        assert_eq!(report.gaps[0], (0, Pitch::from_str("C5").unwrap()));
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut bank = SampleBank {
+            samples: vec![
+                entry("a.wav", "E2", 1, 0),
+                entry("b.wav", "F2", 1, 1),
+                entry("c.wav", "F2", 1, 1),
+                entry("d.wav", "A2", 1, 4),
+                entry("e.wav", "G2", 7, 0),
+            ],
+        };
+        let mut soft = entry("f.wav", "E2", 1, 0);
+        soft.velocity_layer = 1;
+        bank.samples.push(soft);
+        let report = bank.validate(Tuning::E, 40..=42);
+        let gaps: Vec<(u8, String)> = report
+            .gaps
+            .iter()
+            .map(|(l, p)| (*l, p.to_string()))
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![
+                (0, "F#2".to_string()),
+                (1, "F2".to_string()),
+                (1, "F#2".to_string())
+            ]
+        );
+        assert_eq!(
+            report.duplicates,
+            vec![(0, Pitch::from_str("F2").unwrap())]
+        );
+        assert_eq!(report.mismatches, vec!["d.wav", "e.wav"]);
+        assert!(!report.is_valid());
+        assert_eq!(
+            report.to_string().lines().nth(3),
+            Some("More than one sample of F2 (layer 0)")
+        );
+        assert_eq!(
+            bank.samples[5].data_file_name().as_deref(),
+            Some("guitar_e_0_v1.wav")
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let report = SampleBank::default().validate(Tuning::E, 40..=41);
+        assert_eq!(report.gaps.len(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let json = r#"{"samples":[{"file":"a.wav","pitch":"C#3","string_number":2,"fret":4}]}"#;
+        let bank: SampleBank = serde_json::from_str(json).unwrap();
+        assert_eq!(bank.samples[0].velocity_layer, 0);
+        assert_eq!(bank.samples[0].pitch, Pitch::from_str("C#3").unwrap());
+        let back = serde_json::to_string(&bank).unwrap();
+        assert_eq!(serde_json::from_str::<SampleBank>(&back).unwrap(), bank);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_open_save() {
+        let path = std::env::temp_dir().join("guitar_scale_manifest.json");
+        let bank = SampleBank::chromatic(Tuning::E, 0..=2);
+        bank.save(&path).unwrap();
+        assert_eq!(SampleBank::open(&path).unwrap(), bank);
+        fs::write(&path, "{").unwrap();
+        let err = SampleBank::open(&path).unwrap_err();
+        assert!(matches!(err, crate::error::Error::Json(_)));
+        fs::remove_file(&path).unwrap();
+        let err = SampleBank::open(&path).unwrap_err();
+        assert!(matches!(err, crate::error::Error::Io(_)));
+    }
+}
@@ -0,0 +1,149 @@
+use super::vorbis;
+use super::wav::Wav;
+use crate::error::Result;
+use std::fs;
+use std::path;
+
+/// Extension of the files read by `convert_dir`
+const WAV_EXTENSION: &str = "wav";
+
+/// Format of a rendered audio file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AudioFormat {
+    /// 16-bit PCM
+    Wav,
+    /// Ogg Vorbis, encoded by the crate itself
+    OggVorbis,
+}
+
+impl AudioFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            AudioFormat::Wav => WAV_EXTENSION,
+            AudioFormat::OggVorbis => "ogg",
+        }
+    }
+}
+
+/// How rendered audio is written
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExportSettings {
+    pub format: AudioFormat,
+    /// Bits per second of the compressed formats. The encoder may use less
+    /// on quiet or simple audio.
+    pub bitrate: u32,
+}
+
+impl Default for ExportSettings {
+    fn default() -> Self {
+        Self {
+            format: AudioFormat::OggVorbis,
+            bitrate: 96_000,
+        }
+    }
+}
+
+impl Wav {
+    /// Bytes of the audio in the format of `settings`
+    pub fn encode(&self, settings: ExportSettings) -> Vec<u8> {
+        match settings.format {
+            AudioFormat::Wav => self.to_bytes(),
+            AudioFormat::OggVorbis => vorbis::encode(self, settings.bitrate),
+        }
+    }
+
+    /// Write the audio to `path` in the format of `settings`
+    pub fn export<P: AsRef<path::Path>>(
+        &self,
+        path: P,
+        settings: ExportSettings,
+    ) -> Result<()> {
+        fs::write(path, self.encode(settings))?;
+        Ok(())
+    }
+}
+
+/// Convert every wav file of `input` to the format of `settings`, into
+/// `output` under the same names. Return the files written, by name.
+pub fn convert_dir<P: AsRef<path::Path>, Q: AsRef<path::Path>>(
+    input: P,
+    output: Q,
+    settings: ExportSettings,
+) -> Result<Vec<path::PathBuf>> {
+    let mut files: Vec<path::PathBuf> = Vec::new();
+    for entry in fs::read_dir(input)? {
+        let path = entry?.path();
+        let is_wav = path.extension().map_or(false, |e| {
+            e.to_string_lossy().eq_ignore_ascii_case(WAV_EXTENSION)
+        });
+        if is_wav && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    fs::create_dir_all(&output)?;
+    files
+        .iter()
+        .map(|file| {
+            let name = file
+                .with_extension(settings.format.extension())
+                .file_name()
+                .unwrap_or_default()
+                .to_owned();
+            let path = output.as_ref().join(name);
+            Wav::open(file)?.export(&path, settings)?;
+            Ok(path)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::wav::SAMPLE_RATE;
+    use std::f32::consts::PI;
+
+    fn sine() -> Wav {
+        Wav {
+            sample_rate: SAMPLE_RATE,
+            samples: (0..SAMPLE_RATE / 2)
+                .map(|i| (2.0 * PI * 330.0 * i as f32 / 44_100.0).sin() / 2.0)
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let wav = sine();
+        let settings = ExportSettings {
+            format: AudioFormat::Wav,
+            ..ExportSettings::default()
+        };
+        assert_eq!(wav.encode(settings), wav.to_bytes());
+        let ogg = wav.encode(ExportSettings::default());
+        assert_eq!(&ogg[..4], b"OggS");
+        assert!(ogg.len() < wav.to_bytes().len() / 4);
+    }
+
+    #[test]
+    fn test_convert_dir() {
+        let dir = std::env::temp_dir().join("guitar_scale_test_convert");
+        let output = dir.join("ogg");
+        std::fs::create_dir_all(&dir).unwrap();
+        sine().save(dir.join("guitar_e_0.wav")).unwrap();
+        sine().save(dir.join("guitar_a_0.WAV")).unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        let files =
+            convert_dir(&dir, &output, ExportSettings::default()).unwrap();
+        let names: Vec<String> = files
+            .iter()
+            .map(|f| f.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        let written = files.iter().all(|f| f.is_file());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(names, vec!["guitar_a_0.ogg", "guitar_e_0.ogg"]);
+        assert!(written);
+    }
+}
@@ -0,0 +1,100 @@
+use super::wav::Wav;
+use crate::midi::{MidiFile, TEMPO_MIN, TICKS_PER_BEAT};
+use ukebox::Semitones;
+
+/// Seconds a note rings after its end, fading out
+pub const RELEASE: f64 = 0.08;
+/// Highest peak of a rendering, leaving some headroom
+const PEAK: f32 = 0.9;
+
+pub trait TraitInstrument {
+    /// Samples of a note (a MIDI note number, 60 being C4) lasting `length`
+    /// samples at `sample_rate`, `velocity` going from 0 to 127
+    fn render_note(
+        &self,
+        key: Semitones,
+        velocity: u8,
+        length: usize,
+        sample_rate: u32,
+    ) -> Vec<f32>;
+
+    /// Audio of the notes of a MIDI file, at its tempo
+    fn render(&self, midi: &MidiFile, sample_rate: u32) -> Wav {
+        let seconds_per_tick =
+            60.0 / (midi.tempo.max(TEMPO_MIN) as f64 * TICKS_PER_BEAT as f64);
+        let rate = sample_rate as f64;
+        let release = (RELEASE * rate) as usize;
+        let mut wav = Wav::new(sample_rate);
+        for note in &midi.notes {
+            let offset = (note.start as f64 * seconds_per_tick * rate) as usize;
+            let length = (note.duration as f64 * seconds_per_tick * rate)
+                as usize
+                + release;
+            let mut samples =
+                self.render_note(note.key, note.velocity, length, sample_rate);
+            samples.truncate(length);
+            fade_out(&mut samples, release);
+            wav.mix(&samples, offset);
+        }
+        wav.limit(PEAK);
+        wav
+    }
+}
+
+/// Fade the last `length` samples out
+fn fade_out(samples: &mut [f32], length: usize) {
+    let length = length.min(samples.len());
+    let start = samples.len() - length;
+    for (i, sample) in samples[start..].iter_mut().enumerate() {
+        *sample *= 1.0 - i as f32 / length as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::MidiNote;
+
+    /// A square wave as loud as the velocity
+    struct Square;
+
+    impl TraitInstrument for Square {
+        fn render_note(
+            &self,
+            _key: Semitones,
+            velocity: u8,
+            length: usize,
+            _sample_rate: u32,
+        ) -> Vec<f32> {
+            vec![velocity as f32 / 127.0; length]
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let mut midi = MidiFile::new(60);
+        for i in 0..2 {
+            midi.notes.push(MidiNote {
+                start: i * TICKS_PER_BEAT as u32,
+                duration: TICKS_PER_BEAT as u32,
+                key: 60,
+                velocity: 127,
+            });
+        }
+        let wav = Square.render(&midi, 1000);
+        // Two seconds and the release of the last note
+        assert_eq!(wav.samples.len(), 2080);
+        // The release of the first note overlapping the second note peaks
+        assert_eq!(wav.samples[1000], PEAK);
+        assert!(wav.samples.iter().all(|&s| s <= PEAK));
+        assert_eq!(wav.samples[500], wav.samples[1500]);
+        assert!(wav.samples[2079] < 0.1);
+    }
+
+    #[test]
+    fn test_fade_out() {
+        let mut samples = vec![1.0; 6];
+        fade_out(&mut samples, 4);
+        assert_eq!(samples, vec![1.0, 1.0, 1.0, 0.75, 0.5, 0.25]);
+    }
+}
@@ -0,0 +1,19 @@
+mod bank;
+mod export;
+mod instrument;
+mod ogg;
+mod pluck;
+mod sampler;
+mod slicer;
+mod tuner;
+mod vorbis;
+mod wav;
+
+pub use self::bank::{BankReport, SampleBank, SampleEntry};
+pub use self::export::{convert_dir, AudioFormat, ExportSettings};
+pub use self::instrument::{TraitInstrument, RELEASE};
+pub use self::pluck::Pluck;
+pub use self::sampler::{Sampler, FIRST_OCTAVE};
+pub use self::slicer::SliceSettings;
+pub use self::tuner::{DetectedPitch, SampleCheck, SampleStatus};
+pub use self::wav::{resample, ParseWavError, Wav, SAMPLE_RATE};
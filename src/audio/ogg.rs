@@ -0,0 +1,185 @@
+use std::iter;
+
+/// Pages of an Ogg stream, with a single logical stream
+/// https://xiph.org/ogg/doc/framing.html
+pub(crate) struct OggWriter {
+    serial: u32,
+    sequence: u32,
+    pub bytes: Vec<u8>,
+    /// Packets of the page being filled, their lacing values and the
+    /// position at the end of the last one
+    packets: Vec<u8>,
+    lacing: Vec<u8>,
+    granule: u64,
+    /// The page being filled begins with the end of a packet
+    continued: bool,
+}
+
+const FLAG_CONTINUED: u8 = 0x01;
+const FLAG_FIRST: u8 = 0x02;
+const FLAG_LAST: u8 = 0x04;
+/// Most segments in a page
+const MAX_SEGMENTS: usize = 255;
+/// Size from which a page is written out
+const PAGE_SIZE: usize = 4096;
+const CRC_POLYNOMIAL: u32 = 0x04c1_1db7;
+
+impl OggWriter {
+    pub fn new(serial: u32) -> Self {
+        Self {
+            serial,
+            sequence: 0,
+            bytes: Vec::new(),
+            packets: Vec::new(),
+            lacing: Vec::new(),
+            granule: 0,
+            continued: false,
+        }
+    }
+
+    /// Add a packet ending at the position `granule`, the page being
+    /// written out first if it is full. A packet longer than a page goes on
+    /// over the next pages.
+    pub fn packet(&mut self, packet: &[u8], granule: u64) {
+        let segments = packet.len() / 255 + 1;
+        if self.lacing.len() + segments > MAX_SEGMENTS
+            || self.packets.len() >= PAGE_SIZE
+        {
+            self.flush(false);
+        }
+        let mut rest = packet;
+        while rest.len() / 255 + self.lacing.len() >= MAX_SEGMENTS {
+            let free = MAX_SEGMENTS - self.lacing.len();
+            self.lacing.extend(iter::repeat(255).take(free));
+            self.packets.extend_from_slice(&rest[..255 * free]);
+            rest = &rest[255 * free..];
+            // No packet ends on the page
+            self.granule = u64::MAX;
+            self.flush(false);
+            self.continued = true;
+        }
+        self.lacing.extend(iter::repeat(255).take(rest.len() / 255));
+        self.lacing.push((rest.len() % 255) as u8);
+        self.packets.extend_from_slice(rest);
+        self.granule = granule;
+    }
+
+    /// Write out the page being filled, `last` for the end of the stream
+    pub fn flush(&mut self, last: bool) {
+        if self.lacing.is_empty() && !last {
+            return;
+        }
+        debug_assert!(self.lacing.len() <= MAX_SEGMENTS);
+        let mut flags = 0;
+        if self.continued {
+            flags |= FLAG_CONTINUED;
+        }
+        if self.sequence == 0 {
+            flags |= FLAG_FIRST;
+        }
+        if last {
+            flags |= FLAG_LAST;
+        }
+        let start = self.bytes.len();
+        self.bytes.extend_from_slice(b"OggS");
+        self.bytes.push(0); // Version
+        self.bytes.push(flags);
+        self.bytes.extend_from_slice(&self.granule.to_le_bytes());
+        self.bytes.extend_from_slice(&self.serial.to_le_bytes());
+        self.bytes.extend_from_slice(&self.sequence.to_le_bytes());
+        self.bytes.extend_from_slice(&0u32.to_le_bytes()); // Checksum
+        self.bytes.push(self.lacing.len() as u8);
+        self.bytes.append(&mut self.lacing);
+        self.bytes.append(&mut self.packets);
+        let crc = crc(&self.bytes[start..]);
+        self.bytes[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+        self.sequence += 1;
+        self.continued = false;
+    }
+}
+
+/// Checksum of a page, its own field being zero
+fn crc(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0u32, |mut crc, &byte| {
+        crc ^= (byte as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ CRC_POLYNOMIAL
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryInto;
+
+    #[test]
+    fn test_crc() {
+        // CRC-32/POSIX without its final xor
+        assert_eq!(crc(b""), 0);
+        assert_eq!(crc(b"123456789"), 0x89a1_897f);
+    }
+
+    #[test]
+    fn test_pages() {
+        let mut ogg = OggWriter::new(7);
+        ogg.packet(&[1; 300], 0);
+        ogg.flush(false);
+        ogg.packet(&[2; 10], 1024);
+        ogg.packet(&[3; 255], 2048);
+        ogg.flush(true);
+        let bytes = &ogg.bytes;
+        assert_eq!(&bytes[..4], b"OggS");
+        assert_eq!(bytes[5], FLAG_FIRST);
+        // Lacing of 300 bytes: 255 and 45
+        assert_eq!(&bytes[26..29], &[2, 255, 45]);
+        let second = 29 + 300;
+        assert_eq!(&bytes[second..second + 4], b"OggS");
+        assert_eq!(bytes[second + 5], FLAG_LAST);
+        let granule = u64::from_le_bytes(
+            bytes[second + 6..second + 14].try_into().unwrap(),
+        );
+        assert_eq!(granule, 2048);
+        // A packet of 255 bytes ends with an empty segment
+        assert_eq!(&bytes[second + 26..second + 30], &[3, 10, 255, 0]);
+        assert_eq!(bytes.len(), second + 30 + 265);
+    }
+
+    #[test]
+    fn test_packet_over_pages() {
+        // 255 full segments fill a page, the packet ends on the next one
+        let mut ogg = OggWriter::new(7);
+        ogg.packet(&[1; 10], 0);
+        ogg.packet(&[2; 255 * 255 + 10], 1024);
+        ogg.packet(&[3; 255 * 255], 2048);
+        ogg.flush(true);
+        let mut pages = Vec::new();
+        let mut bytes = &ogg.bytes[..];
+        while !bytes.is_empty() {
+            assert_eq!(&bytes[..4], b"OggS");
+            let granule = u64::from_le_bytes(bytes[6..14].try_into().unwrap());
+            let count = bytes[26] as usize;
+            let lacing = bytes[27..27 + count].to_vec();
+            let size: usize = lacing.iter().map(|&l| l as usize).sum();
+            pages.push((bytes[5], granule, lacing));
+            bytes = &bytes[27 + count + size..];
+        }
+        let full = vec![255; MAX_SEGMENTS];
+        assert_eq!(
+            pages,
+            vec![
+                (FLAG_FIRST, 0, vec![10]),
+                (0, u64::MAX, full.clone()),
+                (FLAG_CONTINUED, 1024, vec![10]),
+                (0, u64::MAX, full),
+                // A packet of 255 full segments ends with an empty segment
+                (FLAG_CONTINUED | FLAG_LAST, 2048, vec![0]),
+            ]
+        );
+    }
+}
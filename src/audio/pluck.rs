@@ -0,0 +1,230 @@
+use super::instrument::TraitInstrument;
+use crate::tuning::Tuning;
+use crate::NUMBER_STRING;
+use ukebox::Semitones;
+
+/// Level a note has fallen to at the end of its decay time, -60 dB
+const DECAY_LEVEL: f64 = 0.001;
+/// Decay time added for each string below the highest one, the thick
+/// strings ringing longer
+const DECAY_PER_STRING: f64 = 0.2;
+/// Brightness of the pick on the lowest (wound) string, up to 1 on the
+/// highest one
+const BRIGHTNESS_LOW: f32 = 0.5;
+
+/// Plucked string synthesis (Karplus-Strong), to get audio without the
+/// sample bank. A burst of noise runs around a delay line as long as one
+/// period of the note, losing its highs at each turn as a string does.
+/// https://en.wikipedia.org/wiki/Karplus%E2%80%93Strong_string_synthesis
+#[derive(Debug, Clone, Copy)]
+pub struct Pluck {
+    pub tuning: Tuning,
+    /// Seconds for a note of the highest string to fall by 60 dB
+    pub decay: f64,
+    /// Where the string is picked, from 0 at the bridge to 1 at the nut.
+    /// Picking near the middle cancels the even harmonics and sounds
+    /// rounder. `None` to keep every harmonic.
+    pub pick_position: Option<f64>,
+}
+
+impl Default for Pluck {
+    fn default() -> Self {
+        Self {
+            tuning: Tuning::E,
+            decay: 2.0,
+            pick_position: None,
+        }
+    }
+}
+
+impl Pluck {
+    /// String playing a note, the highest one reaching it (begin at 1 for
+    /// the lowest string)
+    pub fn string_number(self, key: Semitones) -> usize {
+        self.tuning.get_string_number(key)
+    }
+}
+
+impl TraitInstrument for Pluck {
+    fn render_note(
+        &self,
+        key: Semitones,
+        velocity: u8,
+        length: usize,
+        sample_rate: u32,
+    ) -> Vec<f32> {
+        let frequency = 440.0 * 2f64.powf((key as f64 - 69.0) / 12.0);
+        let below = NUMBER_STRING as usize - self.string_number(key);
+        let brightness = 1.0
+            - (1.0 - BRIGHTNESS_LOW) * below as f32
+                / (NUMBER_STRING - 1) as f32;
+        let decay = self.decay * (1.0 + DECAY_PER_STRING * below as f64);
+        // The averaging filter delays half a sample, and an all-pass filter
+        // tunes the fraction of sample left
+        let period = sample_rate as f64 / frequency - 0.5;
+        let delay = (period as usize).max(2);
+        let fraction = period - delay as f64;
+        let allpass = ((1.0 - fraction) / (1.0 + fraction)) as f32;
+        let gain = DECAY_LEVEL.powf(1.0 / (decay * frequency)) as f32;
+        // Noise made darker on the thick strings, then combed by the pick
+        let mut seed = 0x9e37_79b9u32 ^ key as u32;
+        let mut low = 0.0;
+        let mut line: Vec<f32> = (0..delay)
+            .map(|_| {
+                low += brightness * (noise(&mut seed) - low);
+                low
+            })
+            .collect();
+        if let Some(position) = self.pick_position {
+            let notch = (position.clamp(0.0, 1.0) * delay as f64) as usize;
+            let excitation = line.clone();
+            for i in notch..delay {
+                line[i] -= excitation[i - notch];
+            }
+        }
+        let level = velocity as f32 / 127.0;
+        let mut samples: Vec<f32> = Vec::with_capacity(length);
+        let mut previous = 0.0;
+        // Input and output of the all-pass filter on the previous sample
+        let (mut x1, mut y1) = (0.0, 0.0);
+        for n in 0..length {
+            let i = n % delay;
+            let current = line[i];
+            samples.push(current * level);
+            let averaged = gain * 0.5 * (current + previous);
+            previous = current;
+            let tuned = allpass * averaged + x1 - allpass * y1;
+            x1 = averaged;
+            y1 = tuned;
+            line[i] = tuned;
+        }
+        samples
+    }
+}
+
+/// White noise between -1 and 1 (xorshift), the same for each note
+fn noise(seed: &mut u32) -> f32 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 17;
+    *seed ^= *seed << 5;
+    *seed as f32 / u32::MAX as f32 * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arpeggio::Arpeggio;
+    use crate::audio::instrument::RELEASE;
+    use crate::audio::wav::SAMPLE_RATE;
+    use crate::midi::TICKS_PER_BEAT;
+    use rstest::rstest;
+
+    /// Frequency from the periodicity of the samples: the lag, near the
+    /// expected one, where they look the most like themselves
+    fn frequency(samples: &[f32], expected: f64) -> f64 {
+        let rate = SAMPLE_RATE as f64;
+        let guess = rate / expected;
+        let lags = (guess * 0.9) as usize..=(guess * 1.1) as usize;
+        let window = &samples[2000..6000];
+        let score = |lag: usize| -> f32 {
+            window
+                .iter()
+                .zip(&samples[2000 + lag..])
+                .map(|(a, b)| a * b)
+                .sum()
+        };
+        let lag = lags.max_by(|&a, &b| score(a).total_cmp(&score(b))).unwrap();
+        // Parabolic interpolation between the neighbouring lags
+        let (a, b, c) = (score(lag - 1), score(lag), score(lag + 1));
+        let shift = 0.5 * (a - c) / (a - 2.0 * b + c);
+        rate / (lag as f64 + shift as f64)
+    }
+
+    fn energy(samples: &[f32]) -> f32 {
+        samples.iter().map(|s| s * s).sum()
+    }
+
+    #[rstest(
+        key,
+        string_number,
+        case(40, 1),
+        case(44, 1),
+        case(45, 2),
+        case(60, 5),
+        case(64, 6),
+        case(88, 6),
+        case(30, 1)
+    )]
+    fn test_string_number(key: Semitones, string_number: usize) {
+        assert_eq!(Pluck::default().string_number(key), string_number);
+    }
+
+    #[rstest(key, case(40), case(57), case(64), case(76), case(88))]
+    fn test_pitch(key: Semitones) {
+        let expected = 440.0 * 2f64.powf((key as f64 - 69.0) / 12.0);
+        let samples = Pluck::default().render_note(key, 100, 8000, SAMPLE_RATE);
+        let f = frequency(&samples, expected);
+        // Less than 5 cents away
+        let cents = 1200.0 * (f / expected).log2();
+        assert!(cents.abs() < 5.0, "{} Hz for {} Hz", f, expected);
+    }
+
+    #[test]
+    fn test_decay() {
+        let pluck = Pluck::default();
+        let rate = SAMPLE_RATE as usize;
+        let high = pluck.render_note(76, 127, 2 * rate, SAMPLE_RATE);
+        // Below -30 dB after one second, the highs fading first
+        let ratio = energy(&high[rate..rate + 1000]) / energy(&high[..1000]);
+        assert!(ratio < 0.001, "{}", ratio);
+        let longer = Pluck {
+            decay: 4.0,
+            ..pluck
+        };
+        let long = longer.render_note(76, 127, 2 * rate, SAMPLE_RATE);
+        let long_ratio =
+            energy(&long[rate..rate + 1000]) / energy(&long[..1000]);
+        assert!(long_ratio > ratio);
+        // The low E string rings longer
+        let low = pluck.render_note(40, 127, 2 * rate, SAMPLE_RATE);
+        let low_ratio = energy(&low[rate..rate + 1000]) / energy(&low[..1000]);
+        assert!(low_ratio > ratio);
+    }
+
+    #[test]
+    fn test_pick_position() {
+        let pluck = Pluck::default();
+        let bridge = Pluck {
+            pick_position: Some(0.1),
+            ..pluck
+        };
+        let a = pluck.render_note(57, 127, 4000, SAMPLE_RATE);
+        let b = bridge.render_note(57, 127, 4000, SAMPLE_RATE);
+        assert_ne!(a, b);
+        assert_eq!(a, pluck.render_note(57, 127, 4000, SAMPLE_RATE));
+        // The velocity scales the samples
+        let soft = pluck.render_note(57, 127 / 2, 4000, SAMPLE_RATE);
+        assert!(energy(&soft) < energy(&a) / 3.0);
+    }
+
+    #[test]
+    fn test_render_arpeggio() {
+        let arpeggio = Arpeggio::from_names("Am", "E").unwrap();
+        let shape = arpeggio.get_shapes()[0];
+        let midi = arpeggio.to_midi(shape, 120);
+        let wav = Pluck::default().render(&midi, SAMPLE_RATE);
+        // Samples of a time in ticks, at 120 beats per minute
+        let samples = |ticks: u32, release: f64| -> usize {
+            let seconds = ticks as f64 / TICKS_PER_BEAT as f64 / 2.0;
+            ((seconds + release) * SAMPLE_RATE as f64) as usize
+        };
+        // Every note rings up to one beat after the last one begins
+        let first = midi.notes[0];
+        assert_eq!(wav.samples.len(), samples(first.duration, RELEASE));
+        // The first note still rings under the last one
+        let last = midi.notes.last().unwrap();
+        assert!(first.start + first.duration > last.start);
+        let start = samples(last.start, 0.0);
+        assert!(energy(&wav.samples[start..start + 1000]) > 0.0);
+    }
+}
@@ -0,0 +1,185 @@
+use super::instrument::TraitInstrument;
+use super::wav::{resample, Wav};
+use crate::error::Result;
+use crate::midi::KEY_MAX;
+use crate::note::{Note, TraitGenerate, TraitNoteSemitones};
+use crate::pitch::{Octave, Pitch};
+use std::collections::BTreeMap;
+use std::path;
+use ukebox::Semitones;
+
+/// Octave numbered 0 in the names of the sample bank, the octave of the
+/// low E string
+pub const FIRST_OCTAVE: Octave = 2;
+const PITCH_CLASS_COUNT: Semitones = 12;
+
+/// Recorded notes played back at other pitches, so that a few samples
+/// cover the whole neck
+#[derive(Debug, Clone, Default)]
+pub struct Sampler {
+    /// Samples by MIDI note number
+    pub samples: BTreeMap<Semitones, Wav>,
+}
+
+impl Sampler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Name of the file of a note in the sample bank,
+    /// `guitar_<data_name>_<octave>.wav` as written by the `copy_audio`
+    /// example, e.g. `guitar_c_sharp_1.wav` for C#3. `None` below the first
+    /// octave.
+    pub fn file_name(key: Semitones) -> Option<String> {
+        let pitch = Pitch::from_midi(key);
+        let octave = pitch.octave - FIRST_OCTAVE;
+        if octave < 0 {
+            return None;
+        }
+        Some(format!("guitar_{}_{}.wav", pitch.note.data_name(), octave))
+    }
+
+    /// Load the sample bank of `dir`, the missing notes being left out
+    pub fn load<P: AsRef<path::Path>>(dir: P) -> Result<Self> {
+        let mut sampler = Self::new();
+        for key in 0..=KEY_MAX {
+            if let Some(name) = Self::file_name(key) {
+                let path = dir.as_ref().join(name);
+                if path.is_file() {
+                    sampler.samples.insert(key, Wav::open(path)?);
+                }
+            }
+        }
+        Ok(sampler)
+    }
+
+    /// Sample of the nearest note to `key` and the semitones to shift it,
+    /// the lower sample being taken on a tie
+    fn nearest(&self, key: Semitones) -> Option<(&Wav, i32)> {
+        let below = self.samples.range(..=key).next_back();
+        let above = self.samples.range(key..).next();
+        let (&k, wav) = match (below, above) {
+            (Some(b), Some(a)) if a.0 - key < key - b.0 => a,
+            (Some(b), _) => b,
+            (None, a) => a?,
+        };
+        Some((wav, key as i32 - k as i32))
+    }
+
+    /// Notes without their own sample, between the lowest and the highest
+    /// sample
+    pub fn gaps(&self) -> Vec<Note> {
+        match (self.samples.keys().next(), self.samples.keys().next_back()) {
+            (Some(&low), Some(&high)) => (low..=high)
+                .filter(|k| !self.samples.contains_key(k))
+                .map(|k| Note::from_semitones(k % PITCH_CLASS_COUNT))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl TraitInstrument for Sampler {
+    /// The nearest sample, played faster or slower to reach the pitch. A
+    /// silence if the sampler is empty.
+    fn render_note(
+        &self,
+        key: Semitones,
+        velocity: u8,
+        length: usize,
+        sample_rate: u32,
+    ) -> Vec<f32> {
+        let (wav, shift) = match self.nearest(key) {
+            Some(nearest) => nearest,
+            None => return vec![0.0; length],
+        };
+        let ratio = wav.sample_rate as f64 / sample_rate as f64
+            * 2f64.powf(shift as f64 / PITCH_CLASS_COUNT as f64);
+        let gain = velocity as f32 / 127.0;
+        let mut samples = resample(&wav.samples, ratio);
+        samples.resize(length, 0.0);
+        samples.iter_mut().for_each(|s| *s *= gain);
+        samples
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::wav::SAMPLE_RATE;
+    use rstest::rstest;
+    use std::f32::consts::PI;
+
+    /// One second of a sine at the frequency of `key`
+    fn sine(key: Semitones) -> Wav {
+        let frequency = 440.0 * 2f32.powf((key as f32 - 69.0) / 12.0);
+        Wav {
+            sample_rate: SAMPLE_RATE,
+            samples: (0..SAMPLE_RATE)
+                .map(|i| (2.0 * PI * frequency * i as f32 / 44_100.0).sin())
+                .collect(),
+        }
+    }
+
+    /// Frequency from the zero crossings going up
+    fn frequency(samples: &[f32], sample_rate: u32) -> f32 {
+        let crossings = samples
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        crossings as f32 * sample_rate as f32 / samples.len() as f32
+    }
+
+    #[rstest(
+        key,
+        name,
+        case(40, Some("guitar_e_0.wav")),
+        case(49, Some("guitar_c_sharp_1.wav")),
+        case(71, Some("guitar_b_2.wav")),
+        case(35, None)
+    )]
+    fn test_file_name(key: Semitones, name: Option<&str>) {
+        assert_eq!(Sampler::file_name(key).as_deref(), name);
+    }
+
+    #[test]
+    fn test_nearest() {
+        let mut sampler = Sampler::new();
+        assert!(sampler.nearest(60).is_none());
+        sampler.samples.insert(57, sine(57));
+        sampler.samples.insert(64, sine(64));
+        let shifts: Vec<i32> = [50, 57, 60, 61, 70]
+            .iter()
+            .map(|&k| sampler.nearest(k).unwrap().1)
+            .collect();
+        assert_eq!(shifts, vec![-7, 0, 3, -3, 6]);
+        assert_eq!(sampler.gaps().len(), 6);
+    }
+
+    #[test]
+    fn test_render_missing_note() {
+        let mut sampler = Sampler::new();
+        sampler.samples.insert(57, sine(57));
+        // A4 from the A3 sample, one octave higher
+        let samples = sampler.render_note(69, 127, 22_050, SAMPLE_RATE);
+        assert_eq!(samples.len(), 22_050);
+        let f = frequency(&samples, SAMPLE_RATE);
+        assert!((f - 440.0).abs() < 5.0, "{}", f);
+        // At another sample rate
+        let samples = sampler.render_note(57, 127, 8000, 16_000);
+        let f = frequency(&samples, 16_000);
+        assert!((f - 220.0).abs() < 5.0, "{}", f);
+    }
+
+    #[test]
+    fn test_load() {
+        let dir = std::env::temp_dir().join("guitar_scale_test_sampler");
+        std::fs::create_dir_all(&dir).unwrap();
+        sine(40).save(dir.join("guitar_e_0.wav")).unwrap();
+        sine(52).save(dir.join("guitar_e_1.wav")).unwrap();
+        let sampler = Sampler::load(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sampler.samples.keys().collect::<Vec<_>>(), vec![&40, &52]);
+        assert_eq!(sampler.gaps().len(), 11);
+    }
+}
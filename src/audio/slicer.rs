@@ -0,0 +1,189 @@
+use super::wav::Wav;
+
+/// Seconds over which the level is measured
+const FRAME: f64 = 0.005;
+/// Frames over which a rise of the level is an onset
+const RISE_FRAMES: usize = 3;
+/// Seconds of the fade at the end of a slice
+const FADE: f64 = 0.01;
+/// Level of a frame of zeros, in dB
+const LEVEL_MIN: f32 = -200.0;
+
+/// How a recording of single notes is cut into samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SliceSettings {
+    /// Rise of the level within 15 ms marking a new note, in dB
+    pub onset_rise: f32,
+    /// Level of the silence under the peak, in dB: of the recording for the
+    /// onsets, of each slice for the trimming
+    pub silence: f32,
+    /// Shortest time between two notes, in seconds
+    pub min_length: f64,
+    /// Peak of the slices after normalization
+    pub peak: f32,
+}
+
+impl Default for SliceSettings {
+    fn default() -> Self {
+        Self {
+            onset_rise: 9.0,
+            silence: -50.0,
+            min_length: 0.15,
+            peak: 0.9,
+        }
+    }
+}
+
+impl Wav {
+    /// Positions of the attacks of the notes, in samples
+    pub fn onsets(&self, settings: SliceSettings) -> Vec<usize> {
+        let frame = self.frame_length();
+        let levels = self.levels();
+        let peak = levels.iter().fold(LEVEL_MIN, |m, &l| m.max(l));
+        let min_gap = (settings.min_length * self.sample_rate as f64) as usize;
+        let mut onsets: Vec<usize> = Vec::new();
+        for f in 1..levels.len() {
+            let before = levels[f.saturating_sub(RISE_FRAMES)..f]
+                .iter()
+                .fold(levels[f], |m, &l| m.min(l));
+            // The attack begins in the frame or in the one before
+            let onset = (f - 1) * frame;
+            if levels[f] > peak + settings.silence
+                && levels[f] - before >= settings.onset_rise
+                && onsets.last().map_or(true, |&o| onset >= o + min_gap)
+            {
+                onsets.push(onset);
+            }
+        }
+        onsets
+    }
+
+    /// One sample per note, from its onset to the next one: the silence at
+    /// the end is trimmed, the end faded out and the peak normalized
+    pub fn slices(&self, settings: SliceSettings) -> Vec<Wav> {
+        let onsets = self.onsets(settings);
+        let ends = onsets
+            .iter()
+            .skip(1)
+            .copied()
+            .chain(Some(self.samples.len()));
+        onsets
+            .iter()
+            .zip(ends)
+            .map(|(&start, end)| {
+                let mut slice = Wav {
+                    sample_rate: self.sample_rate,
+                    samples: self.samples[start..end].to_vec(),
+                };
+                slice.trim_end(settings.silence);
+                let fade = (FADE * self.sample_rate as f64) as usize;
+                let length = slice.samples.len();
+                let fade = fade.min(length);
+                for (i, s) in
+                    slice.samples[length - fade..].iter_mut().enumerate()
+                {
+                    *s *= 1.0 - (i + 1) as f32 / fade as f32;
+                }
+                slice.normalize(settings.peak);
+                slice
+            })
+            .collect()
+    }
+
+    /// Remove the frames at the end quieter than `silence` dB under the peak
+    fn trim_end(&mut self, silence: f32) {
+        let levels = self.levels();
+        let peak = levels.iter().fold(LEVEL_MIN, |m, &l| m.max(l));
+        let last = levels.iter().rposition(|&l| l > peak + silence);
+        let length = last.map_or(0, |f| (f + 1) * self.frame_length());
+        self.samples.truncate(length);
+    }
+
+    fn frame_length(&self) -> usize {
+        ((FRAME * self.sample_rate as f64) as usize).max(1)
+    }
+
+    /// Level of each frame, in dB
+    fn levels(&self) -> Vec<f32> {
+        self.samples
+            .chunks(self.frame_length())
+            .map(|frame| {
+                let power = frame.iter().map(|s| s * s).sum::<f32>()
+                    / frame.len() as f32;
+                if power > 0.0 {
+                    10.0 * power.log10()
+                } else {
+                    LEVEL_MIN
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{Pluck, TraitInstrument, SAMPLE_RATE};
+    use crate::midi::{MidiFile, MidiNote, TICKS_PER_BEAT};
+
+    /// Plucked notes, one per beat at 60 beats per minute, after a silence
+    /// of one beat
+    fn recording(keys: &[u8]) -> Wav {
+        let beat = TICKS_PER_BEAT as u32;
+        let mut midi = MidiFile::new(60);
+        midi.notes = keys
+            .iter()
+            .enumerate()
+            .map(|(i, &key)| MidiNote {
+                start: (i as u32 + 1) * beat,
+                duration: beat,
+                key,
+                velocity: 100,
+            })
+            .collect();
+        let mut wav = Pluck::default().render(&midi, SAMPLE_RATE);
+        wav.samples
+            .resize(wav.samples.len() + SAMPLE_RATE as usize, 0.0);
+        wav
+    }
+
+    #[test]
+    fn test_onsets() {
+        let wav = recording(&[40, 41, 42, 43]);
+        let onsets = wav.onsets(SliceSettings::default());
+        assert_eq!(onsets.len(), 4);
+        for (i, onset) in onsets.iter().enumerate() {
+            let expected = (i + 1) * SAMPLE_RATE as usize;
+            // Within 10 ms, before the attack
+            assert!(*onset <= expected, "{} {}", onset, expected);
+            assert!(expected - onset < 441, "{} {}", onset, expected);
+        }
+    }
+
+    #[test]
+    fn test_slices() {
+        let settings = SliceSettings::default();
+        let slices = recording(&[52, 64]).slices(settings);
+        assert_eq!(slices.len(), 2);
+        for slice in &slices {
+            let peak = slice.samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+            assert!((peak - settings.peak).abs() < 1e-6);
+            assert_eq!(slice.samples.last(), Some(&0.0));
+        }
+        // The first slice ends at the next onset, the silence after the
+        // last note is trimmed
+        assert!(slices[0].duration() <= 1.0);
+        assert!(slices[1].duration() < 1.5);
+    }
+
+    #[test]
+    fn test_silence() {
+        let mut wav = Wav::new(SAMPLE_RATE);
+        wav.samples = vec![0.0; 10_000];
+        assert!(wav.onsets(SliceSettings::default()).is_empty());
+        assert!(Wav::new(SAMPLE_RATE)
+            .slices(SliceSettings::default())
+            .is_empty());
+    }
+}
@@ -0,0 +1,367 @@
+use super::sampler::Sampler;
+use super::wav::Wav;
+use crate::error::Result;
+use crate::midi::KEY_MAX;
+use crate::pitch::Pitch;
+use std::fmt;
+use std::fs;
+use std::path;
+use ukebox::Semitones;
+
+/// Frequency of A4, in Hz
+const A4: f64 = 440.0;
+const A4_KEY: f64 = 69.0;
+/// Lowest and highest fundamental searched, in Hz
+const FREQUENCY_MIN: f64 = 60.0;
+const FREQUENCY_MAX: f64 = 1500.0;
+/// Seconds of the attack left out of the measure
+const ATTACK: f64 = 0.05;
+/// Samples of a frame of the measure, and frames measured at most
+const FRAME: usize = 2048;
+const FRAMES: usize = 9;
+/// Level of a frame too quiet to be measured
+const SILENCE: f32 = 1e-4;
+/// Threshold of the normalized difference of YIN, and the highest value
+/// still taken as a period
+const THRESHOLD: f64 = 0.1;
+const APERIODIC: f64 = 0.5;
+
+/// A pitch found in audio
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DetectedPitch {
+    /// Fundamental frequency, in Hz
+    pub frequency: f64,
+    /// Nearest pitch in equal temperament, A4 being 440 Hz
+    pub pitch: Pitch,
+    /// Deviation from `pitch`, from -50 to 50 cents
+    pub cents: f64,
+}
+
+impl DetectedPitch {
+    /// `None` out of the MIDI notes
+    pub fn from_frequency(frequency: f64) -> Option<Self> {
+        let key = A4_KEY + 12.0 * (frequency / A4).log2();
+        let nearest = key.round();
+        if !(0.0..=KEY_MAX as f64).contains(&nearest) {
+            return None;
+        }
+        Some(Self {
+            frequency,
+            pitch: Pitch::from_midi(nearest as Semitones),
+            cents: (key - nearest) * 100.0,
+        })
+    }
+}
+
+impl fmt::Display for DetectedPitch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {:+.0} cents", self.pitch, self.cents)
+    }
+}
+
+impl Wav {
+    /// Pitch of a single note, the median of the YIN estimates of frames
+    /// after the attack. `None` for silence or noise.
+    pub fn detect_pitch(&self) -> Option<DetectedPitch> {
+        let rate = self.sample_rate as f64;
+        let tau_min = (rate / FREQUENCY_MAX) as usize;
+        let tau_max = (rate / FREQUENCY_MIN) as usize;
+        let start = (ATTACK * rate) as usize;
+        let mut frequencies: Vec<f64> = (0..FRAMES)
+            .filter_map(|i| {
+                let begin = start + i * FRAME / 2;
+                let frame = self.samples.get(begin..begin + FRAME + tau_max)?;
+                let level = frame[..FRAME].iter().map(|s| s * s).sum::<f32>()
+                    / FRAME as f32;
+                if level.sqrt() < SILENCE {
+                    return None;
+                }
+                let tau = yin(frame, tau_min, tau_max)?;
+                Some(rate / tau)
+            })
+            .collect();
+        if frequencies.is_empty() {
+            return None;
+        }
+        frequencies.sort_by(|a, b| a.total_cmp(b));
+        DetectedPitch::from_frequency(frequencies[frequencies.len() / 2])
+    }
+}
+
+/// Period of `frame` in samples, between `tau_min` and `tau_max`, with the
+/// YIN algorithm of de Cheveigné and Kawahara
+fn yin(frame: &[f32], tau_min: usize, tau_max: usize) -> Option<f64> {
+    // Cumulative mean normalized difference
+    let mut normalized = vec![1.0; tau_max + 1];
+    let mut sum = 0.0;
+    for tau in 1..=tau_max {
+        let difference: f64 = frame[..FRAME]
+            .iter()
+            .zip(&frame[tau..tau + FRAME])
+            .map(|(a, b)| ((a - b) * (a - b)) as f64)
+            .sum();
+        sum += difference;
+        normalized[tau] = if sum > 0.0 {
+            difference * tau as f64 / sum
+        } else {
+            1.0
+        };
+    }
+    // The first dip under the threshold, else the lowest one
+    let below = (tau_min..tau_max).find(|&t| normalized[t] < THRESHOLD);
+    let mut tau = match below {
+        Some(t) => t,
+        None => (tau_min..tau_max)
+            .min_by(|&a, &b| normalized[a].total_cmp(&normalized[b]))?,
+    };
+    while tau + 1 < tau_max && normalized[tau + 1] < normalized[tau] {
+        tau += 1;
+    }
+    if normalized[tau] > APERIODIC {
+        return None;
+    }
+    // Parabola through the dip and its neighbours
+    let (a, b, c) = (normalized[tau - 1], normalized[tau], normalized[tau + 1]);
+    let curvature = a - 2.0 * b + c;
+    let shift = if curvature > 0.0 {
+        (a - c) / (2.0 * curvature)
+    } else {
+        0.0
+    };
+    Some(tau as f64 + shift)
+}
+
+/// State of a file of the sample bank
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleStatus {
+    InTune,
+    /// Right note, but further than the tolerance from it
+    OutOfTune,
+    /// Another note than the one of its name
+    Mislabeled,
+    /// No pitch found
+    Unpitched,
+}
+
+/// Pitch found in a file of the sample bank
+#[derive(Debug, Clone, PartialEq)]
+pub struct SampleCheck {
+    pub file: String,
+    /// Pitch of the name of the file
+    pub expected: Pitch,
+    pub detected: Option<DetectedPitch>,
+}
+
+impl SampleCheck {
+    /// `tolerance` in cents
+    pub fn status(&self, tolerance: f64) -> SampleStatus {
+        match self.detected {
+            None => SampleStatus::Unpitched,
+            Some(d) if d.pitch != self.expected => SampleStatus::Mislabeled,
+            Some(d) if d.cents.abs() > tolerance => SampleStatus::OutOfTune,
+            Some(_) => SampleStatus::InTune,
+        }
+    }
+
+    /// Name of the file of the detected pitch, `None` without pitch or
+    /// below the bank
+    pub fn detected_file_name(&self) -> Option<String> {
+        Sampler::file_name(self.detected?.pitch.midi())
+    }
+}
+
+impl fmt::Display for SampleCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.detected {
+            Some(d) => write!(f, "{}: {} ({})", self.file, d, self.expected),
+            None => write!(f, "{}: no pitch ({})", self.file, self.expected),
+        }
+    }
+}
+
+impl Sampler {
+    /// MIDI note number of a file name of the bank, the reverse of
+    /// `file_name`
+    pub fn key(file_name: &str) -> Option<Semitones> {
+        (0..=KEY_MAX)
+            .find(|&k| Self::file_name(k).as_deref() == Some(file_name))
+    }
+
+    /// Detect the pitch of each file of the bank in `dir`, by name
+    pub fn check<P: AsRef<path::Path>>(dir: P) -> Result<Vec<SampleCheck>> {
+        let mut checks: Vec<SampleCheck> = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let file = entry?.file_name().to_string_lossy().to_string();
+            if let Some(key) = Self::key(&file) {
+                let wav = Wav::open(dir.as_ref().join(&file))?;
+                checks.push(SampleCheck {
+                    file,
+                    expected: Pitch::from_midi(key),
+                    detected: wav.detect_pitch(),
+                });
+            }
+        }
+        checks.sort_by_key(|c| c.expected.midi());
+        Ok(checks)
+    }
+
+    /// Rename the mislabeled files of `dir` after their detected pitch,
+    /// unless a file has that name already. Return the renamed files and
+    /// their new names.
+    pub fn relabel<P: AsRef<path::Path>>(
+        dir: P,
+        checks: &[SampleCheck],
+    ) -> Result<Vec<(String, String)>> {
+        let mut renamed: Vec<(String, String)> = Vec::new();
+        for check in checks {
+            if check.status(f64::INFINITY) != SampleStatus::Mislabeled {
+                continue;
+            }
+            if let Some(name) = check.detected_file_name() {
+                let to = dir.as_ref().join(&name);
+                if !to.exists() {
+                    fs::rename(dir.as_ref().join(&check.file), to)?;
+                    renamed.push((check.file.clone(), name));
+                }
+            }
+        }
+        Ok(renamed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::{Pluck, TraitInstrument, SAMPLE_RATE};
+    use rstest::rstest;
+    use std::f64::consts::PI;
+    use std::str::FromStr;
+
+    /// One second of harmonics of `frequency`, the second one being the
+    /// loudest as on a low string
+    fn tone(frequency: f64) -> Wav {
+        let rate = SAMPLE_RATE as f64;
+        Wav {
+            sample_rate: SAMPLE_RATE,
+            samples: (0..SAMPLE_RATE)
+                .map(|i| {
+                    let t = i as f64 / rate;
+                    [(1.0, 0.4), (2.0, 0.6), (3.0, 0.3), (4.0, 0.2)]
+                        .iter()
+                        .map(|(h, a)| a * (2.0 * PI * frequency * h * t).sin())
+                        .sum::<f64>() as f32
+                        * 0.5
+                })
+                .collect(),
+        }
+    }
+
+    #[rstest(
+        frequency,
+        pitch,
+        cents,
+        case(82.407, "E2", 0.0),
+        case(110.0, "A2", 0.0),
+        case(440.0 * 1.01, "A4", 17.2),
+        case(329.63 * 0.99, "E4", -17.4),
+        case(987.77, "B5", 0.0)
+    )]
+    fn test_detect_pitch(frequency: f64, pitch: &str, cents: f64) {
+        let detected = tone(frequency).detect_pitch().unwrap();
+        assert_eq!(detected.pitch, Pitch::from_str(pitch).unwrap());
+        assert!((detected.cents - cents).abs() < 1.0, "{}", detected);
+    }
+
+    #[rstest(key, case(40), case(45), case(52), case(64))]
+    fn test_detect_pluck(key: Semitones) {
+        let wav = Pluck::default().render_note(key, 100, 44_100, SAMPLE_RATE);
+        let wav = Wav {
+            sample_rate: SAMPLE_RATE,
+            samples: wav,
+        };
+        let detected = wav.detect_pitch().unwrap();
+        assert_eq!(detected.pitch.midi(), key);
+        assert!(detected.cents.abs() < 10.0, "{}", detected);
+    }
+
+    #[test]
+    fn test_unpitched() {
+        let mut wav = Wav::new(SAMPLE_RATE);
+        assert_eq!(wav.detect_pitch(), None);
+        wav.samples = vec![0.0; 44_100];
+        assert_eq!(wav.detect_pitch(), None);
+        // White noise
+        let mut x: u32 = 1;
+        wav.samples = (0..44_100)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                x as f32 / u32::MAX as f32 - 0.5
+            })
+            .collect();
+        assert_eq!(wav.detect_pitch(), None);
+    }
+
+    #[test]
+    fn test_status() {
+        let e2 = Pitch::from_str("E2").unwrap();
+        let check = |frequency: f64| SampleCheck {
+            file: "guitar_e_0.wav".to_string(),
+            expected: e2,
+            detected: DetectedPitch::from_frequency(frequency),
+        };
+        assert_eq!(check(82.41).status(20.0), SampleStatus::InTune);
+        assert_eq!(check(84.0).status(20.0), SampleStatus::OutOfTune);
+        assert_eq!(check(87.31).status(20.0), SampleStatus::Mislabeled);
+        assert_eq!(
+            check(87.31).detected_file_name().as_deref(),
+            Some("guitar_f_0.wav")
+        );
+        assert_eq!(
+            check(84.0).to_string(),
+            "guitar_e_0.wav: E2 +33 cents (E2)"
+        );
+    }
+
+    #[test]
+    fn test_check_and_relabel() {
+        let dir = std::env::temp_dir().join("guitar_scale_test_tuner");
+        std::fs::create_dir_all(&dir).unwrap();
+        tone(82.41).save(dir.join("guitar_e_0.wav")).unwrap();
+        // A2 named A#2
+        tone(110.0).save(dir.join("guitar_a_sharp_0.wav")).unwrap();
+        tone(110.0).save(dir.join("other.wav")).unwrap();
+        let checks = Sampler::check(&dir).unwrap();
+        let renamed = Sampler::relabel(&dir, &checks).unwrap();
+        let relabeled = dir.join("guitar_a_0.wav").is_file();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let status: Vec<SampleStatus> =
+            checks.iter().map(|c| c.status(10.0)).collect();
+        assert_eq!(
+            status,
+            vec![SampleStatus::InTune, SampleStatus::Mislabeled]
+        );
+        assert_eq!(
+            renamed,
+            vec![(
+                "guitar_a_sharp_0.wav".to_string(),
+                "guitar_a_0.wav".to_string()
+            )]
+        );
+        assert!(relabeled);
+    }
+
+    #[rstest(
+        file,
+        key,
+        case("guitar_e_0.wav", Some(40)),
+        case("guitar_c_sharp_1.wav", Some(49)),
+        case("guitar_h_0.wav", None),
+        case("guitar_e_0_v1.wav", None)
+    )]
+    fn test_key(file: &str, key: Option<Semitones>) {
+        assert_eq!(Sampler::key(file), key);
+    }
+}
@@ -0,0 +1,885 @@
+use super::ogg::OggWriter;
+use super::wav::Wav;
+#[cfg(test)]
+use super::wav::SAMPLE_RATE;
+use std::f32::consts::PI;
+
+/// Every block is 2048 samples long, half of it overlapping the next block
+const BLOCK_EXPONENT: u8 = 11;
+const BLOCK_SIZE: usize = 1 << BLOCK_EXPONENT;
+const HALF: usize = BLOCK_SIZE / 2;
+/// The floor is a line through points whose levels go by steps of
+/// `FLOOR_MULTIPLIER` * 140 / 256 dB
+const FLOOR_MULTIPLIER: u32 = 2;
+const FLOOR_RANGE: i32 = 128;
+const FLOOR_Y_BITS: u32 = 7;
+const FLOOR_RANGE_BITS: u32 = 10;
+const FLOOR_POINTS: usize = 28;
+const FLOOR_DIMENSION: usize = 4;
+/// dB of a step of the table of the floor levels
+const DB_PER_STEP: f32 = 140.0 / 256.0;
+/// Coefficients of a partition of the residue
+const PARTITION_SIZE: usize = 32;
+/// The residue is coded in two passes, a coarse value then a fine value
+/// from -FINE to FINE
+const FINE: i32 = 15;
+const ENTRIES: u32 = 2 * FINE as u32 + 1;
+const COARSE_STEP: i32 = 2 * FINE + 1;
+const MAX_RESIDUE: i32 = FINE * COARSE_STEP + FINE;
+/// Classes of the partitions: silent, fine values, coarse and fine values
+const CLASSES: u32 = 3;
+/// Codebooks
+const BOOK_FLOOR: usize = 0;
+const BOOK_CLASS: usize = 1;
+const BOOK_FINE: usize = 2;
+const BOOK_COARSE: usize = 3;
+const CODEBOOK_SYNC: u32 = 0x56_4342;
+/// Exponent of the integers written as Vorbis floats
+const FLOAT_EXPONENT: u32 = 788;
+/// Longest Huffman code allowed by Vorbis
+const MAX_CODE_LENGTH: u8 = 32;
+/// Signal to noise ratio searched for the bitrate, in dB
+const SNR_MIN: f32 = -20.0;
+const SNR_MAX: f32 = 50.0;
+const SEARCH_STEPS: usize = 12;
+/// Lowest level of the floor, in dB: the coefficients far below it are
+/// left out
+const THRESHOLD: f32 = -90.0;
+/// Ratio of the coefficients of the decoder to the ones of the transform
+const SCALE: f32 = 4.0 / BLOCK_SIZE as f32;
+const VENDOR: &str = "guitar_scale";
+
+/// Bits packed from the lowest bit of each byte, as Vorbis does
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+}
+
+impl BitWriter {
+    /// Write the `count` lowest bits of `value`
+    fn write(&mut self, value: u32, count: u32) {
+        for i in 0..count {
+            if self.bits % 8 == 0 {
+                self.bytes.push(0);
+            }
+            if value >> i & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 1 << (self.bits % 8);
+            }
+            self.bits += 1;
+        }
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.write(byte as u32, 8);
+        }
+    }
+
+    /// Packet type and "vorbis" of a header
+    fn header(packet_type: u8) -> Self {
+        let mut w = Self::default();
+        w.write(packet_type as u32, 8);
+        w.write_bytes(b"vorbis");
+        w
+    }
+}
+
+/// A Huffman codebook of scalars, with values `minimum + i * delta`
+/// (lookup type 1 of one dimension) or without values (lookup type 0)
+struct Codebook {
+    lengths: Vec<u8>,
+    codes: Vec<u32>,
+    lookup: Option<(i32, i32)>,
+}
+
+impl Codebook {
+    /// Codebook fitted to how many times each entry is used
+    fn new(counts: &[u32], lookup: Option<(i32, i32)>) -> Self {
+        let lengths = huffman_lengths(counts);
+        let codes = codewords(&lengths);
+        Self {
+            lengths,
+            codes,
+            lookup,
+        }
+    }
+
+    fn write_header(&self, w: &mut BitWriter) {
+        let entries = self.lengths.len() as u32;
+        w.write(CODEBOOK_SYNC, 24);
+        w.write(1, 16); // Dimensions
+        w.write(entries, 24);
+        w.write(0, 1); // Not ordered
+        w.write(0, 1); // Not sparse
+        for &length in &self.lengths {
+            w.write(length as u32 - 1, 5);
+        }
+        match self.lookup {
+            None => w.write(0, 4),
+            Some((minimum, delta)) => {
+                let value_bits = ilog(entries - 1);
+                w.write(1, 4);
+                w.write(float32(minimum), 32);
+                w.write(float32(delta), 32);
+                w.write(value_bits - 1, 4);
+                w.write(0, 1); // No sequence
+                for i in 0..entries {
+                    w.write(i, value_bits);
+                }
+            },
+        }
+    }
+
+    /// Write the code of an entry, its first bit first
+    fn write(&self, w: &mut BitWriter, entry: usize) {
+        let length = self.lengths[entry] as u32;
+        for i in (0..length).rev() {
+            w.write(self.codes[entry] >> i, 1);
+        }
+    }
+
+    /// Bits to write an entry `count` times each
+    fn cost(&self, counts: &[u32]) -> u64 {
+        counts
+            .iter()
+            .zip(&self.lengths)
+            .map(|(&c, &l)| c as u64 * l as u64)
+            .sum()
+    }
+}
+
+/// A block ready to be written
+struct Frame {
+    /// Levels of the first two floor points then the values of the others,
+    /// `None` for a silent block
+    floor: Option<Vec<u32>>,
+    /// Class of each partition
+    classes: Vec<u8>,
+    residue: Vec<i32>,
+}
+
+/// Entries used by the frames, by codebook
+struct Counts([Vec<u32>; 4]);
+
+impl Counts {
+    fn new(frames: &[Frame]) -> Self {
+        let mut counts = [
+            vec![0; FLOOR_RANGE as usize],
+            vec![0; CLASSES as usize],
+            vec![0; ENTRIES as usize],
+            vec![0; ENTRIES as usize],
+        ];
+        for frame in frames {
+            if let Some(floor) = &frame.floor {
+                for &v in &floor[2..] {
+                    counts[BOOK_FLOOR][v as usize] += 1;
+                }
+                for (p, &class) in frame.classes.iter().enumerate() {
+                    counts[BOOK_CLASS][class as usize] += 1;
+                    let values = &frame.residue
+                        [p * PARTITION_SIZE..(p + 1) * PARTITION_SIZE];
+                    for &q in values.iter().filter(|_| class > 0) {
+                        let (coarse, fine) = split(q, class);
+                        counts[BOOK_FINE][(fine + FINE) as usize] += 1;
+                        if class == 2 {
+                            counts[BOOK_COARSE][(coarse + FINE) as usize] += 1;
+                        }
+                    }
+                }
+            }
+        }
+        Self(counts)
+    }
+
+    fn codebooks(&self) -> Vec<Codebook> {
+        vec![
+            Codebook::new(&self.0[BOOK_FLOOR], None),
+            Codebook::new(&self.0[BOOK_CLASS], None),
+            Codebook::new(&self.0[BOOK_FINE], Some((-FINE, 1))),
+            Codebook::new(
+                &self.0[BOOK_COARSE],
+                Some((-FINE * COARSE_STEP, COARSE_STEP)),
+            ),
+        ]
+    }
+}
+
+/// Encode mono audio as Ogg Vorbis of about `bitrate` bits per second
+pub(crate) fn encode(wav: &Wav, bitrate: u32) -> Vec<u8> {
+    let sample_rate = wav.sample_rate.max(1);
+    let floor_x = floor_x();
+    let spectra = analyse(&wav.samples);
+    // The highest signal to noise ratio fitting in the bitrate
+    let budget = bitrate as f64 * wav.duration();
+    let bits = |snr: f32| -> (f64, Vec<Frame>) {
+        let frames: Vec<Frame> =
+            spectra.iter().map(|s| quantize(s, &floor_x, snr)).collect();
+        let counts = Counts::new(&frames);
+        let books = counts.codebooks();
+        let floor_bits = frames.iter().filter(|f| f.floor.is_some()).count()
+            * 2
+            * FLOOR_Y_BITS as usize;
+        let total: u64 = (0..4).map(|b| books[b].cost(&counts.0[b])).sum();
+        (total as f64 + floor_bits as f64, frames)
+    };
+    let (mut low, mut high) = (SNR_MIN, SNR_MAX);
+    for _ in 0..SEARCH_STEPS {
+        let middle = (low + high) / 2.0;
+        if bits(middle).0 > budget {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    let frames = bits(low).1;
+    let books = Counts::new(&frames).codebooks();
+    let mut ogg = OggWriter::new(u32::from_le_bytes(*b"gtrs"));
+    ogg.packet(&identification(sample_rate, bitrate), 0);
+    ogg.flush(false);
+    ogg.packet(&comment(), 0);
+    ogg.packet(&setup(&books, &floor_x), 0);
+    ogg.flush(false);
+    let length = wav.samples.len() as u64;
+    for (i, frame) in frames.iter().enumerate() {
+        let granule = (i as u64 * HALF as u64).min(length);
+        ogg.packet(&audio(frame, &books), granule);
+        // The first block only primes the decoder: alone on its page, the
+        // position of the next pages counts from it
+        if i == 0 {
+            ogg.flush(false);
+        }
+    }
+    ogg.flush(true);
+    ogg.bytes
+}
+
+/// Spectra of the blocks, the block `k` being centered on the sample
+/// `k * HALF`, so that the decoded audio begins with the first sample
+fn analyse(samples: &[f32]) -> Vec<Vec<f32>> {
+    let mdct = Mdct::new();
+    let blocks = (samples.len() + HALF - 1) / HALF + 1;
+    (0..blocks)
+        .map(|b| {
+            let input: Vec<f32> = (0..BLOCK_SIZE)
+                .map(|n| {
+                    (b * HALF + n)
+                        .checked_sub(HALF)
+                        .and_then(|i| samples.get(i))
+                        .map_or(0.0, |&s| s)
+                })
+                .collect();
+            mdct.forward(&input)
+        })
+        .collect()
+}
+
+/// Windowed MDCT of a block, computed as a DCT-IV through a complex FFT
+/// of a quarter of the block
+struct Mdct {
+    window: Vec<f32>,
+    /// Rotations before and after the FFT
+    pre: Vec<(f32, f32)>,
+    post: Vec<(f32, f32)>,
+}
+
+impl Mdct {
+    fn new() -> Self {
+        let rotation = |phase: f64| {
+            let phase = -std::f64::consts::PI * phase / HALF as f64;
+            (phase.cos() as f32, phase.sin() as f32)
+        };
+        Self {
+            window: (0..BLOCK_SIZE)
+                .map(|n| {
+                    let s = (PI * (n as f32 + 0.5) / BLOCK_SIZE as f32).sin();
+                    (PI / 2.0 * s * s).sin()
+                })
+                .collect(),
+            pre: (0..HALF / 2).map(|m| rotation(m as f64 + 0.25)).collect(),
+            post: (0..HALF / 2).map(|m| rotation(m as f64)).collect(),
+        }
+    }
+
+    /// `X[k] = sum(w[n] x[n] cos(2 pi / N (n + 1/2 + N/4) (k + 1/2)))`,
+    /// scaled as the decoder expects
+    fn forward(&self, block: &[f32]) -> Vec<f32> {
+        let x: Vec<f32> =
+            block.iter().zip(&self.window).map(|(x, w)| x * w).collect();
+        // Fold the quarters a, b, c, d into (-c_r - d, a - b_r)
+        let q = HALF / 2;
+        let u: Vec<f32> = (0..HALF)
+            .map(|n| {
+                if n < q {
+                    -x[3 * q - 1 - n] - x[3 * q + n]
+                } else {
+                    x[n - q] - x[3 * q - 1 - n]
+                }
+            })
+            .collect();
+        let mut v: Vec<(f32, f32)> = self
+            .pre
+            .iter()
+            .enumerate()
+            .map(|(m, &r)| multiply((u[2 * m], u[HALF - 1 - 2 * m]), r))
+            .collect();
+        fft(&mut v);
+        let mut spectrum = vec![0.0; HALF];
+        for (m, (&y, &r)) in v.iter().zip(&self.post).enumerate() {
+            let (re, im) = multiply(y, r);
+            spectrum[2 * m] = re * SCALE;
+            spectrum[HALF - 1 - 2 * m] = -im * SCALE;
+        }
+        spectrum
+    }
+}
+
+fn multiply(a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    (a.0 * b.0 - a.1 * b.1, a.0 * b.1 + a.1 * b.0)
+}
+
+/// Radix-2 FFT in place, of a power of two length
+fn fft(v: &mut [(f32, f32)]) {
+    let n = v.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            v.swap(i, j);
+        }
+    }
+    let mut length = 2;
+    while length <= n {
+        let phase = -2.0 * std::f64::consts::PI / length as f64;
+        for start in (0..n).step_by(length) {
+            for k in 0..length / 2 {
+                let angle = phase * k as f64;
+                let w = (angle.cos() as f32, angle.sin() as f32);
+                let a = v[start + k];
+                let b = multiply(v[start + k + length / 2], w);
+                v[start + k] = (a.0 + b.0, a.1 + b.1);
+                v[start + k + length / 2] = (a.0 - b.0, a.1 - b.1);
+            }
+        }
+        length <<= 1;
+    }
+}
+
+/// Positions of the floor points: the two ends then points closer at the
+/// low frequencies
+fn floor_x() -> Vec<u32> {
+    let mut x: Vec<u32> = vec![0, HALF as u32];
+    let ratio = (HALF as f32 / 2.0).ln() / (FLOOR_POINTS as f32 - 1.0);
+    let mut last = 0;
+    for i in 0..FLOOR_POINTS {
+        let v = ((ratio * i as f32).exp() as u32 + 1).max(last + 1);
+        x.push(v);
+        last = v;
+    }
+    x
+}
+
+/// Quantize a spectrum, the floor following its peaks `snr` dB lower
+fn quantize(spectrum: &[f32], floor_x: &[u32], snr: f32) -> Frame {
+    let silent = Frame {
+        floor: None,
+        classes: Vec::new(),
+        residue: Vec::new(),
+    };
+    // Wanted level of each point: the peak between its neighbours, so that
+    // the line between two points stays above the peaks between them
+    let mut sorted: Vec<u32> = floor_x.to_vec();
+    sorted.sort_unstable();
+    let wanted: Vec<i32> = floor_x
+        .iter()
+        .map(|&x| {
+            let i = sorted.binary_search(&x).unwrap();
+            let low = if i == 0 { 0 } else { sorted[i - 1] as usize };
+            let high = sorted.get(i + 1).map_or(HALF, |&h| h as usize);
+            let peak = spectrum[low..high.min(HALF)]
+                .iter()
+                .fold(0.0f32, |m, c| m.max(c.abs()));
+            let db = (20.0 * peak.max(1e-10).log10() - snr).max(THRESHOLD);
+            let step = (db / DB_PER_STEP + 255.0) / FLOOR_MULTIPLIER as f32;
+            (step.ceil() as i32).clamp(0, FLOOR_RANGE - 1)
+        })
+        .collect();
+    let (values, curve) = floor_values(floor_x, &wanted);
+    let residue: Vec<i32> = spectrum
+        .iter()
+        .zip(&curve)
+        .map(|(c, f)| ((c / f).round() as i32).clamp(-MAX_RESIDUE, MAX_RESIDUE))
+        .collect();
+    if residue.iter().all(|&q| q == 0) {
+        return silent;
+    }
+    let classes = residue
+        .chunks_exact(PARTITION_SIZE)
+        .map(|p| match p.iter().map(|q| q.abs()).max() {
+            Some(0) => 0,
+            Some(m) if m <= FINE => 1,
+            _ => 2,
+        })
+        .collect();
+    Frame {
+        floor: Some(values),
+        classes,
+        residue,
+    }
+}
+
+/// Values to write for the wanted levels of the floor points and the floor
+/// curve the decoder draws from them
+fn floor_values(floor_x: &[u32], wanted: &[i32]) -> (Vec<u32>, Vec<f32>) {
+    let count = floor_x.len();
+    let mut values: Vec<u32> = vec![wanted[0] as u32, wanted[1] as u32];
+    let mut final_y: Vec<i32> = vec![wanted[0], wanted[1]];
+    let mut used = vec![false; count];
+    used[0] = true;
+    used[1] = true;
+    for i in 2..count {
+        let x = floor_x[i];
+        let low = (0..i)
+            .filter(|&j| floor_x[j] < x)
+            .max_by_key(|&j| floor_x[j])
+            .unwrap();
+        let high = (0..i)
+            .filter(|&j| floor_x[j] > x)
+            .min_by_key(|&j| floor_x[j])
+            .unwrap();
+        let predicted = render_point(
+            floor_x[low] as i32,
+            final_y[low],
+            floor_x[high] as i32,
+            final_y[high],
+            x as i32,
+        );
+        let high_room = FLOOR_RANGE - predicted;
+        let low_room = predicted;
+        let room = 2 * high_room.min(low_room);
+        let delta = wanted[i] - predicted;
+        let value = if delta > 0 && 2 * delta < room {
+            2 * delta
+        } else if delta < 0 && -2 * delta - 1 < room {
+            -2 * delta - 1
+        } else if delta > 0 && high_room > low_room {
+            delta + low_room
+        } else if delta < 0 && high_room <= low_room {
+            high_room - 1 - delta
+        } else {
+            0
+        };
+        // Decoded as the decoder does
+        let y = if value == 0 {
+            predicted
+        } else if value >= room {
+            if high_room > low_room {
+                value - low_room + predicted
+            } else {
+                predicted - value + high_room - 1
+            }
+        } else if value % 2 == 1 {
+            predicted - (value + 1) / 2
+        } else {
+            predicted + value / 2
+        };
+        if value != 0 {
+            used[low] = true;
+            used[high] = true;
+            used[i] = true;
+        }
+        values.push(value as u32);
+        final_y.push(y);
+    }
+    // Lines through the used points, from the lowest one
+    let mut order: Vec<usize> = (0..count).collect();
+    order.sort_by_key(|&i| floor_x[i]);
+    let mut line = vec![0i32; HALF];
+    let (mut lx, mut ly) = (0, final_y[0] * FLOOR_MULTIPLIER as i32);
+    for &i in order.iter().skip(1).filter(|&&i| used[i]) {
+        let (hx, hy) =
+            (floor_x[i] as i32, final_y[i] * FLOOR_MULTIPLIER as i32);
+        render_line(lx, ly, hx, hy, &mut line);
+        lx = hx;
+        ly = hy;
+    }
+    if (lx as usize) < HALF {
+        render_line(lx, ly, HALF as i32, ly, &mut line);
+    }
+    let curve = line.iter().map(|&y| inverse_db(y)).collect();
+    (values, curve)
+}
+
+/// Level of a point on the line from (x0, y0) to (x1, y1)
+fn render_point(x0: i32, y0: i32, x1: i32, y1: i32, x: i32) -> i32 {
+    let dy = y1 - y0;
+    let offset = dy.abs() * (x - x0) / (x1 - x0);
+    if dy < 0 {
+        y0 - offset
+    } else {
+        y0 + offset
+    }
+}
+
+/// Integer line from (x0, y0) to (x1, y1), without its last point
+fn render_line(x0: i32, y0: i32, x1: i32, y1: i32, v: &mut [i32]) {
+    let dy = y1 - y0;
+    let adx = x1 - x0;
+    let base = dy / adx;
+    let sy = if dy < 0 { base - 1 } else { base + 1 };
+    let ady = dy.abs() - base.abs() * adx;
+    let (mut y, mut err) = (y0, 0);
+    if let Some(p) = v.get_mut(x0 as usize) {
+        *p = y;
+    }
+    for x in x0 + 1..x1 {
+        err += ady;
+        if err >= adx {
+            err -= adx;
+            y += sy;
+        } else {
+            y += base;
+        }
+        if let Some(p) = v.get_mut(x as usize) {
+            *p = y;
+        }
+    }
+}
+
+/// Amplitude of a floor level, 255 being 0 dB
+fn inverse_db(y: i32) -> f32 {
+    10f32.powf((y - 255) as f32 * DB_PER_STEP / 20.0)
+}
+
+/// Coarse and fine values of a residue value of a class
+fn split(q: i32, class: u8) -> (i32, i32) {
+    if class == 2 {
+        let coarse =
+            ((q as f32 / COARSE_STEP as f32).round() as i32).clamp(-FINE, FINE);
+        (coarse, q - coarse * COARSE_STEP)
+    } else {
+        (0, q)
+    }
+}
+
+fn identification(sample_rate: u32, bitrate: u32) -> Vec<u8> {
+    let mut w = BitWriter::header(1);
+    w.write(0, 32); // Version
+    w.write(1, 8); // Mono
+    w.write(sample_rate, 32);
+    w.write(0, 32); // Maximum bitrate
+    w.write(bitrate, 32);
+    w.write(0, 32); // Minimum bitrate
+    w.write(BLOCK_EXPONENT as u32, 4);
+    w.write(BLOCK_EXPONENT as u32, 4);
+    w.write(1, 1); // Framing
+    w.bytes
+}
+
+fn comment() -> Vec<u8> {
+    let mut w = BitWriter::header(3);
+    w.write(VENDOR.len() as u32, 32);
+    w.write_bytes(VENDOR.as_bytes());
+    w.write(0, 32); // No comment
+    w.write(1, 1);
+    w.bytes
+}
+
+fn setup(books: &[Codebook], floor_x: &[u32]) -> Vec<u8> {
+    let mut w = BitWriter::header(5);
+    w.write(books.len() as u32 - 1, 8);
+    for book in books {
+        book.write_header(&mut w);
+    }
+    // Time domain transforms, unused
+    w.write(0, 6);
+    w.write(0, 16);
+    // Floor of type 1, one class of FLOOR_DIMENSION points
+    w.write(0, 6);
+    w.write(1, 16);
+    w.write((FLOOR_POINTS / FLOOR_DIMENSION) as u32, 5);
+    for _ in 0..FLOOR_POINTS / FLOOR_DIMENSION {
+        w.write(0, 4);
+    }
+    w.write(FLOOR_DIMENSION as u32 - 1, 3);
+    w.write(0, 2); // No subclass
+    w.write(BOOK_FLOOR as u32 + 1, 8);
+    w.write(FLOOR_MULTIPLIER - 1, 2);
+    w.write(FLOOR_RANGE_BITS, 4);
+    for &x in &floor_x[2..] {
+        w.write(x, FLOOR_RANGE_BITS);
+    }
+    // Residue of type 1: the fine values on the second pass, the coarse
+    // values on the first one
+    w.write(0, 6);
+    w.write(1, 16);
+    w.write(0, 24);
+    w.write(HALF as u32, 24);
+    w.write(PARTITION_SIZE as u32 - 1, 24);
+    w.write(CLASSES - 1, 6);
+    w.write(BOOK_CLASS as u32, 8);
+    for cascade in [0b000, 0b010, 0b011] {
+        w.write(cascade, 3);
+        w.write(0, 1);
+    }
+    w.write(BOOK_FINE as u32, 8);
+    w.write(BOOK_COARSE as u32, 8);
+    w.write(BOOK_FINE as u32, 8);
+    // Mapping of type 0: one channel on the floor and the residue
+    w.write(0, 6);
+    w.write(0, 16);
+    w.write(0, 1); // One submap
+    w.write(0, 1); // No coupling
+    w.write(0, 2);
+    w.write(0, 8);
+    w.write(0, 8);
+    w.write(0, 8);
+    // One mode, with the short block
+    w.write(0, 6);
+    w.write(0, 1);
+    w.write(0, 16);
+    w.write(0, 16);
+    w.write(0, 8);
+    w.write(1, 1); // Framing
+    w.bytes
+}
+
+fn audio(frame: &Frame, books: &[Codebook]) -> Vec<u8> {
+    let mut w = BitWriter::default();
+    w.write(0, 1); // Audio packet
+    let floor = match &frame.floor {
+        Some(floor) => floor,
+        None => {
+            w.write(0, 1);
+            return w.bytes;
+        },
+    };
+    w.write(1, 1);
+    w.write(floor[0], FLOOR_Y_BITS);
+    w.write(floor[1], FLOOR_Y_BITS);
+    for &v in &floor[2..] {
+        books[BOOK_FLOOR].write(&mut w, v as usize);
+    }
+    let partitions = frame.residue.chunks_exact(PARTITION_SIZE);
+    for (values, &class) in partitions.clone().zip(&frame.classes) {
+        books[BOOK_CLASS].write(&mut w, class as usize);
+        if class == 2 {
+            for &q in values {
+                let coarse = split(q, class).0;
+                books[BOOK_COARSE].write(&mut w, (coarse + FINE) as usize);
+            }
+        }
+    }
+    for (values, &class) in partitions.zip(&frame.classes) {
+        if class > 0 {
+            for &q in values {
+                let fine = split(q, class).1;
+                books[BOOK_FINE].write(&mut w, (fine + FINE) as usize);
+            }
+        }
+    }
+    w.bytes
+}
+
+/// Bits of the highest bit set, 0 for 0
+fn ilog(value: u32) -> u32 {
+    32 - value.leading_zeros()
+}
+
+/// Vorbis float of an integer
+fn float32(value: i32) -> u32 {
+    let sign = if value < 0 { 0x8000_0000 } else { 0 };
+    sign | FLOAT_EXPONENT << 21 | value.unsigned_abs()
+}
+
+/// Lengths of a Huffman code, every entry having a code
+fn huffman_lengths(counts: &[u32]) -> Vec<u8> {
+    let mut weights: Vec<u64> = counts.iter().map(|&c| c as u64 + 1).collect();
+    loop {
+        // Nodes: weight and entries below
+        let mut nodes: Vec<(u64, Vec<usize>)> = weights
+            .iter()
+            .enumerate()
+            .map(|(i, &w)| (w, vec![i]))
+            .collect();
+        let mut lengths = vec![0u8; counts.len()];
+        while nodes.len() > 1 {
+            nodes.sort_by_key(|n| std::cmp::Reverse(n.0));
+            let (w1, e1) = nodes.pop().unwrap();
+            let (w2, e2) = nodes.pop().unwrap();
+            for &e in e1.iter().chain(&e2) {
+                lengths[e] += 1;
+            }
+            nodes.push((w1 + w2, [e1, e2].concat()));
+        }
+        if lengths.iter().all(|&l| l <= MAX_CODE_LENGTH) {
+            return lengths.iter().map(|&l| l.max(1)).collect();
+        }
+        // Flatten the weights until the codes are short enough
+        weights
+            .iter_mut()
+            .for_each(|w| *w = (*w as f64).sqrt() as u64 + 1);
+    }
+}
+
+/// Codes of the lengths, each entry taking the lowest free code of its
+/// length as Vorbis does
+fn codewords(lengths: &[u8]) -> Vec<u32> {
+    // Lowest free code of each length, aligned on the highest bit
+    let mut available = [0u64; MAX_CODE_LENGTH as usize + 1];
+    let mut codes: Vec<u32> = Vec::with_capacity(lengths.len());
+    for (i, &length) in lengths.iter().enumerate() {
+        let length = length as usize;
+        if i == 0 {
+            codes.push(0);
+            for (l, a) in available.iter_mut().enumerate().skip(1) {
+                if l <= length {
+                    *a = 1 << (32 - l);
+                }
+            }
+            continue;
+        }
+        let mut z = length;
+        while z > 0 && available[z] == 0 {
+            z -= 1;
+        }
+        let code = available[z];
+        available[z] = 0;
+        for y in (z + 1..=length).rev() {
+            available[y] = code + (1 << (32 - y));
+        }
+        codes.push((code >> (32 - length)) as u32);
+    }
+    codes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lewton::inside_ogg::OggStreamReader;
+    use rstest::rstest;
+    use std::io::Cursor;
+
+    /// A plucked note: harmonics fading out
+    fn note(frequency: f32, seconds: f32) -> Wav {
+        let mut wav = Wav::new(SAMPLE_RATE);
+        let count = (seconds * SAMPLE_RATE as f32) as usize;
+        wav.samples = (0..count)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                (1..6)
+                    .map(|h| {
+                        (2.0 * PI * frequency * h as f32 * t).sin() / h as f32
+                            * 0.3
+                    })
+                    .sum::<f32>()
+                    * (-3.0 * t).exp()
+            })
+            .collect();
+        wav
+    }
+
+    fn decode(bytes: Vec<u8>) -> (u32, Vec<f32>) {
+        let mut reader = OggStreamReader::new(Cursor::new(bytes)).unwrap();
+        let mut samples: Vec<f32> = Vec::new();
+        while let Some(packet) = reader.read_dec_packet_itl().unwrap() {
+            samples.extend(packet.iter().map(|&s| s as f32 / 32768.0));
+        }
+        (reader.ident_hdr.audio_sample_rate, samples)
+    }
+
+    /// Signal to noise ratio of the decoded audio, in dB
+    fn snr(original: &[f32], decoded: &[f32]) -> f32 {
+        let signal: f32 = original.iter().map(|s| s * s).sum();
+        let noise: f32 = original
+            .iter()
+            .zip(decoded)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum();
+        10.0 * (signal / noise).log10()
+    }
+
+    #[test]
+    fn test_mdct() {
+        let block: Vec<f32> =
+            (0..BLOCK_SIZE).map(|n| ((n * 7919) % 101) as f32).collect();
+        let spectrum = Mdct::new().forward(&block);
+        let window = Mdct::new().window;
+        for k in [0, 1, 100, HALF - 1] {
+            let direct: f64 = (0..BLOCK_SIZE)
+                .map(|n| {
+                    let phase = 2.0 * std::f64::consts::PI / BLOCK_SIZE as f64
+                        * (n as f64 + 0.5 + HALF as f64 / 2.0)
+                        * (k as f64 + 0.5);
+                    (block[n] * window[n]) as f64 * phase.cos()
+                })
+                .sum();
+            let direct = direct as f32 * SCALE;
+            assert!((spectrum[k] - direct).abs() < 1e-3, "{}", k);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let wav = note(220.0, 1.0);
+        let (sample_rate, decoded) = decode(encode(&wav, 96_000));
+        assert_eq!(sample_rate, SAMPLE_RATE);
+        assert_eq!(decoded.len(), wav.samples.len());
+        assert!(snr(&wav.samples, &decoded) > 40.0);
+    }
+
+    #[test]
+    fn test_bitrate() {
+        let wav = note(110.0, 2.0);
+        let low = encode(&wav, 10_000);
+        let high = encode(&wav, 14_000);
+        // The headers and the codebooks apart, within the bitrate
+        assert!(low.len() < 2 * 10_000 / 8 + 1000);
+        assert!(low.len() < high.len());
+        let low = snr(&wav.samples, &decode(low).1);
+        let high = snr(&wav.samples, &decode(high).1);
+        assert!(low > 10.0);
+        assert!(low < high);
+    }
+
+    #[test]
+    fn test_silence() {
+        let mut wav = Wav::new(SAMPLE_RATE);
+        wav.samples = vec![0.0; 5000];
+        let (_, decoded) = decode(encode(&wav, 96_000));
+        assert_eq!(decoded, vec![0.0; 5000]);
+        let (_, decoded) = decode(encode(&Wav::new(SAMPLE_RATE), 96_000));
+        assert!(decoded.is_empty());
+    }
+
+    #[rstest(
+        counts,
+        lengths,
+        case(vec![1, 1, 1, 1], vec![2, 2, 2, 2]),
+        case(vec![9, 3, 1, 1], vec![1, 2, 3, 3]),
+        case(vec![0, 0], vec![1, 1])
+    )]
+    fn test_huffman_lengths(counts: Vec<u32>, lengths: Vec<u8>) {
+        assert_eq!(huffman_lengths(&counts), lengths);
+    }
+
+    #[test]
+    fn test_codewords() {
+        // Example of the Vorbis specification
+        let lengths = [2, 4, 4, 4, 4, 2, 3, 3];
+        let codes: Vec<String> = codewords(&lengths)
+            .iter()
+            .zip(&lengths)
+            .map(|(c, &l)| format!("{:0width$b}", c, width = l as usize))
+            .collect();
+        assert_eq!(
+            codes,
+            vec!["00", "0100", "0101", "0110", "0111", "10", "110", "111"]
+        );
+    }
+}
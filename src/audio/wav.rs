@@ -0,0 +1,338 @@
+use crate::error::Result;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path;
+
+/// Sample rate of the rendered audio
+pub const SAMPLE_RATE: u32 = 44_100;
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xfffe;
+/// Bits of the samples written
+const BITS_PER_SAMPLE: u16 = 16;
+
+/// Custom error for bytes that cannot be read as a wav file.
+#[derive(Debug)]
+pub struct ParseWavError {
+    pub reason: String,
+}
+
+impl Error for ParseWavError {}
+
+impl fmt::Display for ParseWavError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not parse wav: {}", self.reason)
+    }
+}
+
+impl ParseWavError {
+    fn new(reason: &str) -> Self {
+        Self {
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Mono audio, the samples being between -1 and 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct Wav {
+    pub sample_rate: u32,
+    pub samples: Vec<f32>,
+}
+
+impl Wav {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Length in seconds
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate as f64
+    }
+
+    /// Read a RIFF wave file of integer (8, 16, 24 or 32 bits) or float
+    /// samples, the channels being mixed down to mono
+    pub fn from_bytes(
+        bytes: &[u8],
+    ) -> std::result::Result<Self, ParseWavError> {
+        if bytes.len() < 12
+            || &bytes[..4] != b"RIFF"
+            || &bytes[8..12] != b"WAVE"
+        {
+            return Err(ParseWavError::new("not a RIFF wave file"));
+        }
+        // Format, channels, sample rate and bits per sample
+        let mut format: Option<(u16, usize, u32, usize)> = None;
+        let mut i = 12;
+        while i + 8 <= bytes.len() {
+            let id = &bytes[i..i + 4];
+            let size = u32_at(bytes, i + 4) as usize;
+            let chunk = &bytes[i + 8..(i + 8 + size).min(bytes.len())];
+            match id {
+                b"fmt " if chunk.len() >= 16 => {
+                    let mut tag = u16_at(chunk, 0);
+                    if tag == FORMAT_EXTENSIBLE && chunk.len() >= 26 {
+                        tag = u16_at(chunk, 24);
+                    }
+                    format = Some((
+                        tag,
+                        u16_at(chunk, 2) as usize,
+                        u32_at(chunk, 4),
+                        u16_at(chunk, 14) as usize,
+                    ));
+                },
+                b"data" => {
+                    let (tag, channels, sample_rate, bits) = format
+                        .ok_or_else(|| ParseWavError::new("no fmt chunk"))?;
+                    let samples = decode(chunk, tag, channels, bits)?;
+                    return Ok(Self {
+                        sample_rate,
+                        samples,
+                    });
+                },
+                _ => {},
+            }
+            // Chunks are padded to an even size
+            i += 8 + size + size % 2;
+        }
+        Err(ParseWavError::new("no data chunk"))
+    }
+
+    /// Read the wav file at `path`
+    pub fn open<P: AsRef<path::Path>>(path: P) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        Ok(Self::from_bytes(&bytes)?)
+    }
+
+    /// Bytes of a 16-bit PCM mono file, the samples out of range being
+    /// clipped
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let block_align = BITS_PER_SAMPLE / 8;
+        let data_size = self.samples.len() as u32 * block_align as u32;
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&FORMAT_PCM.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes()); // Mono
+        bytes.extend_from_slice(&self.sample_rate.to_le_bytes());
+        let byte_rate = self.sample_rate * block_align as u32;
+        bytes.extend_from_slice(&byte_rate.to_le_bytes());
+        bytes.extend_from_slice(&block_align.to_le_bytes());
+        bytes.extend_from_slice(&BITS_PER_SAMPLE.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_size.to_le_bytes());
+        for &sample in &self.samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Write the file to `path`
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Add `samples` to the audio from the sample `offset`, the audio being
+    /// made longer if needed
+    pub fn mix(&mut self, samples: &[f32], offset: usize) {
+        if self.samples.len() < offset + samples.len() {
+            self.samples.resize(offset + samples.len(), 0.0);
+        }
+        for (a, b) in self.samples[offset..].iter_mut().zip(samples) {
+            *a += b;
+        }
+    }
+
+    /// Scale the audio down so that its highest peak is `peak`, quieter
+    /// audio being left as it is
+    pub fn limit(&mut self, peak: f32) {
+        let max = self.samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        if max > peak {
+            for sample in &mut self.samples {
+                *sample *= peak / max;
+            }
+        }
+    }
+
+    /// Scale the audio up or down so that its highest peak is `peak`
+    pub fn normalize(&mut self, peak: f32) {
+        let max = self.samples.iter().fold(0.0f32, |m, s| m.max(s.abs()));
+        if max > 0.0 {
+            for sample in &mut self.samples {
+                *sample *= peak / max;
+            }
+        }
+    }
+}
+
+/// Samples read `ratio` times faster, with a linear interpolation: a ratio
+/// of 2 halves the length and raises the pitch one octave
+pub fn resample(samples: &[f32], ratio: f64) -> Vec<f32> {
+    if samples.is_empty() || ratio <= 0.0 {
+        return Vec::new();
+    }
+    let length = ((samples.len() - 1) as f64 / ratio) as usize + 1;
+    (0..length)
+        .map(|i| {
+            let position = i as f64 * ratio;
+            let j = position as usize;
+            let fraction = (position - j as f64) as f32;
+            let next = samples.get(j + 1).copied().unwrap_or(samples[j]);
+            samples[j] + (next - samples[j]) * fraction
+        })
+        .collect()
+}
+
+fn u16_at(bytes: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([bytes[i], bytes[i + 1]])
+}
+
+fn u32_at(bytes: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]])
+}
+
+/// Samples of a data chunk, the channels of each frame being averaged
+fn decode(
+    data: &[u8],
+    tag: u16,
+    channels: usize,
+    bits: usize,
+) -> std::result::Result<Vec<f32>, ParseWavError> {
+    let width = bits / 8;
+    let sample: fn(&[u8]) -> f32 = match (tag, bits) {
+        (FORMAT_PCM, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+        (FORMAT_PCM, 16) => {
+            |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32_768.0
+        },
+        (FORMAT_PCM, 24) => |b| {
+            i32::from_le_bytes([0, b[0], b[1], b[2]]) as f32 / 2_147_483_648.0
+        },
+        (FORMAT_PCM, 32) => |b| {
+            i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32
+                / 2_147_483_648.0
+        },
+        (FORMAT_FLOAT, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => return Err(ParseWavError::new("unsupported sample format")),
+    };
+    if channels == 0 {
+        return Err(ParseWavError::new("no channel"));
+    }
+    Ok(data
+        .chunks_exact(width * channels)
+        .map(|frame| {
+            frame.chunks_exact(width).map(sample).sum::<f32>() / channels as f32
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    /// Header of a file of `channels` channels at 8 kHz
+    fn header(tag: u16, channels: u16, bits: u16, data: &[u8]) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"WAVEfmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&tag.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(&bits.to_le_bytes());
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let wav = Wav {
+            sample_rate: SAMPLE_RATE,
+            samples: vec![0.0, 0.5, -0.5, 1.0, -1.0],
+        };
+        let bytes = wav.to_bytes();
+        assert_eq!(bytes.len(), 44 + 2 * 5);
+        let read = Wav::from_bytes(&bytes).unwrap();
+        assert_eq!(read.sample_rate, SAMPLE_RATE);
+        for (a, b) in read.samples.iter().zip(&wav.samples) {
+            assert!((a - b).abs() < 0.001);
+        }
+    }
+
+    #[rstest(
+        tag,
+        channels,
+        bits,
+        data,
+        samples,
+        case(FORMAT_PCM, 1, 8, vec![128, 192], vec![0.0, 0.5]),
+        case(FORMAT_PCM, 2, 16, vec![0, 0x40, 0, 0], vec![0.25]),
+        case(FORMAT_PCM, 1, 24, vec![0, 0, 0xc0], vec![-0.5]),
+        case(FORMAT_FLOAT, 1, 32, 0.75f32.to_le_bytes().to_vec(), vec![0.75])
+    )]
+    fn test_formats(
+        tag: u16,
+        channels: u16,
+        bits: u16,
+        data: Vec<u8>,
+        samples: Vec<f32>,
+    ) {
+        let wav = Wav::from_bytes(&header(tag, channels, bits, &data)).unwrap();
+        assert_eq!(wav.sample_rate, 8000);
+        assert_eq!(wav.samples, samples);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = Wav::from_bytes(b"RIFX").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Could not parse wav: not a RIFF wave file"
+        );
+        let bytes = header(2, 1, 4, &[0]);
+        let err = Wav::from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Could not parse wav: unsupported sample format"
+        );
+    }
+
+    #[test]
+    fn test_mix_and_limit() {
+        let mut wav = Wav::new(SAMPLE_RATE);
+        wav.mix(&[0.5, 0.5], 0);
+        wav.mix(&[1.0, 1.0], 1);
+        assert_eq!(wav.samples, vec![0.5, 1.5, 1.0]);
+        wav.limit(1.0);
+        assert_eq!(wav.samples[1], 1.0);
+        wav.limit(2.0);
+        assert_eq!(wav.samples[1], 1.0);
+        wav.normalize(0.5);
+        assert_eq!(wav.samples, vec![1.0 / 6.0, 0.5, 1.0 / 3.0]);
+    }
+
+    #[rstest(
+        ratio,
+        result,
+        case(1.0, vec![0.0, 1.0, 2.0, 3.0]),
+        case(2.0, vec![0.0, 2.0]),
+        case(0.5, vec![0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0])
+    )]
+    fn test_resample(ratio: f64, result: Vec<f32>) {
+        assert_eq!(resample(&[0.0, 1.0, 2.0, 3.0], ratio), result);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::fretboard::NUMBER_POSITION;
+use crate::NUMBER_STRING;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+
+// Custom error for capos clamped past the last fret of the neck.
+#[derive(Debug)]
+pub struct CapoError {
+    pub fret: u8,
+}
+
+impl Error for CapoError {}
+
+impl fmt::Display for CapoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Capo on fret {} is past the last fret {}",
+            self.fret,
+            NUMBER_POSITION - 1
+        )
+    }
+}
+
+/// A capo clamped across some strings. A capoed string sounds from the capo
+/// instead of the nut and the frets behind the capo cannot be played.
+/// Each string has its own fret, so that partial capos (e.g. an Esus cut
+/// capo) and per-string capos (e.g. a Spider capo) can be described.
+/// Fret 0 means no capo on the string.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "CapoFrets"))]
+pub struct Capo {
+    /// Fret of each string, from the lowest string
+    frets: [u8; NUMBER_STRING as usize],
+}
+
+/// Unchecked frets of a deserialized capo
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct CapoFrets {
+    frets: [u8; NUMBER_STRING as usize],
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CapoFrets> for Capo {
+    type Error = CapoError;
+
+    fn try_from(capo: CapoFrets) -> Result<Self, Self::Error> {
+        Self::per_string(capo.frets)
+    }
+}
+
+impl Capo {
+    /// Full capo on `fret`, across all the strings
+    pub fn new(fret: u8) -> Result<Self, CapoError> {
+        Self::per_string([fret; NUMBER_STRING as usize])
+    }
+
+    /// Partial capo on `fret`, across `strings` only (begin at 1 for the
+    /// lowest string). Strings out of range are ignored.
+    pub fn partial(fret: u8, strings: &[usize]) -> Result<Self, CapoError> {
+        let mut frets = [0; NUMBER_STRING as usize];
+        for &string_number in strings {
+            if let Some(f) =
+                string_number.checked_sub(1).and_then(|i| frets.get_mut(i))
+            {
+                *f = fret;
+            }
+        }
+        Self::per_string(frets)
+    }
+
+    /// Capo with its own fret on each string, from the lowest string
+    /// (e.g. a Spider capo). Every fret must be on the neck.
+    pub fn per_string(
+        frets: [u8; NUMBER_STRING as usize],
+    ) -> Result<Self, CapoError> {
+        match frets.iter().find(|&&f| f as u16 >= NUMBER_POSITION) {
+            Some(&fret) => Err(CapoError { fret }),
+            None => Ok(Self { frets }),
+        }
+    }
+
+    /// Fret of the capo on each string, from the lowest string
+    pub fn frets(self) -> [u8; NUMBER_STRING as usize] {
+        self.frets
+    }
+
+    /// Fret of the capo on a string (begin at 1 (not 0)), 0 without capo
+    pub fn get_fret(self, string_number: usize) -> u8 {
+        string_number
+            .checked_sub(1)
+            .and_then(|i| self.frets.get(i))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Fret the frets are counted from: the fret of a capo across all the
+    /// strings, 0 without capo or for a partial or per-string capo, whose
+    /// open strings still sound from the nut
+    pub fn fret(self) -> u8 {
+        let fret = self.frets[0];
+        if self.frets.iter().all(|&f| f == fret) {
+            fret
+        } else {
+            0
+        }
+    }
+
+    /// Return `true` if there is no capo on any string
+    pub fn is_empty(self) -> bool {
+        self.frets.iter().all(|&f| f == 0)
+    }
+
+    /// Strings clamped by the same bar: `(fret, first, last)` for each run
+    /// of neighbouring strings on the same fret, strings beginning at 1
+    pub fn bars(self) -> Vec<(u8, usize, usize)> {
+        let mut bars: Vec<(u8, usize, usize)> = Vec::new();
+        for (i, &fret) in self.frets.iter().enumerate() {
+            let string_number = i + 1;
+            match bars.last_mut() {
+                Some((f, _, last))
+                    if *f == fret && *last + 1 == string_number =>
+                {
+                    *last = string_number
+                },
+                _ if fret != 0 => {
+                    bars.push((fret, string_number, string_number))
+                },
+                _ => {},
+            }
+        }
+        bars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full() {
+        let capo = Capo::new(2).unwrap();
+        assert!(!capo.is_empty());
+        assert!(Capo::default().is_empty());
+        assert!(Capo::new(0).unwrap().is_empty());
+        for string_number in 1..=NUMBER_STRING as usize {
+            assert_eq!(capo.get_fret(string_number), 2);
+        }
+        assert_eq!(capo.get_fret(0), 0);
+        assert_eq!(capo.get_fret(7), 0);
+        assert_eq!(capo.bars(), vec![(2, 1, 6)]);
+        assert_eq!(capo.fret(), 2);
+    }
+
+    #[test]
+    fn test_partial() {
+        // Esus cut capo: strings 5 to 3 as guitarists count them
+        let capo = Capo::partial(2, &[2, 3, 4, 9]).unwrap();
+        assert_eq!(capo.frets(), [0, 2, 2, 2, 0, 0]);
+        assert_eq!(capo.bars(), vec![(2, 2, 4)]);
+        assert_eq!(capo.fret(), 0);
+    }
+
+    #[test]
+    fn test_per_string() {
+        let capo = Capo::per_string([2, 2, 0, 3, 3, 2]).unwrap();
+        assert_eq!(capo.bars(), vec![(2, 1, 2), (3, 4, 5), (2, 6, 6)]);
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert!(Capo::new(NUMBER_POSITION as u8 - 1).is_ok());
+        assert_eq!(Capo::new(NUMBER_POSITION as u8).unwrap_err().fret, 19);
+        assert_eq!(Capo::partial(40, &[2, 3]).unwrap_err().fret, 40);
+        assert!(Capo::partial(40, &[9]).is_ok());
+        let err = Capo::per_string([0, 2, 2, 25, 0, 0]).unwrap_err();
+        assert_eq!(err.to_string(), "Capo on fret 25 is past the last fret 18");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let capo = Capo::partial(2, &[2, 3, 4]).unwrap();
+        let json = serde_json::to_string(&capo).unwrap();
+        assert_eq!(json, r#"{"frets":[0,2,2,2,0,0]}"#);
+        assert_eq!(serde_json::from_str::<Capo>(&json).unwrap(), capo);
+        let json = r#"{"frets":[0,2,2,30,0,0]}"#;
+        assert!(serde_json::from_str::<Capo>(json).is_err());
+    }
+}
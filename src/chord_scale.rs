@@ -0,0 +1,184 @@
+use crate::capo::Capo;
+use crate::note::{Note, TraitNoteSemitones};
+use crate::pitch_class_set::PitchClassSet;
+use crate::scale::{Scale, ScaleType};
+use crate::scale_catalogue::ScaleFamily;
+use crate::tuning::Tuning;
+use strum::IntoEnumIterator;
+use ukebox::{Chord, PitchClass, Semitones};
+
+/// Bonus for scales built on the root of the chord
+const SCORE_ROOT: i32 = 100;
+/// Penalty for each avoid note of the scale
+const SCORE_AVOID_NOTE: i32 = 5;
+/// Penalty for each note more or less than a seven notes scale
+const SCORE_NOTE_COUNT: i32 = 2;
+
+/// A scale that fits a chord, with its notes sorted by role
+/// https://en.wikipedia.org/wiki/Chord-scale_system
+#[derive(Debug, Clone)]
+pub struct ChordScale {
+    pub scale: Scale,
+    pub score: i32,
+    pub chord_tones: Vec<Note>,
+    /// Scale notes that can be added to the chord
+    pub tensions: Vec<Note>,
+    /// Scale notes a half step above a chord tone, which clash with the
+    /// chord when held
+    pub avoid_notes: Vec<Note>,
+}
+
+pub trait TraitChordScale {
+    /// Get all scales containing every note of the chord, best first
+    fn chord_scales(&self, tuning: Tuning) -> Vec<ChordScale>;
+}
+
+impl TraitChordScale for Chord {
+    fn chord_scales(&self, tuning: Tuning) -> Vec<ChordScale> {
+        let chord_pitch_classes: Vec<PitchClass> =
+            self.notes().map(|n| n.pitch_class).collect();
+        let chord_set = PitchClassSet::from_pitch_classes(&chord_pitch_classes);
+
+        let mut vec_chord_scale: Vec<ChordScale> = Vec::new();
+        for scale_type in ScaleType::iter() {
+            for t in 0..12 {
+                let root = self.root.pitch_class;
+                let tonic = if t == 0 {
+                    Note::from(self.root)
+                } else {
+                    Note::from_semitones(root as Semitones + t)
+                };
+                let scale = Scale {
+                    scale_type,
+                    tuning,
+                    tonic,
+                    capo: Capo::default(),
+                };
+                let scale_set = scale.pitch_class_set();
+                let fits = chord_pitch_classes
+                    .iter()
+                    .all(|&pc| scale_set.contains(pc));
+                if !fits {
+                    continue;
+                }
+                vec_chord_scale.push(ChordScale::new(scale, chord_set, t == 0));
+            }
+        }
+        // Stable sort: on equal scores the order of `ScaleType` is kept
+        vec_chord_scale.sort_by_key(|c| -c.score);
+        vec_chord_scale
+    }
+}
+
+impl ChordScale {
+    fn new(scale: Scale, chord_set: PitchClassSet, sw_root: bool) -> Self {
+        let mut chord_tones: Vec<Note> = Vec::new();
+        let mut tensions: Vec<Note> = Vec::new();
+        let mut avoid_notes: Vec<Note> = Vec::new();
+        for note in scale.notes() {
+            if chord_set.contains(note.pitch_class) {
+                chord_tones.push(note);
+            } else if chord_set.contains(note.pitch_class - 1) {
+                avoid_notes.push(note);
+            } else {
+                tensions.push(note);
+            }
+        }
+
+        let mut score = family_score(scale.scale_type.family());
+        if sw_root {
+            score += SCORE_ROOT;
+        }
+        score -= SCORE_AVOID_NOTE * avoid_notes.len() as i32;
+        score -=
+            SCORE_NOTE_COUNT * (scale.scale_type.note_count() as i32 - 7).abs();
+
+        Self {
+            scale,
+            score,
+            chord_tones,
+            tensions,
+            avoid_notes,
+        }
+    }
+}
+
+/// How common the scales of a family are in chord-scale theory
+fn family_score(family: ScaleFamily) -> i32 {
+    use ScaleFamily::*;
+    match family {
+        Diatonic => 40,
+        MelodicMinor => 25,
+        HarmonicMinor | Pentatonic => 20,
+        Symmetric | Blues => 15,
+        HarmonicMajor | DoubleHarmonic | Bebop => 10,
+        _ => 5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::str::FromStr;
+    use ScaleType::*;
+
+    #[rstest(
+        chord,
+        scale_type,
+        tonic,
+        case("G7", Mixolydian, "G"),
+        case("Bm7b5", Locrian, "B"),
+        case("CmMaj7", MelodicMinor, "C"),
+        case("Dm7", Dorian, "D"),
+        case("Cmaj7", Lydian, "C"),
+        case("Cdim7", WholeHalfDiminished, "C")
+    )]
+    fn test_best_chord_scale(chord: &str, scale_type: ScaleType, tonic: &str) {
+        let chord = Chord::from_str(chord).unwrap();
+        let best = &chord.chord_scales(Tuning::E)[0];
+        assert_eq!(best.scale.scale_type, scale_type);
+        assert_eq!(best.scale.tonic, Note::from_str(tonic).unwrap());
+    }
+
+    #[test]
+    fn test_avoid_notes() {
+        let chord = Chord::from_str("G7").unwrap();
+        let chord_scales = chord.chord_scales(Tuning::E);
+        let mixolydian = chord_scales
+            .iter()
+            .find(|c| c.scale.scale_type == Mixolydian)
+            .unwrap();
+        assert_eq!(mixolydian.avoid_notes, vec![Note::from_str("C").unwrap()]);
+        assert_eq!(mixolydian.chord_tones.len(), 4);
+        assert_eq!(mixolydian.tensions.len(), 2);
+    }
+
+    #[test]
+    fn test_every_scale_contains_the_chord() {
+        let chord = Chord::from_str("Ebaug").unwrap();
+        for chord_scale in chord.chord_scales(Tuning::E) {
+            assert_eq!(chord_scale.chord_tones.len(), 3);
+        }
+    }
+
+    #[rstest(
+        chord,
+        best,
+        case("Cmaj7", vec![Lydian, Major, Augmented]),
+        case("G7", vec![Mixolydian, LydianDominant])
+    )]
+    fn test_bebop_ranking(chord: &str, best: Vec<ScaleType>) {
+        // Bebop scales hold every chord tone, they must not outrank the
+        // usual scales of the chord
+        let chord = Chord::from_str(chord).unwrap();
+        let top: Vec<ScaleType> = chord
+            .chord_scales(Tuning::E)
+            .iter()
+            .take(3)
+            .map(|c| c.scale.scale_type)
+            .collect();
+        assert_eq!(top[..best.len()], best[..]);
+        assert!(top.iter().all(|s| s.family() != ScaleFamily::Bebop));
+    }
+}
@@ -0,0 +1,205 @@
+use crate::arpeggio::ParseChordError;
+use crate::audio::ParseWavError;
+use crate::capo::CapoError;
+use crate::interval::ParseIntervalError;
+use crate::note::ParseNoteError;
+use crate::pitch::ParsePitchError;
+use crate::progression::ParseProgressionError;
+use crate::scale::ParseScaleError;
+use crate::svg_draw::ParseThemeError;
+use crate::tuning::ParseTuningError;
+use std::fmt;
+use std::io;
+
+/// Any error of the crate, so that callers can use `?` on every fallible
+/// function and never have to unwrap
+#[derive(Debug)]
+pub enum Error {
+    Note(ParseNoteError),
+    Pitch(ParsePitchError),
+    Interval(ParseIntervalError),
+    Scale(ParseScaleError),
+    Tuning(ParseTuningError),
+    Theme(ParseThemeError),
+    Progression(ParseProgressionError),
+    Chord(ParseChordError),
+    Capo(CapoError),
+    Wav(ParseWavError),
+    Io(io::Error),
+    #[cfg(feature = "serde")]
+    Json(serde_json::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Note(err) => err.fmt(f),
+            Error::Pitch(err) => err.fmt(f),
+            Error::Interval(err) => err.fmt(f),
+            Error::Scale(err) => err.fmt(f),
+            Error::Tuning(err) => err.fmt(f),
+            Error::Theme(err) => err.fmt(f),
+            Error::Progression(err) => err.fmt(f),
+            Error::Chord(err) => err.fmt(f),
+            Error::Capo(err) => err.fmt(f),
+            Error::Wav(err) => err.fmt(f),
+            Error::Io(err) => err.fmt(f),
+            #[cfg(feature = "serde")]
+            Error::Json(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Note(err) => Some(err),
+            Error::Pitch(err) => Some(err),
+            Error::Interval(err) => Some(err),
+            Error::Scale(err) => Some(err),
+            Error::Tuning(err) => Some(err),
+            Error::Theme(err) => Some(err),
+            Error::Progression(err) => Some(err),
+            Error::Chord(err) => Some(err),
+            Error::Capo(err) => Some(err),
+            Error::Wav(err) => Some(err),
+            Error::Io(err) => Some(err),
+            #[cfg(feature = "serde")]
+            Error::Json(err) => Some(err),
+        }
+    }
+}
+
+impl From<ParseNoteError> for Error {
+    fn from(err: ParseNoteError) -> Self {
+        Error::Note(err)
+    }
+}
+
+impl From<ParsePitchError> for Error {
+    fn from(err: ParsePitchError) -> Self {
+        Error::Pitch(err)
+    }
+}
+
+impl From<ParseIntervalError> for Error {
+    fn from(err: ParseIntervalError) -> Self {
+        Error::Interval(err)
+    }
+}
+
+impl From<ParseScaleError> for Error {
+    fn from(err: ParseScaleError) -> Self {
+        Error::Scale(err)
+    }
+}
+
+impl From<ParseTuningError> for Error {
+    fn from(err: ParseTuningError) -> Self {
+        Error::Tuning(err)
+    }
+}
+
+impl From<ParseThemeError> for Error {
+    fn from(err: ParseThemeError) -> Self {
+        Error::Theme(err)
+    }
+}
+
+impl From<ParseProgressionError> for Error {
+    fn from(err: ParseProgressionError) -> Self {
+        Error::Progression(err)
+    }
+}
+
+impl From<ParseChordError> for Error {
+    fn from(err: ParseChordError) -> Self {
+        Error::Chord(err)
+    }
+}
+
+impl From<CapoError> for Error {
+    fn from(err: CapoError) -> Self {
+        Error::Capo(err)
+    }
+}
+
+impl From<ParseWavError> for Error {
+    fn from(err: ParseWavError) -> Self {
+        Error::Wav(err)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capo::Capo;
+    use crate::note::Note;
+    use crate::scale::{Scale, ScaleType};
+    use crate::svg_draw::{DrawScale, Theme};
+    use crate::tuning::Tuning;
+    use rstest::rstest;
+    use std::error::Error as _;
+    use std::str::FromStr;
+
+    #[rstest(
+        tonic,
+        scale_type,
+        tuning,
+        message,
+        case("H", "major", "E", "Could not parse note name \"H\""),
+        case("C", "majr", "E", "Could not parse scale \"majr\""),
+        case("C", "major", "D", "Could not parse tuning name \"D\"")
+    )]
+    fn test_scale_from_names(
+        tonic: &str,
+        scale_type: &str,
+        tuning: &str,
+        message: &str,
+    ) {
+        let err = Scale::from_names(tonic, scale_type, tuning).unwrap_err();
+        assert_eq!(err.to_string(), message);
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_draw_scale_from_names() {
+        let draw = DrawScale::from_names("Bb", "dorian", "E", "D").unwrap();
+        assert_eq!(draw.scale.scale_type, ScaleType::Dorian);
+        assert_eq!(draw.scale.tonic, Note::from_str("Bb").unwrap());
+        match DrawScale::from_names("Bb", "dorian", "E", "X") {
+            Err(err) => {
+                assert_eq!(err.to_string(), "Could not parse theme \"X\"")
+            },
+            Ok(_) => panic!("\"X\" is not a theme"),
+        }
+    }
+
+    #[test]
+    fn test_save_io_error() {
+        let scale = Scale {
+            scale_type: ScaleType::Major,
+            tuning: Tuning::E,
+            tonic: Note::from_str("C").unwrap(),
+            capo: Capo::default(),
+        };
+        let draw = DrawScale::new(scale, Theme::Light);
+        let err = draw.save("/nonexistent/scale.svg").unwrap_err();
+        assert!(matches!(err, Error::Io(_)));
+    }
+}
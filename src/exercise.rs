@@ -0,0 +1,390 @@
+use crate::fingering::{Finger, ScaleBox};
+use crate::midi::{MidiFile, MidiNote, TICKS_PER_BEAT, VELOCITY_DEFAULT};
+use crate::note::Note;
+use crate::pitch::Pitch;
+use crate::scale::Scale;
+
+/// Beats of a bar in the tab
+const BEATS_PER_BAR: usize = 4;
+/// Notes of a chord arpeggiated by `Pattern::Arpeggios`
+const ARPEGGIO_NOTES: usize = 3;
+
+/// Order in which the notes of a box are practised
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Pattern {
+    /// Every note once, as in the scale
+    Straight,
+    /// Each note followed by the note `n` degrees above, e.g. 2 for thirds
+    /// and 3 for fourths
+    Intervals(usize),
+    /// Groups of `n` notes, each group beginning one note higher
+    Groups(usize),
+    /// Groups of three notes played as triplets
+    Triplets,
+    /// The triad built on each degree of the scale
+    Arpeggios,
+}
+
+impl Pattern {
+    /// Notes played on a beat: eighths, triplets or sixteenths
+    pub fn notes_per_beat(self) -> usize {
+        match self {
+            Pattern::Triplets => 3,
+            Pattern::Groups(n) if n % 4 == 0 => 4,
+            _ => 2,
+        }
+    }
+
+    /// Indexes of the notes to play, from the lowest of `count` notes.
+    /// `degrees` is the number of notes of the scale.
+    fn indexes(self, count: usize, degrees: usize) -> Vec<usize> {
+        match self {
+            Pattern::Straight => (0..count).collect(),
+            Pattern::Intervals(n) => (0..count.saturating_sub(n))
+                .flat_map(|i| vec![i, i + n])
+                .collect(),
+            Pattern::Groups(n) => (0..(count + 1).saturating_sub(n))
+                .flat_map(|i| i..i + n)
+                .collect(),
+            Pattern::Triplets => Pattern::Groups(3).indexes(count, degrees),
+            Pattern::Arpeggios => (0..degrees)
+                .filter(|i| i + 2 * (ARPEGGIO_NOTES - 1) < count)
+                .flat_map(|i| (0..ARPEGGIO_NOTES).map(move |n| i + 2 * n))
+                .collect(),
+        }
+    }
+}
+
+/// Way up or down the box
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Direction {
+    Ascending,
+    Descending,
+    /// Up then down
+    AscendingDescending,
+}
+
+/// A note of an exercise
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Event {
+    /// Begin at 1 for the lowest string
+    pub string_number: usize,
+    pub position: usize,
+    pub note: Note,
+    pub pitch: Pitch,
+    pub finger: Finger,
+}
+
+/// A practice sequence of a scale, played within one box
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Exercise {
+    pub scale: Scale,
+    pub pattern: Pattern,
+    pub direction: Direction,
+    /// Notes in playing order
+    pub events: Vec<Event>,
+}
+
+impl Exercise {
+    /// Exercise of `scale` within `scale_box`. The notes of the box behind
+    /// the capo of `scale`, e.g. of a box found before the capo was put on,
+    /// cannot be played and are left out.
+    pub fn new(
+        scale: Scale,
+        scale_box: &ScaleBox,
+        pattern: Pattern,
+        direction: Direction,
+    ) -> Self {
+        let strings = scale.get_string_combination();
+        // The notes of a box are ascending
+        let notes: Vec<Event> = scale_box
+            .notes
+            .iter()
+            .filter_map(|n| {
+                strings[n.string_number - 1]
+                    .degree_single_string
+                    .iter()
+                    .find(|v| v.position == n.position && !v.sw_behind_capo)
+                    .map(|v| Event {
+                        string_number: n.string_number,
+                        position: n.position,
+                        note: v.note,
+                        pitch: v.pitch,
+                        finger: n.finger,
+                    })
+            })
+            .collect();
+        let count = notes.len();
+        let up = pattern.indexes(count, scale.notes().len());
+        // Going down mirrors the pattern from the highest note, which is
+        // not repeated on the way down
+        let down = up.iter().map(|&i| count - 1 - i);
+        let indexes: Vec<usize> = match direction {
+            Direction::Ascending => up.clone(),
+            Direction::Descending => down.collect(),
+            Direction::AscendingDescending => {
+                up.iter().copied().chain(down.skip(1)).collect()
+            },
+        };
+        Self {
+            scale,
+            pattern,
+            direction,
+            events: indexes.into_iter().map(|i| notes[i]).collect(),
+        }
+    }
+
+    /// Tablature of the exercise, the highest string at the top. The frets
+    /// are counted from the capo, the notes behind it being left out, and a
+    /// bar lasts four beats.
+    pub fn to_tab(&self) -> String {
+        let bar = BEATS_PER_BAR * self.pattern.notes_per_beat();
+        let names: Vec<String> = self
+            .scale
+            .get_open_strings()
+            .iter()
+            .map(|p| p.note.to_string())
+            .collect();
+        let name_width = names.iter().map(|n| n.len()).max().unwrap_or(0);
+        let mut lines: Vec<String> = names
+            .iter()
+            .map(|n| format!("{:<width$}|", n, width = name_width))
+            .collect();
+        let frets = self.events.iter().filter_map(|event| {
+            let capo_fret = self.scale.capo.get_fret(event.string_number);
+            let fret = event.position.checked_sub(capo_fret as usize)?;
+            Some((event, fret))
+        });
+        for (i, (event, fret)) in frets.enumerate() {
+            if i > 0 && i % bar == 0 {
+                for line in lines.iter_mut() {
+                    line.push_str("-|");
+                }
+            }
+            let fret = fret.to_string();
+            for (s, line) in lines.iter_mut().enumerate() {
+                let column = if s + 1 == event.string_number {
+                    fret.clone()
+                } else {
+                    "-".repeat(fret.len())
+                };
+                line.push('-');
+                line.push_str(&column);
+            }
+        }
+        lines
+            .iter()
+            .rev()
+            .map(|line| format!("{}-|\n", line))
+            .collect()
+    }
+
+    /// MIDI file of the exercise at `tempo` beats per minute
+    pub fn to_midi(&self, tempo: u16) -> MidiFile {
+        let duration =
+            TICKS_PER_BEAT as u32 / self.pattern.notes_per_beat() as u32;
+        let mut midi = MidiFile::new(tempo);
+        midi.notes = self
+            .events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| MidiNote {
+                start: i as u32 * duration,
+                duration,
+                key: event.pitch.midi(),
+                velocity: VELOCITY_DEFAULT,
+            })
+            .collect();
+        midi
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capo::Capo;
+    use crate::fingering::FingeringSettings;
+    use rstest::rstest;
+
+    fn first_box(scale: Scale) -> ScaleBox {
+        scale.get_boxes(FingeringSettings::default())[0].clone()
+    }
+
+    fn names(exercise: &Exercise) -> Vec<String> {
+        exercise
+            .events
+            .iter()
+            .map(|e| e.pitch.to_string())
+            .collect()
+    }
+
+    #[rstest(
+        pattern,
+        result,
+        case(Pattern::Straight, vec![0, 1, 2, 3, 4]),
+        case(Pattern::Intervals(2), vec![0, 2, 1, 3, 2, 4]),
+        case(Pattern::Intervals(3), vec![0, 3, 1, 4]),
+        case(Pattern::Groups(3), vec![0, 1, 2, 1, 2, 3, 2, 3, 4]),
+        case(Pattern::Groups(4), vec![0, 1, 2, 3, 1, 2, 3, 4]),
+        case(Pattern::Triplets, vec![0, 1, 2, 1, 2, 3, 2, 3, 4]),
+        case(Pattern::Arpeggios, vec![0, 2, 4])
+    )]
+    fn test_indexes(pattern: Pattern, result: Vec<usize>) {
+        assert_eq!(pattern.indexes(5, 7), result);
+    }
+
+    #[test]
+    fn test_thirds() {
+        let scale = Scale::from_names("G", "major", "E").unwrap();
+        let exercise = Exercise::new(
+            scale,
+            &first_box(scale),
+            Pattern::Intervals(2),
+            Direction::Ascending,
+        );
+        assert_eq!(
+            names(&exercise)[..6],
+            ["E2", "G2", "F#2", "A2", "G2", "B2"]
+        );
+    }
+
+    #[test]
+    fn test_directions() {
+        let scale = Scale::from_names("A", "minor pentatonic", "E").unwrap();
+        let scale_box =
+            scale.get_boxes(FingeringSettings::default())[1].clone();
+        let up = Exercise::new(
+            scale,
+            &scale_box,
+            Pattern::Straight,
+            Direction::Ascending,
+        );
+        let down = Exercise::new(
+            scale,
+            &scale_box,
+            Pattern::Straight,
+            Direction::Descending,
+        );
+        let both = Exercise::new(
+            scale,
+            &scale_box,
+            Pattern::Straight,
+            Direction::AscendingDescending,
+        );
+        assert_eq!(up.events.len(), scale_box.notes.len());
+        assert_eq!(names(&up)[0], "G2");
+        let mut reversed = names(&down);
+        reversed.reverse();
+        assert_eq!(reversed, names(&up));
+        assert_eq!(both.events.len(), 2 * up.events.len() - 1);
+        let top = up.events.len() - 1;
+        assert_eq!(names(&both)[..=top], names(&up)[..]);
+        assert_eq!(names(&both)[top..], names(&down)[..]);
+    }
+
+    #[test]
+    fn test_arpeggios() {
+        let scale = Scale::from_names("C", "major", "E").unwrap();
+        let scale_box = ScaleBox::new(scale, 8, FingeringSettings::default());
+        let exercise = Exercise::new(
+            scale,
+            &scale_box,
+            Pattern::Arpeggios,
+            Direction::Ascending,
+        );
+        let names = names(&exercise);
+        // C, Dm, Em, F, G, Am and Bdim
+        assert_eq!(names.len(), 7 * 3);
+        assert_eq!(names[..6], ["C3", "E3", "G3", "D3", "F3", "A3"]);
+        assert_eq!(names[18..], ["B3", "D4", "F4"]);
+    }
+
+    #[test]
+    fn test_to_tab() {
+        let scale = Scale::from_names("E", "minor pentatonic", "E").unwrap();
+        let exercise = Exercise::new(
+            scale,
+            &first_box(scale),
+            Pattern::Straight,
+            Direction::Ascending,
+        );
+        assert_eq!(
+            exercise.to_tab(),
+            "E|-----------------|-----0-3-5-|\n\
+             B|-----------------|-0-3-------|\n\
+             G|-------------0-2-|-----------|\n\
+             D|---------0-2-----|-----------|\n\
+             A|-----0-2---------|-----------|\n\
+             E|-0-3-------------|-----------|\n"
+        );
+    }
+
+    #[test]
+    fn test_to_tab_capo() {
+        let mut scale =
+            Scale::from_names("F#", "minor pentatonic", "E").unwrap();
+        scale.capo = Capo::new(2).unwrap();
+        let exercise = Exercise::new(
+            scale,
+            &first_box(scale),
+            Pattern::Straight,
+            Direction::Ascending,
+        );
+        let tab = exercise.to_tab();
+        assert!(tab.starts_with("F#|"));
+        assert!(tab.ends_with("F#|-0-3-------------|-----------|\n"));
+    }
+
+    #[test]
+    fn test_box_behind_capo() {
+        // A box found without capo, its open strings behind the capo
+        let mut scale =
+            Scale::from_names("E", "minor pentatonic", "E").unwrap();
+        let scale_box = first_box(scale);
+        scale.capo = Capo::new(2).unwrap();
+        let mut exercise = Exercise::new(
+            scale,
+            &scale_box,
+            Pattern::Straight,
+            Direction::Ascending,
+        );
+        assert_eq!(exercise.events.len(), 7);
+        assert!(exercise.events.iter().all(|e| e.position >= 2));
+        assert_eq!(
+            exercise.to_tab(),
+            "F#|-----------1-3-|\n\
+             C#|---------1-----|\n\
+             A |-------0-------|\n\
+             E |-----0---------|\n\
+             B |---0-----------|\n\
+             F#|-1-------------|\n"
+        );
+        // Events behind the capo are left out of the tab
+        let tab = exercise.to_tab();
+        let mut behind = exercise.events[0];
+        behind.position = 0;
+        exercise.events.insert(0, behind);
+        assert_eq!(exercise.to_tab(), tab);
+    }
+
+    #[test]
+    fn test_to_midi() {
+        let scale = Scale::from_names("E", "minor pentatonic", "E").unwrap();
+        let exercise = Exercise::new(
+            scale,
+            &first_box(scale),
+            Pattern::Triplets,
+            Direction::Ascending,
+        );
+        let midi = exercise.to_midi(90);
+        assert_eq!(midi.tempo, 90);
+        assert_eq!(midi.notes.len(), exercise.events.len());
+        assert_eq!(midi.notes[0].key, 40);
+        assert_eq!(midi.notes[1].start, 160);
+        assert_eq!(midi.notes[1].duration, 160);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::scale::{DegreeSingleString, Scale};
+use std::fmt;
+use ukebox::Semitones;
+
+/// Frets covered by the four fingers without stretch
+pub(crate) const HAND_SPAN: usize = 4;
+
+/// Finger of the fretting hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Finger {
+    /// Open string, or string fretted by the capo
+    Open,
+    Index,
+    Middle,
+    Ring,
+    Pinky,
+    /// Thumb over the neck, on the lowest string
+    Thumb,
+}
+
+impl Finger {
+    /// Finger of a number, 1 being the index
+    fn from_number(n: usize) -> Self {
+        match n {
+            1 => Finger::Index,
+            2 => Finger::Middle,
+            3 => Finger::Ring,
+            _ => Finger::Pinky,
+        }
+    }
+}
+
+impl fmt::Display for Finger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Finger::Open => "0",
+            Finger::Index => "1",
+            Finger::Middle => "2",
+            Finger::Ring => "3",
+            Finger::Pinky => "4",
+            Finger::Thumb => "T",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+/// How far the hand may move away from one finger per fret
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FingeringSettings {
+    /// Frets reached by the index below the hand and by the pinky above it
+    pub stretch: usize,
+    /// Frets the hand may shift up while going up the strings
+    pub max_shift: usize,
+    /// Play the notes below the hand on the lowest string with the thumb
+    pub thumb: bool,
+}
+
+impl Default for FingeringSettings {
+    fn default() -> Self {
+        Self {
+            stretch: 1,
+            max_shift: 1,
+            thumb: false,
+        }
+    }
+}
+
+/// A note of a box and the finger playing it
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FingeredNote {
+    /// Begin at 1 for the lowest string
+    pub string_number: usize,
+    pub position: usize,
+    pub finger: Finger,
+}
+
+/// A scale box: every note of the scale once, from the lowest string to
+/// the highest, played around one hand position
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScaleBox {
+    /// Fret of the index finger on the lowest string
+    pub hand: usize,
+    /// String by string from the lowest, fret by fret
+    pub notes: Vec<FingeredNote>,
+}
+
+impl ScaleBox {
+    /// Box beginning with the note at `anchor` on the lowest string
+    pub fn new(
+        scale: Scale,
+        anchor: usize,
+        settings: FingeringSettings,
+    ) -> Self {
+        let strings = scale.get_string_combination();
+        let open_low = scale.capo.get_fret(1) as usize;
+        let mut hand = if anchor <= open_low {
+            open_low + 1
+        } else {
+            anchor
+        };
+        let first_hand = hand;
+        let last_hand = hand + settings.max_shift;
+        let mut last_midi: Option<Semitones> = None;
+        let mut notes: Vec<FingeredNote> = Vec::new();
+        for (i, degree_all_strings) in strings.iter().enumerate() {
+            let string_number = degree_all_strings.string_number;
+            let open = scale.capo.get_fret(string_number) as usize;
+            let thumb = settings.thumb && i == 0;
+            // Shift the hand up only if the notes do not fit, and keep it
+            // shifted for the higher strings
+            let mut best: Option<(usize, Vec<(usize, Finger)>)> = None;
+            for h in hand..=last_hand.max(hand) {
+                let (low, high) = window(h, open, settings.stretch);
+                // The thumb reaches one fret further than the index
+                let low = match i {
+                    0 if thumb => window(h, open, settings.stretch + 1).0,
+                    0 => anchor.max(open),
+                    _ => low,
+                };
+                // The next string takes over from its lowest note, unless
+                // the index would have to fret it and the note above
+                let next_midi = strings.get(i + 1).and_then(|s| {
+                    let open = scale.capo.get_fret(s.string_number) as usize;
+                    let (low, high) = window(h, open, settings.stretch);
+                    let mut next = playable(&s.degree_single_string, low, high);
+                    match (next.next(), next.next()) {
+                        (Some(a), Some(b))
+                            if a.position != open
+                                && a.position < h
+                                && b.position <= h =>
+                        {
+                            Some(b.pitch.midi())
+                        },
+                        (a, _) => a.map(|v| v.pitch.midi()),
+                    }
+                });
+                let positions: Vec<usize> = playable(
+                    &degree_all_strings.degree_single_string,
+                    low,
+                    high,
+                )
+                .filter(|v| last_midi.map_or(true, |m| v.pitch.midi() > m))
+                .filter(|v| next_midi.map_or(true, |m| v.pitch.midi() < m))
+                .map(|v| v.position)
+                .collect();
+                let (fingered, complete) =
+                    assign_fingers(&positions, h, open, thumb);
+                if complete
+                    || best
+                        .as_ref()
+                        .map_or(true, |b| fingered.len() > b.1.len())
+                {
+                    best = Some((h, fingered));
+                }
+                if complete {
+                    break;
+                }
+            }
+            let (h, fingered) = best.unwrap_or_default();
+            hand = h.max(hand);
+            for (position, finger) in fingered {
+                let midi = degree_all_strings
+                    .degree_single_string
+                    .iter()
+                    .find(|v| v.position == position)
+                    .map(|v| v.pitch.midi());
+                last_midi = midi.max(last_midi);
+                notes.push(FingeredNote {
+                    string_number,
+                    position,
+                    finger,
+                });
+            }
+        }
+        Self {
+            hand: first_hand,
+            notes,
+        }
+    }
+
+    /// Finger playing a note of the box, `None` if the note is not in it
+    pub fn finger(
+        &self,
+        string_number: usize,
+        position: usize,
+    ) -> Option<Finger> {
+        self.notes
+            .iter()
+            .find(|n| {
+                n.string_number == string_number && n.position == position
+            })
+            .map(|n| n.finger)
+    }
+}
+
+/// Frets reached with the index on `hand`. Open strings are always reached
+/// near the nut (or the capo).
+fn window(hand: usize, open: usize, stretch: usize) -> (usize, usize) {
+    let low = hand.saturating_sub(stretch);
+    let low = if low <= open + 1 { open } else { low };
+    (low, hand + HAND_SPAN - 1 + stretch)
+}
+
+/// Notes of a string that can be played between `low` and `high`
+fn playable(
+    degrees: &[DegreeSingleString],
+    low: usize,
+    high: usize,
+) -> impl Iterator<Item = &DegreeSingleString> {
+    degrees.iter().filter(move |v| {
+        !v.sw_behind_capo && (low..=high).contains(&v.position)
+    })
+}
+
+/// One finger per fret from the index on `hand`, the index and the pinky
+/// stretching out of it. Two notes of a string needing the same finger
+/// cannot be played together: the higher one is left out and the result is
+/// not complete.
+fn assign_fingers(
+    positions: &[usize],
+    hand: usize,
+    open: usize,
+    mut thumb: bool,
+) -> (Vec<(usize, Finger)>, bool) {
+    let mut fingered: Vec<(usize, Finger)> = Vec::new();
+    let mut complete = true;
+    let mut last = 0;
+    for &position in positions {
+        if position == open {
+            fingered.push((position, Finger::Open));
+        } else if thumb && position < hand && last == 0 {
+            thumb = false;
+            fingered.push((position, Finger::Thumb));
+        } else {
+            let n = (position + 1).saturating_sub(hand).clamp(1, HAND_SPAN);
+            if n <= last {
+                complete = false;
+                continue;
+            }
+            last = n;
+            fingered.push((position, Finger::from_number(n)));
+        }
+    }
+    (fingered, complete)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capo::Capo;
+    use rstest::rstest;
+    use ukebox::PitchClass;
+
+    fn frets(scale_box: &ScaleBox, string_number: usize) -> Vec<String> {
+        scale_box
+            .notes
+            .iter()
+            .filter(|n| n.string_number == string_number)
+            .map(|n| format!("{}:{}", n.position, n.finger))
+            .collect()
+    }
+
+    #[test]
+    fn test_open_box() {
+        let scale = Scale::from_names("E", "minor pentatonic", "E").unwrap();
+        let boxes = scale.get_boxes(FingeringSettings::default());
+        assert_eq!(boxes.len(), 5);
+        let open = &boxes[0];
+        assert_eq!(frets(open, 1), vec!["0:0", "3:3"]);
+        assert_eq!(frets(open, 2), vec!["0:0", "2:2"]);
+        assert_eq!(frets(open, 5), vec!["0:0", "3:3"]);
+        assert_eq!(open.finger(2, 2), Some(Finger::Middle));
+        assert_eq!(open.finger(2, 5), None);
+    }
+
+    #[rstest(
+        tonic,
+        scale_type,
+        case("A", "minor pentatonic"),
+        case("G", "major"),
+        case("C", "harmonic minor"),
+        case("F#", "dorian")
+    )]
+    fn test_each_note_once(tonic: &str, scale_type: &str) {
+        let scale = Scale::from_names(tonic, scale_type, "E").unwrap();
+        let strings = scale.get_string_combination();
+        for scale_box in scale.get_boxes(FingeringSettings::default()) {
+            let midi: Vec<Semitones> = scale_box
+                .notes
+                .iter()
+                .map(|n| {
+                    strings[n.string_number - 1]
+                        .degree_single_string
+                        .iter()
+                        .find(|v| v.position == n.position)
+                        .unwrap()
+                        .pitch
+                        .midi()
+                })
+                .collect();
+            // Every note of the scale once, ascending, on two octaves
+            let set = scale.pitch_class_set();
+            let first = midi[0];
+            let last = *midi.last().unwrap();
+            let in_scale = (first..=last)
+                .filter(|&m| set.contains(PitchClass::from(m)))
+                .count();
+            assert_eq!(midi.len(), in_scale);
+            assert!(midi.windows(2).all(|w| w[0] < w[1]));
+            assert!(midi.len() >= 2 * scale.notes().len());
+            // One finger per fret on each string
+            for string_number in 1..=6 {
+                let fingers: Vec<Finger> = scale_box
+                    .notes
+                    .iter()
+                    .filter(|n| n.string_number == string_number)
+                    .map(|n| n.finger)
+                    .filter(|&f| f != Finger::Open)
+                    .collect();
+                let mut unique = fingers.clone();
+                unique.dedup();
+                assert_eq!(unique, fingers);
+            }
+        }
+    }
+
+    #[test]
+    fn test_thumb() {
+        let scale = Scale::from_names("A", "major", "E").unwrap();
+        let settings = FingeringSettings {
+            thumb: true,
+            ..FingeringSettings::default()
+        };
+        let scale_box = ScaleBox::new(scale, 4, settings);
+        assert_eq!(frets(&scale_box, 1), vec!["2:T", "4:1", "5:2", "7:4"]);
+        let scale_box = ScaleBox::new(scale, 4, FingeringSettings::default());
+        assert_eq!(frets(&scale_box, 1), vec!["4:1", "5:2", "7:4"]);
+    }
+
+    #[test]
+    fn test_capo() {
+        let mut scale =
+            Scale::from_names("F#", "minor pentatonic", "E").unwrap();
+        scale.capo = Capo::new(2).unwrap();
+        let boxes = scale.get_boxes(FingeringSettings::default());
+        assert!(boxes
+            .iter()
+            .all(|b| b.notes.iter().all(|n| n.position >= 2)));
+        // Nothing fretted at the capo
+        assert_eq!(boxes[0].finger(1, 2), Some(Finger::Open));
+    }
+
+    #[test]
+    fn test_display() {
+        let fingers = [
+            Finger::Open,
+            Finger::Index,
+            Finger::Middle,
+            Finger::Ring,
+            Finger::Pinky,
+            Finger::Thumb,
+        ];
+        let s: Vec<String> = fingers.iter().map(|f| f.to_string()).collect();
+        assert_eq!(s, vec!["0", "1", "2", "3", "4", "T"]);
+    }
+}
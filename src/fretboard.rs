@@ -0,0 +1,953 @@
+use crate::arpeggio::{Arpeggio, ArpeggioShape};
+use crate::capo::Capo;
+use crate::exercise::Exercise;
+use crate::fingering::{Finger, ScaleBox};
+use crate::note::{Note, TraitNoteSemitones};
+use crate::pitch::Pitch;
+use crate::pitch_class_set::PitchClassSet;
+use crate::scale::{DegreeAllStrings, Scale};
+use crate::NUMBER_STRING;
+use ukebox::Semitones;
+
+/// Coordinate on the drawing
+pub type Number = f32;
+
+pub const WIDTH_LEFT: u16 = 50; // Place for root note (reverse A E C G)
+pub const WIDTH_RIGHT: u16 = 25; // Blank
+pub const WIDTH: u16 = 900 - WIDTH_LEFT - WIDTH_RIGHT;
+pub const HEIGHT_TOP: u16 = 25;
+pub const HEIGHT_BOTTOM: u16 = 45;
+pub const HEIGHT: u16 = 250 - HEIGHT_TOP - HEIGHT_BOTTOM;
+pub const NUMBER_POSITION: u16 = 19;
+pub const OFFSET_TEXT_BOTTOM: Number = -1.0;
+
+pub const VER_HEIGHT_TOP: u16 = 50;
+pub const VER_HEIGHT_BOTTOM: u16 = 25;
+pub const VER_HEIGHT: u16 = 1100 - VER_HEIGHT_TOP - VER_HEIGHT_BOTTOM;
+pub const VER_WIDTH_LEFT: u16 = 50;
+pub const VER_WIDTH_RIGHT: u16 = 50;
+pub const VER_WIDTH: u16 = 300 - VER_WIDTH_LEFT - VER_WIDTH_RIGHT;
+pub const VER_OFFSET_WIDTH: f32 = 200.0 / 5.0;
+pub const VER_FLUTTER_OFFSET_NOTE_NAME: Number = 4.75;
+
+pub const SWIFT_WIDTH_OFFSET_NOTE_NAME: Number = -3.5;
+pub const SWIFT_HEIGHT_OFFSET_NOTE_NAME: Number = 3.0;
+
+pub const SWIFT_VERTICAL_WIDTH_OFFSET: Number = -3.5;
+
+/// Frets numbered under the fretboard
+pub const FRET_MARKERS: [u16; 7] = [1, 3, 5, 7, 10, 12, 15];
+
+/// Direction of the strings on the drawing
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Orientation {
+    /// Low string at the bottom, frets from left to right
+    Horizontal,
+    /// Low string on the left, frets from top to bottom
+    Vertical,
+}
+
+/// A straight line from (`x`, `y`), `dx` long horizontally or `dy` long
+/// vertically
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Line {
+    pub x: Number,
+    pub y: Number,
+    pub dx: Number,
+    pub dy: Number,
+}
+
+/// A rectangle from its top left corner (`x`, `y`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rect {
+    pub x: Number,
+    pub y: Number,
+    pub width: Number,
+    pub height: Number,
+}
+
+/// A text and where to write it. `dx` shifts the texts of two characters
+/// or more to keep them centered.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Label {
+    pub x: Number,
+    pub y: Number,
+    pub dx: Option<Number>,
+    pub text: String,
+}
+
+impl Label {
+    fn new(x: Number, y: Number, text: String) -> Self {
+        let dx = if text.len() > 1 {
+            Some(OFFSET_TEXT_BOTTOM)
+        } else {
+            None
+        };
+        Self { x, y, dx, text }
+    }
+}
+
+/// A note of the scale on the fretboard, drawn as a circle with its name
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Dot {
+    /// Begin at 1 for the lowest string
+    pub string_number: usize,
+    pub position: usize,
+    pub note: Note,
+    pub pitch: Pitch,
+    pub sw_tonic: bool,
+    /// Circled with a ring, e.g. a note shared with another scale
+    pub highlight: bool,
+    /// Between the nut and the capo, drawn greyed out
+    pub sw_behind_capo: bool,
+    /// Fret counted from the capo, `None` behind it
+    pub relative_position: Option<usize>,
+    /// Finger playing the note, `None` out of the box
+    pub finger: Option<Finger>,
+    /// When the note is first played in an exercise, from 1
+    pub order: Option<usize>,
+    pub cx: Number,
+    pub cy: Number,
+    pub r: Number,
+    pub label: Label,
+}
+
+impl Dot {
+    /// Id of the circle in the svg, e.g. `s3-f5`
+    pub fn id(&self) -> String {
+        circle_id(self.string_number, self.position)
+    }
+
+    /// Fret of the note as read by a guitarist, e.g. "fret 3", or "fret 1
+    /// from the capo" in front of a capo on fret 2
+    pub fn fret_name(&self) -> String {
+        match self.relative_position {
+            Some(relative) if relative != self.position => {
+                format!("fret {} from the capo", relative)
+            },
+            _ => format!("fret {}", self.position),
+        }
+    }
+
+    /// Text read by screen readers, e.g. "string 5, fret 3, C, tonic". The
+    /// strings are numbered as guitarists do, from 1 for the highest.
+    pub fn aria_label(&self) -> String {
+        let mut label = format!(
+            "string {}, {}, {}",
+            NUMBER_STRING as usize + 1 - self.string_number,
+            self.fret_name(),
+            self.note
+        );
+        if self.sw_tonic {
+            label.push_str(", tonic");
+        }
+        if self.highlight {
+            label.push_str(", circled");
+        }
+        if self.sw_behind_capo {
+            label.push_str(", behind the capo");
+        }
+        label
+    }
+
+    /// Class of the circle in the svg
+    pub fn class(&self) -> &'static str {
+        if self.sw_behind_capo {
+            "circle_behind_capo"
+        } else if self.sw_tonic {
+            "circle_tonic"
+        } else {
+            "circle_in_scale"
+        }
+    }
+}
+
+/// Layout of a scale on the fretboard, independent of the way it is drawn
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Fretboard {
+    pub orientation: Orientation,
+    pub width: u16,
+    pub height: u16,
+    pub strings: Vec<Line>,
+    pub frets: Vec<Line>,
+    /// Numbers of the frets usually inlaid, counted from the capo if it
+    /// covers all the strings on the same fret
+    pub markers: Vec<Label>,
+    /// Names of the open strings (at the capo), from the lowest string
+    pub string_labels: Vec<Label>,
+    /// Bars of the capo, one for each run of neighbouring strings capoed on
+    /// the same fret, empty without capo
+    pub capo: Vec<Line>,
+    /// Frets behind each bar of the capo, greyed out
+    pub behind_capo: Vec<Rect>,
+    /// Notes of the scale, string by string from the lowest, fret by fret
+    pub dots: Vec<Dot>,
+    pub note_font_size: u8,
+}
+
+impl Fretboard {
+    pub fn new(
+        scale: Scale,
+        orientation: Orientation,
+        highlight: PitchClassSet,
+    ) -> Self {
+        Self::from_strings(
+            &scale.get_string_combination(),
+            &scale.get_open_strings(),
+            scale.capo,
+            orientation,
+            highlight,
+        )
+    }
+
+    /// Layout of any notes on the strings, e.g. the tones of a chord.
+    /// `open_strings` are the pitches of the strings at the capo.
+    pub fn from_strings(
+        degree_all_strings: &[DegreeAllStrings],
+        open_strings: &[Pitch],
+        capo: Capo,
+        orientation: Orientation,
+        highlight: PitchClassSet,
+    ) -> Self {
+        let layout: &dyn Layout = match orientation {
+            Orientation::Horizontal => &HorizontalLayout,
+            Orientation::Vertical => &VerticalLayout,
+        };
+        let strings = (0..NUMBER_STRING).map(|n| layout.string(n)).collect();
+        let frets = (0..NUMBER_POSITION).map(|n| layout.fret(n)).collect();
+        let markers = FRET_MARKERS
+            .iter()
+            .map(|&n| (n, n + capo.fret() as u16))
+            .filter(|&(_, position)| position < NUMBER_POSITION)
+            .map(|(n, position)| {
+                let (x, y) = layout.marker(position);
+                Label::new(x, y, n.to_string())
+            })
+            .collect();
+        let string_labels = open_strings
+            .iter()
+            .enumerate()
+            .map(|(i, pitch)| {
+                let (x, y) = layout.string_label(i as u8);
+                Label::new(x, y, sharp_name(pitch.note))
+            })
+            .collect();
+        let r = layout.note_r();
+        let capo_bars: Vec<Line> = capo
+            .bars()
+            .into_iter()
+            .map(|(fret, first, last)| {
+                let (x1, y1) = layout.center((first - 1) as u8, 0);
+                let (x2, y2) = layout.center((last - 1) as u8, 0);
+                capo_line(layout.fret(fret as u16), (x1, y1), (x2, y2), r)
+            })
+            .collect();
+        let behind_capo = capo
+            .bars()
+            .into_iter()
+            .zip(&capo_bars)
+            .map(|((fret, _, _), &bar)| {
+                let edge = layout.fret(fret as u16 - 1);
+                behind_capo_area(layout.string(0), edge, bar)
+            })
+            .collect();
+        let mut dots: Vec<Dot> = Vec::new();
+        for degree_all_strings in degree_all_strings {
+            let i = (degree_all_strings.string_number - 1) as u8;
+            // The scale reaches one fret further than the drawing
+            for v in degree_all_strings
+                .degree_single_string
+                .iter()
+                .filter(|v| v.position < NUMBER_POSITION as usize)
+            {
+                let (cx, cy) = layout.center(i, v.position as u16);
+                let (x, y) = layout.note_name(cx, cy);
+                let capo_fret =
+                    capo.get_fret(degree_all_strings.string_number);
+                dots.push(Dot {
+                    string_number: degree_all_strings.string_number,
+                    position: v.position,
+                    note: v.note,
+                    pitch: v.pitch,
+                    sw_tonic: v.sw_tonic,
+                    highlight: highlight.contains(v.note.pitch_class),
+                    sw_behind_capo: v.sw_behind_capo,
+                    relative_position: v
+                        .position
+                        .checked_sub(capo_fret as usize),
+                    finger: None,
+                    order: None,
+                    cx,
+                    cy,
+                    r,
+                    label: Label::new(x, y, sharp_name(v.note)),
+                });
+            }
+        }
+        let (width, height) = layout.size();
+        Self {
+            orientation,
+            width,
+            height,
+            strings,
+            frets,
+            markers,
+            string_labels,
+            capo: capo_bars,
+            behind_capo,
+            dots,
+            note_font_size: layout.note_font_size(),
+        }
+    }
+
+    /// Write the fingers of a box in the circles instead of the note names,
+    /// the circles out of the box being left blank
+    pub fn with_fingering(mut self, scale_box: &ScaleBox) -> Self {
+        for dot in &mut self.dots {
+            dot.finger = scale_box.finger(dot.string_number, dot.position);
+            let text = dot.finger.map(|f| f.to_string()).unwrap_or_default();
+            dot.label = Label::new(dot.label.x, dot.label.y, text);
+        }
+        self
+    }
+
+    /// Write in the circles when the notes are first played in `exercise`,
+    /// the circles out of it being left blank
+    pub fn with_order(mut self, exercise: &Exercise) -> Self {
+        for dot in &mut self.dots {
+            dot.order = exercise
+                .events
+                .iter()
+                .position(|e| {
+                    e.string_number == dot.string_number
+                        && e.position == dot.position
+                })
+                .map(|i| i + 1);
+            let text = dot.order.map(|o| o.to_string()).unwrap_or_default();
+            dot.label = Label::new(dot.label.x, dot.label.y, text);
+        }
+        self
+    }
+    /// Write the chord tones (R, 3, 5, ...) in the circles instead of the
+    /// note names, keeping only the notes of `shape` if any
+    pub fn with_chord_tones(
+        mut self,
+        arpeggio: &Arpeggio,
+        shape: Option<ArpeggioShape>,
+    ) -> Self {
+        if let Some(shape) = shape {
+            self.dots.retain(|dot| shape.contains(dot.position));
+        }
+        for dot in &mut self.dots {
+            let text = arpeggio.chord_tone(dot.note.pitch_class).unwrap_or("");
+            dot.label = Label::new(dot.label.x, dot.label.y, text.to_string());
+        }
+        self
+    }
+
+    /// Text alternative of the drawing: the notes of each string, from the
+    /// highest string, e.g. "string 1 (E): fret 0 E, fret 3 G, ..."
+    pub fn summary(&self) -> String {
+        let lines: Vec<String> = self
+            .string_labels
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, label)| {
+                let notes: Vec<String> = self
+                    .dots
+                    .iter()
+                    .filter(|d| d.string_number == i + 1)
+                    .map(|d| {
+                        let tonic = if d.sw_tonic { " (tonic)" } else { "" };
+                        format!("{} {}{}", d.fret_name(), d.note, tonic)
+                    })
+                    .collect();
+                format!(
+                    "string {} ({}): {}",
+                    self.string_labels.len() - i,
+                    label.text,
+                    notes.join(", ")
+                )
+            })
+            .collect();
+        lines.join("\n")
+    }
+}
+
+/// Id of the circle of a note in the svg: `s<string>-f<fret>`, the strings
+/// being numbered from 1 for the lowest and the frets from the nut
+pub fn circle_id(string_number: usize, position: usize) -> String {
+    format!("s{}-f{}", string_number, position)
+}
+
+/// Name of the note spelled with a sharp, as written in the circles
+fn sharp_name(note: Note) -> String {
+    Note::from_semitones(note.pitch_class as Semitones).to_string()
+}
+
+/// Bar of the capo along the wire of a fret, from the string at `first`
+/// to the string at `last` and reaching `r` past them to cover the notes
+fn capo_line(
+    fret: Line,
+    first: (Number, Number),
+    last: (Number, Number),
+    r: Number,
+) -> Line {
+    if fret.dy == 0.0 {
+        Line {
+            x: first.0.min(last.0) - r,
+            dx: (last.0 - first.0).abs() + 2.0 * r,
+            ..fret
+        }
+    } else {
+        Line {
+            y: first.1.min(last.1) - r,
+            dy: (last.1 - first.1).abs() + 2.0 * r,
+            ..fret
+        }
+    }
+}
+
+/// Frets behind a bar of the capo, from the start of the strings to `edge`,
+/// the wire before the capo, and as wide as the bar
+fn behind_capo_area(start: Line, edge: Line, bar: Line) -> Rect {
+    if bar.dx == 0.0 {
+        Rect {
+            x: start.x,
+            y: bar.y,
+            width: edge.x - start.x,
+            height: bar.dy,
+        }
+    } else {
+        Rect {
+            x: bar.x,
+            y: start.y,
+            width: bar.dx,
+            height: edge.y - start.y,
+        }
+    }
+}
+
+/// Geometry of one orientation. `string` begins at 0 for the lowest string
+/// and `position` at 0 for the open string.
+trait Layout {
+    fn size(&self) -> (u16, u16);
+    fn string(&self, n: u8) -> Line;
+    fn fret(&self, n: u16) -> Line;
+    fn marker(&self, n: u16) -> (Number, Number);
+    fn string_label(&self, string: u8) -> (Number, Number);
+    fn center(&self, string: u8, position: u16) -> (Number, Number);
+    fn note_name(&self, cx: Number, cy: Number) -> (Number, Number);
+    fn note_r(&self) -> Number;
+    fn note_font_size(&self) -> u8;
+}
+
+struct HorizontalLayout;
+
+impl HorizontalLayout {
+    /// The lowest string is drawn at the bottom
+    fn string_y(string: u8) -> Number {
+        let row = NUMBER_STRING - 1 - string;
+        HEIGHT_TOP as f32
+            + ((HEIGHT as f32 / NUMBER_STRING as f32) * (row as f32 + 1.0))
+    }
+
+    fn position_x(position: f32) -> Number {
+        let mut w: Number = WIDTH as f32 / NUMBER_POSITION as f32;
+        w *= position + 0.5;
+        WIDTH_LEFT as f32 + w
+    }
+}
+
+impl Layout for HorizontalLayout {
+    fn size(&self) -> (u16, u16) {
+        (
+            WIDTH_LEFT + WIDTH + WIDTH_RIGHT,
+            HEIGHT_TOP + HEIGHT + HEIGHT_BOTTOM,
+        )
+    }
+
+    fn string(&self, n: u8) -> Line {
+        let h =
+            (HEIGHT as Number / NUMBER_STRING as Number) * ((n + 1) as Number);
+        Line {
+            x: WIDTH_LEFT as f32,
+            y: HEIGHT_TOP as f32 + h,
+            dx: WIDTH as Number,
+            dy: 0.0,
+        }
+    }
+
+    fn fret(&self, n: u16) -> Line {
+        let w: Number =
+            (WIDTH as Number / NUMBER_POSITION as Number) * ((n + 1) as Number);
+        let h = HEIGHT / NUMBER_STRING as u16;
+        Line {
+            x: WIDTH_LEFT as f32 + w,
+            y: HEIGHT_TOP as f32 + h as Number,
+            dx: 0.0,
+            dy: (HEIGHT - h) as Number,
+        }
+    }
+
+    fn marker(&self, n: u16) -> (Number, Number) {
+        let height_pos: Number = HEIGHT_TOP as f32 + HEIGHT as f32;
+        let h = HEIGHT_BOTTOM / 2;
+        (
+            Self::position_x(n as f32) + SWIFT_WIDTH_OFFSET_NOTE_NAME,
+            height_pos + h as Number + SWIFT_HEIGHT_OFFSET_NOTE_NAME,
+        )
+    }
+
+    fn string_label(&self, string: u8) -> (Number, Number) {
+        (WIDTH_LEFT as f32 / 2.0, Self::string_y(string))
+    }
+
+    fn center(&self, string: u8, position: u16) -> (Number, Number) {
+        (Self::position_x(position as f32), Self::string_y(string))
+    }
+
+    fn note_name(&self, cx: Number, cy: Number) -> (Number, Number) {
+        (
+            cx + SWIFT_WIDTH_OFFSET_NOTE_NAME,
+            cy + SWIFT_HEIGHT_OFFSET_NOTE_NAME,
+        )
+    }
+
+    fn note_r(&self) -> Number {
+        let w: Number = WIDTH as f32 / NUMBER_POSITION as f32;
+        w / 5.0
+    }
+
+    fn note_font_size(&self) -> u8 {
+        9
+    }
+}
+
+struct VerticalLayout;
+
+impl VerticalLayout {
+    /// The lowest string is drawn on the left
+    fn string_x(string: u8) -> Number {
+        VER_WIDTH_LEFT as f32
+            + ((VER_WIDTH as f32 / (NUMBER_STRING - 1) as f32) * string as f32
+                + 0.0)
+    }
+
+    fn position_y(position: f32) -> Number {
+        let mut h: Number = VER_HEIGHT as f32 / NUMBER_POSITION as f32;
+        h *= position + 0.5;
+        VER_HEIGHT_TOP as f32 + h
+    }
+}
+
+impl Layout for VerticalLayout {
+    fn size(&self) -> (u16, u16) {
+        (
+            VER_WIDTH_LEFT + VER_WIDTH + VER_WIDTH_RIGHT,
+            VER_HEIGHT_TOP + VER_HEIGHT + VER_HEIGHT_BOTTOM,
+        )
+    }
+
+    fn string(&self, n: u8) -> Line {
+        Line {
+            x: VER_WIDTH_LEFT as f32 + VER_OFFSET_WIDTH as Number * n as Number,
+            y: VER_HEIGHT_TOP as f32,
+            dx: 0.0,
+            dy: VER_HEIGHT as Number,
+        }
+    }
+
+    fn fret(&self, n: u16) -> Line {
+        let h: Number = (VER_HEIGHT as Number / NUMBER_POSITION as Number)
+            * ((n + 1) as Number);
+        Line {
+            x: VER_WIDTH_LEFT as f32,
+            y: VER_HEIGHT_TOP as f32 + h,
+            dx: VER_WIDTH as Number,
+            dy: 0.0,
+        }
+    }
+
+    fn marker(&self, n: u16) -> (Number, Number) {
+        let width_pos: Number = VER_WIDTH_LEFT as f32 + VER_WIDTH as f32;
+        let w = VER_WIDTH_RIGHT / 2;
+        (
+            width_pos + w as Number,
+            Self::position_y(n as f32) + VER_FLUTTER_OFFSET_NOTE_NAME,
+        )
+    }
+
+    fn string_label(&self, string: u8) -> (Number, Number) {
+        let width_pos: Number =
+            VER_WIDTH_LEFT as f32 + (VER_OFFSET_WIDTH * string as f32 + 0.0);
+        (
+            width_pos + SWIFT_VERTICAL_WIDTH_OFFSET,
+            VER_HEIGHT_TOP as f32 / 2.0,
+        )
+    }
+
+    fn center(&self, string: u8, position: u16) -> (Number, Number) {
+        (Self::string_x(string), Self::position_y(position as f32))
+    }
+
+    fn note_name(&self, cx: Number, cy: Number) -> (Number, Number) {
+        (
+            cx + SWIFT_VERTICAL_WIDTH_OFFSET,
+            cy + VER_FLUTTER_OFFSET_NOTE_NAME,
+        )
+    }
+
+    fn note_r(&self) -> Number {
+        let h: Number = VER_HEIGHT as f32 / NUMBER_POSITION as f32;
+        h / 3.5
+    }
+
+    fn note_font_size(&self) -> u8 {
+        14
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arpeggio::CagedShape;
+    use crate::exercise::{Direction, Pattern};
+    use crate::fingering::FingeringSettings;
+    use crate::scale::ScaleType;
+    use rstest::rstest;
+
+    #[rstest(
+        orientation,
+        width,
+        height,
+        case(Orientation::Horizontal, 900, 250),
+        case(Orientation::Vertical, 300, 1100)
+    )]
+    fn test_size(orientation: Orientation, width: u16, height: u16) {
+        let scale = Scale::from_names("C", "major", "E").unwrap();
+        let fretboard =
+            Fretboard::new(scale, orientation, PitchClassSet::default());
+        assert_eq!((fretboard.width, fretboard.height), (width, height));
+        assert_eq!(fretboard.strings.len(), NUMBER_STRING as usize);
+        assert_eq!(fretboard.frets.len(), NUMBER_POSITION as usize);
+        assert_eq!(fretboard.markers.len(), FRET_MARKERS.len());
+        assert_eq!(fretboard.string_labels.len(), NUMBER_STRING as usize);
+    }
+
+    #[test]
+    fn test_dots() {
+        let scale = Scale::from_names("A", "minor pentatonic", "E").unwrap();
+        let highlight = PitchClassSet::from_semitones(&[9]);
+        let fretboard =
+            Fretboard::new(scale, Orientation::Horizontal, highlight);
+        assert_eq!(scale.scale_type, ScaleType::MinorPentagonic);
+        // Five notes in 12 frets, on 20 positions from the open string
+        for string_number in 1..=NUMBER_STRING as usize {
+            let count = fretboard
+                .dots
+                .iter()
+                .filter(|d| d.string_number == string_number)
+                .count();
+            assert!((8..=9).contains(&count));
+        }
+        for dot in &fretboard.dots {
+            assert_eq!(dot.sw_tonic, dot.note.to_string() == "A");
+            assert_eq!(dot.highlight, dot.sw_tonic);
+            assert_eq!(dot.label.text, dot.note.to_string());
+        }
+        // Lowest string at the bottom, frets from the left
+        let first = &fretboard.dots[0];
+        assert_eq!((first.string_number, first.position), (1, 0));
+        assert_eq!(first.class(), "circle_in_scale");
+        assert_eq!(first.id(), "s1-f0");
+        let mut ids: Vec<String> =
+            fretboard.dots.iter().map(|d| d.id()).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), fretboard.dots.len());
+        let last = fretboard.dots.last().unwrap();
+        assert!(first.cy > last.cy);
+        assert!(first.cx < fretboard.dots[1].cx);
+    }
+
+    #[rstest(
+        string_number,
+        position,
+        label,
+        case(2, 3, "string 5, fret 3, C"),
+        case(2, 0, "string 5, fret 0, A, tonic, circled"),
+        case(6, 5, "string 1, fret 5, A, tonic, circled")
+    )]
+    fn test_aria_label(string_number: usize, position: usize, label: &str) {
+        let scale = Scale::from_names("A", "minor pentatonic", "E").unwrap();
+        let highlight = PitchClassSet::from_semitones(&[9]);
+        let fretboard =
+            Fretboard::new(scale, Orientation::Horizontal, highlight);
+        let dot = fretboard
+            .dots
+            .iter()
+            .find(|d| d.id() == circle_id(string_number, position))
+            .unwrap();
+        assert_eq!(dot.aria_label(), label);
+    }
+
+    #[test]
+    fn test_summary() {
+        let mut scale =
+            Scale::from_names("E", "minor pentatonic", "E").unwrap();
+        scale.capo = Capo::new(2).unwrap();
+        let fretboard = Fretboard::new(
+            scale,
+            Orientation::Vertical,
+            PitchClassSet::default(),
+        );
+        let summary = fretboard.summary();
+        let lines: Vec<&str> = summary.lines().collect();
+        assert_eq!(lines.len(), NUMBER_STRING as usize);
+        assert!(lines[0].starts_with(
+            "string 1 (F#): fret 0 E (tonic), fret 1 from the capo G, \
+             fret 3 from the capo A,"
+        ));
+        assert!(lines[5].starts_with("string 6 (F#): fret 0 E (tonic),"));
+        let dot = &fretboard.dots[0];
+        assert_eq!(
+            dot.aria_label(),
+            "string 6, fret 0, E, tonic, behind the capo"
+        );
+        let dot = &fretboard.dots[1];
+        assert_eq!(dot.aria_label(), "string 6, fret 1 from the capo, G");
+    }
+
+    #[test]
+    fn test_vertical_dots() {
+        let scale = Scale::from_names("E", "minor", "E").unwrap();
+        let fretboard = Fretboard::new(
+            scale,
+            Orientation::Vertical,
+            PitchClassSet::default(),
+        );
+        let first = &fretboard.dots[0];
+        let last = fretboard.dots.last().unwrap();
+        assert_eq!(first.class(), "circle_tonic");
+        assert!(first.cx < last.cx);
+        assert!(first.cy < fretboard.dots[1].cy);
+        assert_eq!(fretboard.string_labels[0].text, "E");
+        assert_eq!(fretboard.string_labels[1].text, "A");
+    }
+
+    #[rstest(
+        orientation,
+        case(Orientation::Horizontal),
+        case(Orientation::Vertical)
+    )]
+    fn test_capo(orientation: Orientation) {
+        let mut scale = Scale::from_names("G", "major", "E").unwrap();
+        let highlight = PitchClassSet::default();
+        assert!(Fretboard::new(scale, orientation, highlight).capo.is_empty());
+        scale.capo = Capo::new(2).unwrap();
+        let fretboard = Fretboard::new(scale, orientation, highlight);
+        // The bar is on the wire of the second fret, across all the strings
+        let fret = fretboard.frets[2];
+        let bar = fretboard.capo[0];
+        assert_eq!(fretboard.capo.len(), 1);
+        assert_eq!(bar.x + bar.dx / 2.0, fret.x + fret.dx / 2.0);
+        assert_eq!(bar.y + bar.dy / 2.0, fret.y + fret.dy / 2.0);
+        assert!(bar.dx + bar.dy > fret.dx + fret.dy);
+        for dot in &fretboard.dots {
+            assert_eq!(dot.sw_behind_capo, dot.position < 2);
+            assert_eq!(dot.relative_position, dot.position.checked_sub(2));
+            if dot.sw_behind_capo {
+                assert_eq!(dot.class(), "circle_behind_capo");
+            }
+        }
+        assert_eq!(fretboard.string_labels[0].text, "F#");
+        // Fret numbers counted from the capo: "1" on the third fret
+        let without_capo = Fretboard::new(
+            Scale {
+                capo: Capo::default(),
+                ..scale
+            },
+            orientation,
+            highlight,
+        );
+        let (marker, fret_3) =
+            (&fretboard.markers[0], &without_capo.markers[1]);
+        assert_eq!((marker.x, marker.y), (fret_3.x, fret_3.y));
+        assert_eq!((marker.text.as_str(), fret_3.text.as_str()), ("1", "3"));
+        // Frets 0 and 1 greyed out, up to the wire of the first fret
+        assert_eq!(fretboard.behind_capo.len(), 1);
+        let area = fretboard.behind_capo[0];
+        let start = fretboard.strings[0];
+        let fret = fretboard.frets[1];
+        match orientation {
+            Orientation::Horizontal => {
+                assert_eq!((area.x, area.width), (start.x, fret.x - start.x));
+                assert_eq!((area.y, area.height), (bar.y, bar.dy));
+            },
+            Orientation::Vertical => {
+                assert_eq!((area.y, area.height), (start.y, fret.y - start.y));
+                assert_eq!((area.x, area.width), (bar.x, bar.dx));
+            },
+        }
+    }
+
+    #[rstest(
+        capo,
+        case(Capo::partial(2, &[2, 3, 4]).unwrap()),
+        case(Capo::per_string([0, 2, 2, 2, 0, 4]).unwrap())
+    )]
+    fn test_markers_from_the_nut(capo: Capo) {
+        // The open strings sound from the nut, and so do the fret numbers:
+        // E phrygian has a note on each marked fret of the low E string
+        let mut scale = Scale::from_names("E", "phrygian", "E").unwrap();
+        scale.capo = capo;
+        let fretboard = Fretboard::new(
+            scale,
+            Orientation::Horizontal,
+            PitchClassSet::default(),
+        );
+        for (marker, &n) in fretboard.markers.iter().zip(&FRET_MARKERS) {
+            let dot = fretboard
+                .dots
+                .iter()
+                .find(|d| d.string_number == 1 && d.label.x == marker.x)
+                .unwrap();
+            assert_eq!(dot.position, n as usize);
+            assert_eq!(dot.fret_name(), format!("fret {}", marker.text));
+        }
+        assert_eq!(fretboard.markers.len(), FRET_MARKERS.len());
+    }
+
+    #[rstest(
+        orientation,
+        case(Orientation::Horizontal),
+        case(Orientation::Vertical)
+    )]
+    fn test_per_string_capo(orientation: Orientation) {
+        let mut scale = Scale::from_names("E", "major", "E").unwrap();
+        scale.capo = Capo::per_string([0, 2, 2, 2, 0, 4]).unwrap();
+        let fretboard =
+            Fretboard::new(scale, orientation, PitchClassSet::default());
+        assert_eq!(fretboard.capo.len(), 2);
+        let full = Fretboard::new(
+            Scale {
+                capo: Capo::new(2).unwrap(),
+                ..scale
+            },
+            orientation,
+            PitchClassSet::default(),
+        );
+        // Three strings of six: shorter than a full capo on the same fret
+        let (bar, full_bar) = (fretboard.capo[0], full.capo[0]);
+        assert_eq!(bar.x == full_bar.x, bar.dx == 0.0);
+        assert_eq!(bar.y == full_bar.y, bar.dy == 0.0);
+        assert!(bar.dx + bar.dy < full_bar.dx + full_bar.dy);
+        // A single string: the bar only covers its note
+        let r = fretboard.dots[0].r;
+        let bar = fretboard.capo[1];
+        assert_eq!(bar.dx + bar.dy, 2.0 * r);
+        for dot in &fretboard.dots {
+            let capo_fret = scale.capo.get_fret(dot.string_number) as usize;
+            assert_eq!(dot.sw_behind_capo, dot.position < capo_fret);
+        }
+        let labels: Vec<&str> = fretboard
+            .string_labels
+            .iter()
+            .map(|l| l.text.as_str())
+            .collect();
+        assert_eq!(labels, vec!["E", "B", "E", "A", "B", "G#"]);
+    }
+
+    #[test]
+    fn test_with_fingering() {
+        let scale = Scale::from_names("A", "minor pentatonic", "E").unwrap();
+        let boxes = scale.get_boxes(FingeringSettings::default());
+        let scale_box = &boxes[1];
+        let fretboard = Fretboard::new(
+            scale,
+            Orientation::Horizontal,
+            PitchClassSet::default(),
+        )
+        .with_fingering(scale_box);
+        let fingered: Vec<&Dot> =
+            fretboard.dots.iter().filter(|d| d.finger.is_some()).collect();
+        assert_eq!(fingered.len(), scale_box.notes.len());
+        for dot in fingered {
+            assert_eq!(dot.label.text, dot.finger.unwrap().to_string());
+            assert_eq!(dot.label.dx, None);
+        }
+        // The notes out of the box are left blank
+        let dot = fretboard.dots.iter().find(|d| d.finger.is_none()).unwrap();
+        assert!(dot.label.text.is_empty());
+    }
+
+    #[test]
+    fn test_with_order() {
+        let scale = Scale::from_names("E", "minor pentatonic", "E").unwrap();
+        let boxes = scale.get_boxes(FingeringSettings::default());
+        let exercise = Exercise::new(
+            scale,
+            &boxes[0],
+            Pattern::Intervals(2),
+            Direction::Ascending,
+        );
+        let fretboard = Fretboard::new(
+            scale,
+            Orientation::Vertical,
+            PitchClassSet::default(),
+        )
+        .with_order(&exercise);
+        let mut numbered: Vec<&Dot> =
+            fretboard.dots.iter().filter(|d| d.order.is_some()).collect();
+        numbered.sort_by_key(|d| d.order);
+        assert_eq!(numbered.len(), boxes[0].notes.len());
+        let labels: Vec<&str> =
+            numbered.iter().map(|d| d.label.text.as_str()).collect();
+        // Thirds: each note but the first two is played again after the
+        // note above it
+        assert_eq!(labels[..5], ["1", "2", "3", "4", "6"]);
+        assert_eq!(numbered[4].label.dx, None);
+        assert!(numbered.last().unwrap().label.dx.is_some());
+    }
+
+    #[test]
+    fn test_with_chord_tones() {
+        let arpeggio = Arpeggio::from_names("Am7", "E").unwrap();
+        let fretboard = Fretboard::from_strings(
+            &arpeggio.get_string_combination(),
+            &arpeggio.get_open_strings(),
+            arpeggio.capo,
+            Orientation::Horizontal,
+            PitchClassSet::default(),
+        );
+        let neck = fretboard.clone().with_chord_tones(&arpeggio, None);
+        assert_eq!(neck.dots.len(), fretboard.dots.len());
+        let tones: Vec<&str> = neck.dots[..4]
+            .iter()
+            .map(|d| d.label.text.as_str())
+            .collect();
+        // E, G, A and C on the low E string
+        assert_eq!(tones, vec!["5", "b7", "R", "b3"]);
+        let shape = arpeggio
+            .get_shapes()
+            .into_iter()
+            .find(|s| s.caged == CagedShape::E)
+            .unwrap();
+        assert_eq!((shape.low, shape.high), (5, 8));
+        let boxed = fretboard.with_chord_tones(&arpeggio, Some(shape));
+        assert!(boxed.dots.iter().all(|d| (5..=8).contains(&d.position)));
+        assert_eq!(boxed.dots.iter().filter(|d| d.sw_tonic).count(), 3);
+    }
+}
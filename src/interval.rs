@@ -1,106 +1,63 @@
-use std::fmt;
-use std::str::FromStr;
-use ukebox::{Semitones, StaffSteps};
-
-/// Custom error for strings that cannot be parsed into intervals.
-#[derive(Debug)]
-pub struct ParseIntervalError {
-    name: String,
+// Intervals are the ones of ukebox, which spells the notes with them
+pub use ukebox::interval::ParseIntervalError;
+pub use ukebox::Interval;
+
+pub trait TraitChordTone {
+    /// Name of the chord tone, "R" for the root, as written on arpeggio
+    /// diagrams
+    fn to_chord_tone(self) -> &'static str;
 }
 
-impl fmt::Display for ParseIntervalError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Could not parse interval name \"{}\"", self.name)
-    }
-}
-
-/// An interval is the difference between two notes.
-/// https://en.wikipedia.org/wiki/Interval_(music)
-#[derive(Debug, Clone, Copy)]
-pub enum Interval {
-    PerfectUnison,
-    MajorSecond,
-    MinorThird,
-    MajorThird,
-    PerfectFourth,
-    DiminishedFifth,
-    PerfectFifth,
-    AugmentedFifth,
-    DiminishedSeventh,
-    MinorSeventh,
-    MajorSeventh,
-    PlusOctave,
-    PlusPlusOctave,
-}
-
-impl Interval {
-    /// Return the number of semitones that the interval encompasses.
-    pub fn to_semitones(self) -> Semitones {
-        use Interval::*;
-
-        match self {
-            PerfectUnison => 0,
-            MajorSecond => 2,
-            MinorThird => 3,
-            MajorThird => 4,
-            PerfectFourth => 5,
-            DiminishedFifth => 6,
-            PerfectFifth => 7,
-            AugmentedFifth => 8,
-            DiminishedSeventh => 9,
-            MinorSeventh => 10,
-            MajorSeventh => 11,
-            PlusOctave => 12,
-            PlusPlusOctave => 12,
-        }
-    }
-
-    /// Return the interval's number. It corresponds to the number of
-    /// staff positions that the intervall encompasses.
-    pub fn to_number(self) -> StaffSteps {
+impl TraitChordTone for Interval {
+    fn to_chord_tone(self) -> &'static str {
         use Interval::*;
 
         match self {
-            PerfectUnison => 1,
-            MajorSecond => 2,
-            MinorThird => 3,
-            MajorThird => 3,
-            PerfectFourth => 4,
-            DiminishedFifth => 5,
-            PerfectFifth => 5,
-            AugmentedFifth => 5,
-            DiminishedSeventh => 7,
-            MinorSeventh => 7,
-            MajorSeventh => 7,
-            PlusOctave => 12, // not sure about that
-            PlusPlusOctave => 24, // not sure about that
+            PerfectUnison | PlusOctave | PlusPlusOctave => "R",
+            MajorSecond => "2",
+            MinorThird => "b3",
+            MajorThird => "3",
+            PerfectFourth => "4",
+            DiminishedFifth => "b5",
+            PerfectFifth => "5",
+            AugmentedFifth => "#5",
+            DiminishedSeventh => "bb7",
+            MinorSeventh => "b7",
+            MajorSeventh => "7",
         }
     }
 }
 
-impl FromStr for Interval {
-    type Err = ParseIntervalError;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use Interval::*;
-
-        let name = s.to_string();
-
-        let interval = match s {
-            "P1" => PerfectUnison,
-            "M2" => MajorSecond,
-            "m3" => MinorThird,
-            "M3" => MajorThird,
-            "P4" => PerfectFourth,
-            "d5" => DiminishedFifth,
-            "P5" => PerfectFifth,
-            "A5" => AugmentedFifth,
-            "d7" => DiminishedSeventh,
-            "m7" => MinorSeventh,
-            "M7" => MajorSeventh,
-            _ => return Err(ParseIntervalError { name }),
-        };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    #[rstest(
+        s,
+        tone,
+        case("P1", "R"),
+        case("m3", "b3"),
+        case("M3", "3"),
+        case("d5", "b5"),
+        case("A5", "#5"),
+        case("d7", "bb7"),
+        case("M7", "7"),
+        case("P8", "R")
+    )]
+    fn test_chord_tone(s: &str, tone: &str) {
+        assert_eq!(Interval::from_str(s).unwrap().to_chord_tone(), tone);
+    }
 
-        Ok(interval)
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let json = serde_json::to_string(&Interval::MajorThird).unwrap();
+        assert_eq!(json, "\"MajorThird\"");
+        assert_eq!(
+            serde_json::from_str::<Interval>(&json).unwrap(),
+            Interval::MajorThird
+        );
     }
-}
\ No newline at end of file
+}
@@ -1,13 +1,56 @@
+/// Serialize a type as its `Display` string and deserialize it with
+/// `FromStr`, so that it reads the same in json as on screen
+#[cfg(feature = "serde")]
+macro_rules! serde_string {
+    ($t:ty) => {
+        impl serde::Serialize for $t {
+            fn serialize<S: serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $t {
+            fn deserialize<D: serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                <$t as std::str::FromStr>::from_str(&s)
+                    .map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+pub mod arpeggio;
+pub mod audio;
+pub mod capo;
+pub mod chord_scale;
+pub mod error;
+pub mod exercise;
+pub mod fingering;
+pub mod fretboard;
+pub mod interval;
+pub mod midi;
 pub mod scale;
+pub mod scale_catalogue;
 pub mod note;
+pub mod pitch;
+pub mod pitch_class_set;
+pub mod progression;
 pub mod svg_draw;
+pub mod timeline;
 pub mod tuning;
-mod interval;
 
 extern crate strum;
 #[macro_use]
 extern crate strum_macros;
+#[cfg(feature = "serde")]
 extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
 extern crate serde_derive;
 
 /// The number of degree is the position from tonic in scale
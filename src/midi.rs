@@ -0,0 +1,258 @@
+use crate::error::Result;
+use crate::exercise::Direction;
+use std::fs;
+use std::path;
+use ukebox::Semitones;
+
+/// Ticks of a quarter note
+pub const TICKS_PER_BEAT: u16 = 480;
+/// General MIDI program of the steel string acoustic guitar
+pub const PROGRAM_GUITAR: u8 = 25;
+pub const VELOCITY_DEFAULT: u8 = 96;
+pub const VELOCITY_ACCENT: u8 = 120;
+/// Highest MIDI note number
+pub const KEY_MAX: Semitones = 127;
+/// Slowest tempo, in beats per minute: the microseconds of a beat must fit
+/// the 24 bits of the tempo event
+pub const TEMPO_MIN: u16 = 4;
+
+const MICROSECONDS_PER_MINUTE: u32 = 60_000_000;
+const NOTE_OFF: u8 = 0x80;
+const NOTE_ON: u8 = 0x90;
+const PROGRAM_CHANGE: u8 = 0xc0;
+const META: u8 = 0xff;
+const META_TEMPO: u8 = 0x51;
+const META_END_OF_TRACK: u8 = 0x2f;
+
+/// Length of the notes of an export
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NoteValue {
+    Whole,
+    Half,
+    Quarter,
+    Eighth,
+    /// Eighth triplet, three notes per beat
+    Triplet,
+    Sixteenth,
+}
+
+impl NoteValue {
+    /// Length in ticks
+    pub fn ticks(self) -> u32 {
+        let beat = TICKS_PER_BEAT as u32;
+        match self {
+            NoteValue::Whole => 4 * beat,
+            NoteValue::Half => 2 * beat,
+            NoteValue::Quarter => beat,
+            NoteValue::Eighth => beat / 2,
+            NoteValue::Triplet => beat / 3,
+            NoteValue::Sixteenth => beat / 4,
+        }
+    }
+}
+
+/// How a scale is played in a MIDI export
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MidiSettings {
+    /// Beats per minute, from `TEMPO_MIN` to `u16::MAX`
+    pub tempo: u16,
+    pub note_value: NoteValue,
+    /// Octaves played from the first tonic
+    pub octaves: usize,
+    /// Fret of the lowest string where the scale begins: it starts on the
+    /// first tonic at or above the note of this fret
+    pub position: usize,
+    pub direction: Direction,
+    /// Play the tonic louder
+    pub accent_tonic: bool,
+}
+
+impl Default for MidiSettings {
+    fn default() -> Self {
+        Self {
+            tempo: 120,
+            note_value: NoteValue::Eighth,
+            octaves: 2,
+            position: 0,
+            direction: Direction::AscendingDescending,
+            accent_tonic: true,
+        }
+    }
+}
+
+/// A note of a MIDI file, times in ticks
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MidiNote {
+    pub start: u32,
+    pub duration: u32,
+    /// MIDI note number, 60 being C4
+    pub key: Semitones,
+    pub velocity: u8,
+}
+
+/// A Standard MIDI File of format 0: one track on the first channel
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MidiFile {
+    /// Beats per minute, from `TEMPO_MIN` to `u16::MAX`, a slower tempo
+    /// being played and written as `TEMPO_MIN`
+    pub tempo: u16,
+    pub notes: Vec<MidiNote>,
+}
+
+impl MidiFile {
+    /// Empty file at `tempo` beats per minute, at least `TEMPO_MIN`
+    pub fn new(tempo: u16) -> Self {
+        Self {
+            tempo: tempo.max(TEMPO_MIN),
+            notes: Vec::new(),
+        }
+    }
+
+    /// Bytes of the file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend_from_slice(b"MThd");
+        bytes.extend_from_slice(&6u32.to_be_bytes());
+        bytes.extend_from_slice(&0u16.to_be_bytes()); // Format 0
+        bytes.extend_from_slice(&1u16.to_be_bytes()); // One track
+        bytes.extend_from_slice(&TICKS_PER_BEAT.to_be_bytes());
+        let track = self.track();
+        bytes.extend_from_slice(b"MTrk");
+        bytes.extend_from_slice(&(track.len() as u32).to_be_bytes());
+        bytes.extend(track);
+        bytes
+    }
+
+    /// Write the file to `path`
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Events of the track, each one after its delta time
+    fn track(&self) -> Vec<u8> {
+        let tempo = MICROSECONDS_PER_MINUTE / self.tempo.max(TEMPO_MIN) as u32;
+        let mut track: Vec<u8> = vec![0, META, META_TEMPO, 3];
+        track.extend_from_slice(&tempo.to_be_bytes()[1..]);
+        track.extend_from_slice(&[0, PROGRAM_CHANGE, PROGRAM_GUITAR]);
+        // Note off before note on at the same tick, so that a note played
+        // again is not cut
+        let mut events: Vec<(u32, u8, Semitones, u8)> = Vec::new();
+        for note in &self.notes {
+            events.push((note.start, NOTE_ON, note.key, note.velocity));
+            events.push((note.start + note.duration, NOTE_OFF, note.key, 0));
+        }
+        events.sort_by_key(|&(time, status, key, _)| (time, status, key));
+        let mut time = 0;
+        for (t, status, key, velocity) in events {
+            write_variable_length(&mut track, t - time);
+            track.extend_from_slice(&[status, key, velocity]);
+            time = t;
+        }
+        track.extend_from_slice(&[0, META, META_END_OF_TRACK, 0]);
+        track
+    }
+}
+
+/// Variable length quantity: 7 bits per byte, the highest bit set on all
+/// the bytes but the last
+fn write_variable_length(bytes: &mut Vec<u8>, value: u32) {
+    let mut groups: Vec<u8> = vec![(value & 0x7f) as u8];
+    let mut value = value >> 7;
+    while value > 0 {
+        groups.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.extend(groups.iter().rev());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        value,
+        bytes,
+        case(0, vec![0x00]),
+        case(0x40, vec![0x40]),
+        case(0x7f, vec![0x7f]),
+        case(0x80, vec![0x81, 0x00]),
+        case(0x2000, vec![0xc0, 0x00]),
+        case(0x0fff_ffff, vec![0xff, 0xff, 0xff, 0x7f])
+    )]
+    fn test_variable_length(value: u32, bytes: Vec<u8>) {
+        let mut result: Vec<u8> = Vec::new();
+        write_variable_length(&mut result, value);
+        assert_eq!(result, bytes);
+    }
+
+    #[rstest(
+        note_value,
+        ticks,
+        case(NoteValue::Whole, 1920),
+        case(NoteValue::Quarter, 480),
+        case(NoteValue::Triplet, 160),
+        case(NoteValue::Sixteenth, 120)
+    )]
+    fn test_note_value(note_value: NoteValue, ticks: u32) {
+        assert_eq!(note_value.ticks(), ticks);
+    }
+
+    #[rstest(
+        tempo,
+        bytes,
+        case(1, [0xe4, 0xe1, 0xc0]),
+        case(4, [0xe4, 0xe1, 0xc0]),
+        case(5, [0xb7, 0x1b, 0x00]),
+        case(u16::MAX, [0x00, 0x03, 0x93])
+    )]
+    fn test_tempo(tempo: u16, bytes: [u8; 3]) {
+        // 15 seconds per beat at most, in 24 bits
+        let mut midi = MidiFile::new(tempo);
+        assert_eq!(&midi.track()[4..7], &bytes);
+        midi.tempo = tempo;
+        assert_eq!(&midi.track()[4..7], &bytes);
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let mut midi = MidiFile::new(120);
+        midi.notes.push(MidiNote {
+            start: 0,
+            duration: 480,
+            key: 60,
+            velocity: 100,
+        });
+        midi.notes.push(MidiNote {
+            start: 480,
+            duration: 480,
+            key: 60,
+            velocity: 100,
+        });
+        let bytes = midi.to_bytes();
+        assert_eq!(&bytes[..14], b"MThd\0\0\0\x06\0\0\0\x01\x01\xe0");
+        assert_eq!(&bytes[14..18], b"MTrk");
+        let track = &bytes[22..];
+        let len =
+            u32::from_be_bytes([bytes[18], bytes[19], bytes[20], bytes[21]]);
+        assert_eq!(track.len(), len as usize);
+        // 500000 microseconds per beat
+        assert_eq!(&track[..7], &[0, 0xff, 0x51, 3, 0x07, 0xa1, 0x20]);
+        assert_eq!(&track[7..10], &[0, 0xc0, PROGRAM_GUITAR]);
+        assert_eq!(
+            &track[10..],
+            &[
+                0x00, 0x90, 60, 100, // On
+                0x83, 0x60, 0x80, 60, 0, // Off after 480 ticks
+                0x00, 0x90, 60, 100, // On again
+                0x83, 0x60, 0x80, 60, 0, // Off
+                0x00, 0xff, 0x2f, 0x00,
+            ][..]
+        );
+    }
+}
@@ -1,11 +1,11 @@
 use ukebox::{Semitones, PitchClass, StaffPosition};
+use std::error::Error;
 use std::fmt;
-use std::ops::Add;
+use std::ops::{Add, Sub};
 use std::str::FromStr;
 use crate::interval::Interval;
-
-/// Number of pitch classes.
-const PITCH_CLASS_COUNT: Semitones = 12;
+// Sign of the accidentals of a note, e.g. -2 for a double flat
+pub use ukebox::Accidental;
 
 // Custom error for strings that cannot be parsed into notes.
 #[derive(Debug)]
@@ -13,39 +13,63 @@ pub struct ParseNoteError {
     pub name: String,
 }
 
+impl Error for ParseNoteError {}
+
 impl fmt::Display for ParseNoteError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Could not parse note name \"{}\"", self.name)
     }
 }
 
-/// A note such a C, C# and so on.
+/// A note such a C, C# and so on, spelled by ukebox from its staff
+/// position and pitch class.
 #[derive(Debug, Clone, Copy)]
 pub struct Note {
     pub pitch_class: PitchClass,
     pub staff_position: StaffPosition,
 }
 
+impl Note {
+    pub fn new(pitch_class: PitchClass, staff_position: StaffPosition) -> Self {
+        Self {
+            pitch_class,
+            staff_position,
+        }
+    }
+
+    /// Sharps (positive) or flats (negative) between the natural note of
+    /// the staff position and the pitch class, from -6 to 5. Any
+    /// combination of staff position and pitch class can be spelled.
+    pub fn accidental(self) -> Accidental {
+        ukebox::Note::from(self).accidental()
+    }
+
+    /// Spell the note with at most one accidental, the way it is casually
+    /// called: `B#` is `C`, `Fbb` is `Eb` and `E##` is `F#`.
+    pub fn simplify(self) -> Self {
+        Self::from(ukebox::Note::from(self).simplify())
+    }
+
+    /// Octaves crossed between the letter and the pitch class, e.g. 1 for
+    /// `B#` which sounds as the `C` of the next octave
+    pub(crate) fn octave_shift(self) -> i8 {
+        ukebox::Note::from(self).octave_shift()
+    }
+}
+
 pub trait TraitGenerate {
     fn data_name(&self) -> &str;
 }
 
+/// Names used for the generated files, by pitch class
+const DATA_NAMES: [&str; 12] = [
+    "c", "c_sharp", "d", "d_sharp", "e", "f", "f_sharp", "g", "g_sharp", "a",
+    "a_sharp", "b",
+];
+
 impl TraitGenerate for Note {
     fn data_name(&self) -> &str {
-        match self.pitch_class {
-            PitchClass::C => "c",
-            PitchClass::CSharp => "c_sharp",
-            PitchClass::D => "d",
-            PitchClass::DSharp => "d_sharp",
-            PitchClass::E => "e",
-            PitchClass::F => "f",
-            PitchClass::FSharp => "f_sharp",
-            PitchClass::G => "g",
-            PitchClass::GSharp => "g_sharp",
-            PitchClass::A => "a",
-            PitchClass::ASharp => "a_sharp",
-            PitchClass::B => "b"
-        }
+        DATA_NAMES[self.pitch_class as usize]
     }
 }
 
@@ -55,154 +79,59 @@ pub trait TraitNoteSemitones {
 }
 
 impl TraitNoteSemitones for Note {
-    /// From Semitones
+    /// From Semitones, black keys spelled with a sharp
     fn from_semitones(semitones: Semitones) -> Self {
-        use ukebox::PitchClass::*;
-        use ukebox::StaffPosition::*;
-        let semitones_redux = semitones % PITCH_CLASS_COUNT;
-        // There does not seem to be a good way to turn integers into enum
-        // variants without using external crates. Hardcoding the mapping
-        // is not so elegant but at least readable.
-        let pitch_class = match semitones_redux {
-            0 => C,
-            1 => CSharp,
-            2 => D,
-            3 => DSharp,
-            4 => E,
-            5 => F,
-            6 => FSharp,
-            7 => G,
-            8 => GSharp,
-            9 => A,
-            10 => ASharp,
-            11 => B,
-            // Because of the modulo, `degre_compute` will always be in the correct range.
-            _ => unreachable!(),
-        };
-        let staff_position = match semitones_redux {
-            0 => CPos,
-            1 => CPos,
-            2 => DPos,
-            3 => DPos,
-            4 => EPos,
-            5 => FPos,
-            6 => FPos,
-            7 => GPos,
-            8 => GPos,
-            9 => APos,
-            10 => APos,
-            11 => BPos,
-            // Because of the modulo, `degre_compute` will always be in the correct range.
-            _ => unreachable!(),
-        };
-
-        Self {
-            pitch_class,
-            staff_position,
-        }
+        Self::from(ukebox::Note::from(PitchClass::from(semitones)))
     }
 }
 
 impl PartialEq for Note {
-    /// Treat two notes as equal if they are represented by the same symbol.
+    /// Treat two notes as equal if they are casually called the same.
     /// For example, `B sharp`, `C` and `D double flat` are all casually
-    /// called `C`.
+    /// called `C`, but `C#` and `Db` are two notes.
     fn eq(&self, other: &Self) -> bool {
-        self.to_string() == other.to_string()
+        let a = self.simplify();
+        let b = other.simplify();
+        a.pitch_class == b.pitch_class && a.staff_position == b.staff_position
     }
 }
 
 impl fmt::Display for Note {
+    /// The note as written, with as many sharps or flats as needed
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use PitchClass::*;
-        use StaffPosition::*;
-
-        let s = match (self.staff_position, self.pitch_class) {
-            // Notes on staff position for C.
-            (CPos, ASharp) => "Bb", // C double flat
-            (CPos, B) => "B",       // Cb
-            (CPos, C) => "C",
-            (CPos, CSharp) => "C#",
-            (CPos, D) => "D", // C double sharp
-            // Notes on staff position for D.
-            (DPos, C) => "C", // D double flat
-            (DPos, CSharp) => "Db",
-            (DPos, D) => "D",
-            (DPos, DSharp) => "D#",
-            (DPos, E) => "E", // D double sharp
-            // Notes on staff position for E.
-            (EPos, D) => "D", // E double flat
-            (EPos, DSharp) => "Eb",
-            (EPos, E) => "E",
-            (EPos, F) => "F",       // E#
-            (EPos, FSharp) => "F#", // E double sharp
-            // Notes on staff position for F.
-            (FPos, DSharp) => "Eb", // F double flat
-            (FPos, E) => "E",       // Fb
-            (FPos, F) => "F",
-            (FPos, FSharp) => "F#",
-            (FPos, G) => "G", // F double sharp
-            // Notes on staff position for G.
-            (GPos, F) => "F", // G double flat
-            (GPos, FSharp) => "Gb",
-            (GPos, G) => "G",
-            (GPos, GSharp) => "G#",
-            (GPos, A) => "A", // G double sharp
-            // Notes on staff position for A.
-            (APos, G) => "G", // A double flat
-            (APos, GSharp) => "Ab",
-            (APos, A) => "A",
-            (APos, ASharp) => "A#",
-            (APos, B) => "B", // A double sharp
-            // Notes on staff position for B.
-            (BPos, A) => "A", // B double flat
-            (BPos, ASharp) => "Bb",
-            (BPos, B) => "B",
-            (BPos, C) => "C",       // B#
-            (BPos, CSharp) => "C#", // B double sharp
-            _ => {
-                panic!("Impossible combination of PitchClass and StaffPosition")
-            },
-        };
-
-        write!(f, "{}", s)
+        write!(f, "{}", ukebox::Note::from(*self).written_name())
     }
 }
 
+#[cfg(feature = "serde")]
+serde_string!(Note);
+
 impl FromStr for Note {
     type Err = ParseNoteError;
 
+    /// Note from a letter followed by up to two sharps or two flats, such
+    /// as `C`, `Eb`, `F##` or `Bbb`
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use ukebox::PitchClass::*;
-        use ukebox::StaffPosition::*;
-
-        let name = s.to_string();
-
-        let (pitch_class, staff_position) = match s {
-            "C" => (C, CPos),
-            "C#" => (CSharp, CPos),
-            "Db" => (CSharp, DPos),
-            "D" => (D, DPos),
-            "D#" => (DSharp, DPos),
-            "Eb" => (DSharp, EPos),
-            "E" => (E, EPos),
-            "F" => (F, FPos),
-            "F#" => (FSharp, FPos),
-            "Gb" => (FSharp, GPos),
-            "G" => (G, GPos),
-            "G#" => (GSharp, GPos),
-            "Ab" => (GSharp, APos),
-            "A" => (A, APos),
-            "A#" => (ASharp, APos),
-            "Bb" => (ASharp, BPos),
-            "B" => (B, BPos),
-            _ => return Err(ParseNoteError { name }),
-        };
-
-        Ok(Self {
-            pitch_class,
-            staff_position,
-        })
+        match ukebox::Note::from_str(s) {
+            Ok(note) => Ok(Self::from(note)),
+            Err(_) => Err(ParseNoteError {
+                name: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl From<ukebox::Note> for Note {
+    /// Convert a note of ukebox keeping its spelling
+    fn from(note: ukebox::Note) -> Self {
+        Self::new(note.pitch_class, note.staff_position())
+    }
+}
+
+impl From<Note> for ukebox::Note {
+    /// Convert into a note of ukebox keeping its spelling
+    fn from(note: Note) -> Self {
+        ukebox::Note::new(note.pitch_class, note.staff_position)
     }
 }
 
@@ -211,12 +140,27 @@ impl Add<Interval> for Note {
 
     /// Get the next note when adding `interval` to the current note.
     fn add(self, interval: Interval) -> Self {
-        let pitch_class = self.pitch_class + interval.to_semitones();
-        let staff_position = self.staff_position + (interval.to_number() - 1);
-        Self {
-            pitch_class,
-            staff_position,
-        }
+        Self::from(ukebox::Note::from(self) + interval)
+    }
+}
+
+impl Add<Semitones> for Note {
+    type Output = Self;
+
+    /// Get the note `n` semitones higher, spelled like ukebox does: the
+    /// spelling is kept for a whole number of octaves, else sharps are used
+    fn add(self, n: Semitones) -> Self {
+        Self::from(ukebox::Note::from(self) + n)
+    }
+}
+
+impl Sub<Semitones> for Note {
+    type Output = Self;
+
+    /// Get the note `n` semitones lower, spelled like ukebox does: the
+    /// spelling is kept for a whole number of octaves, else flats are used
+    fn sub(self, n: Semitones) -> Self {
+        Self::from(ukebox::Note::from(self) - n)
     }
 }
 
@@ -225,7 +169,6 @@ mod tests {
     use super::*;
     use rstest::rstest;
     use Interval::*;
-    use ukebox::Interval;
 
     #[rstest(
     s,
@@ -271,4 +214,107 @@ mod tests {
         let note = Note::from_str(note_name).unwrap();
         assert_eq!(note + interval, Note::from_str(result_name).unwrap());
     }
-}
\ No newline at end of file
+
+    #[rstest(
+        s,
+        accidental,
+        case("Cb", -1),
+        case("E#", 1),
+        case("Fb", -1),
+        case("B#", 1),
+        case("F##", 2),
+        case("Bbb", -2),
+        case("Dbb", -2),
+        case("C##", 2)
+    )]
+    fn test_double_accidentals(s: &str, accidental: Accidental) {
+        let note = Note::from_str(s).unwrap();
+        assert_eq!(note.accidental(), accidental);
+        assert_eq!(note.to_string(), s);
+    }
+
+    #[rstest(s, case(""), case("H"), case("c"), case("C#b"), case("C###"))]
+    fn test_from_str_error(s: &str) {
+        assert!(Note::from_str(s).is_err());
+    }
+
+    #[rstest(
+        a,
+        b,
+        case("B#", "C"),
+        case("Cb", "B"),
+        case("Dbb", "C"),
+        case("Fbb", "Eb"),
+        case("E##", "F#"),
+        case("B##", "C#")
+    )]
+    fn test_casual_name(a: &str, b: &str) {
+        let a = Note::from_str(a).unwrap();
+        assert_eq!(a.simplify().to_string(), b);
+        assert_eq!(a, Note::from_str(b).unwrap());
+    }
+
+    #[test]
+    fn test_enharmonic_not_equal() {
+        let c_sharp = Note::from_str("C#").unwrap();
+        assert_ne!(c_sharp, Note::from_str("Db").unwrap());
+    }
+
+    #[test]
+    fn test_every_combination() {
+        for p in 0..7 {
+            for semitones in 0..12 {
+                let note = Note::new(
+                    PitchClass::from(semitones),
+                    StaffPosition::from(p),
+                );
+                let name = note.to_string();
+                assert!(name.starts_with(&"CDEFGAB"[p as usize..=p as usize]));
+                assert!(note.accidental().abs() <= 6);
+                if note.accidental().abs() <= 2 {
+                    assert_eq!(Note::from_str(&name).unwrap(), note);
+                }
+                let _ = note.simplify().to_string();
+                let ukebox_note = ukebox::Note::from(note);
+                let back = Note::from(ukebox_note);
+                assert_eq!(back.staff_position, note.staff_position);
+            }
+        }
+    }
+
+    #[rstest(s, case("C"), case("Db"), case("F#"), case("Bb"), case("E#"))]
+    fn test_ukebox_round_trip(s: &str) {
+        let note = Note::from_str(s).unwrap();
+        let ukebox_note = ukebox::Note::from(note);
+        assert_eq!(ukebox_note.pitch_class, note.pitch_class);
+        assert_eq!(Note::from(ukebox_note).to_string(), s);
+    }
+
+    #[rstest(
+        note,
+        n,
+        add,
+        sub,
+        case("C", 0, "C", "C"),
+        case("C", 1, "C#", "B"),
+        case("D", 1, "D#", "Db"),
+        case("Bb", 2, "C", "Ab"),
+        case("Bb", 12, "Bb", "Bb"),
+        case("E", 7, "B", "A")
+    )]
+    fn test_add_sub_semitones(note: &str, n: Semitones, add: &str, sub: &str) {
+        let note = Note::from_str(note).unwrap();
+        assert_eq!((note + n).to_string(), add);
+        assert_eq!((note - n).to_string(), sub);
+    }
+
+    #[cfg(feature = "serde")]
+    #[rstest(s, case("C"), case("Db"), case("F##"))]
+    fn test_serde(s: &str) {
+        let note = Note::from_str(s).unwrap();
+        let json = serde_json::to_string(&note).unwrap();
+        assert_eq!(json, format!("\"{}\"", s));
+        assert_eq!(serde_json::from_str::<Note>(&json).unwrap(), note);
+        assert!(serde_json::from_str::<Note>("\"H\"").is_err());
+    }
+}
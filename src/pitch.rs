@@ -0,0 +1,229 @@
+use crate::interval::Interval;
+use crate::note::{Note, TraitNoteSemitones};
+use std::error::Error;
+use std::fmt;
+use std::ops::Add;
+use std::str::FromStr;
+use ukebox::Semitones;
+
+/// Number of pitch classes.
+const PITCH_CLASS_COUNT: Semitones = 12;
+/// Highest MIDI note number
+const MIDI_MAX: Semitones = 127;
+
+/// Octave number in scientific pitch notation, C4 being the middle C
+pub type Octave = i8;
+
+// Custom error for strings that cannot be parsed into pitches.
+#[derive(Debug)]
+pub struct ParsePitchError {
+    pub name: String,
+}
+
+impl Error for ParsePitchError {}
+
+impl fmt::Display for ParsePitchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not parse pitch name \"{}\"", self.name)
+    }
+}
+
+/// A note with its octave such as E2 or C#4.
+/// https://en.wikipedia.org/wiki/Scientific_pitch_notation
+/// `octave` is the octave of the pitch class; the written octave follows the
+/// letter, so that `B#3` and `C4` have the same octave and MIDI number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pitch {
+    pub note: Note,
+    pub octave: Octave,
+}
+
+impl Pitch {
+    /// Get the pitch of a MIDI note number (60 is C4), spelled with sharps.
+    /// Numbers above 127 give the highest pitch, G9.
+    pub fn from_midi(midi: Semitones) -> Self {
+        let midi = midi.min(MIDI_MAX);
+        Self {
+            note: Note::from_semitones(midi),
+            octave: (midi / PITCH_CLASS_COUNT) as Octave - 1,
+        }
+    }
+
+    /// MIDI note number of the pitch (60 is C4)
+    pub fn midi(self) -> Semitones {
+        let octave = (self.octave + 1) as Semitones;
+        octave * PITCH_CLASS_COUNT + self.note.pitch_class as Semitones
+    }
+
+    /// Get the pitch `n` semitones higher, spelled with sharps, `None`
+    /// above G9
+    pub fn checked_add(self, n: Semitones) -> Option<Self> {
+        match self.midi().checked_add(n) {
+            Some(midi) if midi <= MIDI_MAX => Some(Self::from_midi(midi)),
+            _ => None,
+        }
+    }
+
+    /// Get the pitch `interval` higher, keeping the spelling of the
+    /// interval, `None` above G9
+    pub fn checked_add_interval(self, interval: Interval) -> Option<Self> {
+        match self.midi().checked_add(interval.to_semitones()) {
+            Some(midi) if midi <= MIDI_MAX => Some(Self {
+                note: self.note + interval,
+                octave: (midi / PITCH_CLASS_COUNT) as Octave - 1,
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Pitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.note, self.octave - self.note.octave_shift())
+    }
+}
+
+#[cfg(feature = "serde")]
+serde_string!(Pitch);
+
+impl FromStr for Pitch {
+    type Err = ParsePitchError;
+
+    /// Pitch from its scientific notation, from C-1 to G9
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_string();
+        let split = match s.find(|c: char| c.is_ascii_digit() || c == '-') {
+            Some(split) => split,
+            None => return Err(ParsePitchError { name }),
+        };
+        let note = match Note::from_str(&s[..split]) {
+            Ok(note) => note,
+            Err(_) => return Err(ParsePitchError { name }),
+        };
+        let octave = match s[split..].parse::<Octave>() {
+            Ok(octave) if (-2..=10).contains(&octave) => {
+                octave + note.octave_shift()
+            },
+            _ => return Err(ParsePitchError { name }),
+        };
+        let pitch = Self { note, octave };
+        if !(-1..=9).contains(&octave)
+            || octave == 9
+                && pitch.note.pitch_class as Semitones
+                    > MIDI_MAX % PITCH_CLASS_COUNT
+        {
+            return Err(ParsePitchError { name });
+        }
+        Ok(pitch)
+    }
+}
+
+impl Add<Semitones> for Pitch {
+    type Output = Self;
+
+    /// Get the pitch `n` semitones higher, spelled with sharps, G9 at most
+    fn add(self, n: Semitones) -> Self {
+        self.checked_add(n)
+            .unwrap_or_else(|| Self::from_midi(MIDI_MAX))
+    }
+}
+
+impl Add<Interval> for Pitch {
+    type Output = Self;
+
+    /// Get the pitch `interval` higher, keeping the spelling of the interval,
+    /// G9 at most
+    fn add(self, interval: Interval) -> Self {
+        self.checked_add_interval(interval)
+            .unwrap_or_else(|| Self::from_midi(MIDI_MAX))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use Interval::*;
+
+    #[rstest(
+        s,
+        midi,
+        case("C-1", 0),
+        case("E2", 40),
+        case("A2", 45),
+        case("D3", 50),
+        case("G3", 55),
+        case("B3", 59),
+        case("C4", 60),
+        case("C#4", 61),
+        case("Db4", 61),
+        case("A4", 69),
+        case("E6", 88),
+        case("G9", 127)
+    )]
+    fn test_midi(s: &str, midi: Semitones) {
+        let pitch = Pitch::from_str(s).unwrap();
+        assert_eq!(pitch.midi(), midi);
+        assert_eq!(pitch.to_string(), s);
+        assert_eq!(Pitch::from_midi(midi).midi(), midi);
+    }
+
+    #[rstest(
+        s,
+        midi,
+        case("B#3", 60),
+        case("Cb4", 59),
+        case("E#4", 65),
+        case("Fb4", 64)
+    )]
+    fn test_octave_of_the_letter(s: &str, midi: Semitones) {
+        let pitch = Pitch::from_str(s).unwrap();
+        assert_eq!(pitch.midi(), midi);
+        assert_eq!(pitch.to_string(), s);
+    }
+
+    #[rstest(s, case("H4"), case("C"), case("C10"), case("G#9"), case("C-2"))]
+    fn test_from_str_error(s: &str) {
+        assert!(Pitch::from_str(s).is_err());
+    }
+
+    #[rstest(
+        pitch,
+        interval,
+        result,
+        case("E2", PlusOctave, "E3"),
+        case("E2", PlusPlusOctave, "E4"),
+        case("B3", MajorSecond, "C#4"),
+        case("G3", MinorThird, "Bb3"),
+        case("A3", MinorThird, "C4"),
+        case("C#4", MajorThird, "E#4"),
+        case("Bb3", PerfectUnison, "Bb3")
+    )]
+    fn test_add_interval(pitch: &str, interval: Interval, result: &str) {
+        let pitch = Pitch::from_str(pitch).unwrap();
+        assert_eq!((pitch + interval).to_string(), result);
+    }
+
+    #[test]
+    fn test_add_past_g9() {
+        let g9 = Pitch::from_str("G9").unwrap();
+        let e2 = Pitch::from_str("E2").unwrap();
+        assert_eq!(e2.checked_add(87), Some(g9));
+        assert_eq!(e2.checked_add(88), None);
+        assert_eq!(e2.checked_add(Semitones::MAX), None);
+        assert_eq!(e2 + 200, g9);
+        assert_eq!(g9 + Semitones::MAX, g9);
+        assert_eq!(g9.checked_add_interval(MajorSecond), None);
+        assert_eq!(g9 + PlusPlusOctave, g9);
+        assert_eq!(Pitch::from_midi(200), g9);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let pitch = Pitch::from_str("C#4").unwrap();
+        let json = serde_json::to_string(&pitch).unwrap();
+        assert_eq!(json, "\"C#4\"");
+        assert_eq!(serde_json::from_str::<Pitch>(&json).unwrap(), pitch);
+    }
+}
@@ -0,0 +1,540 @@
+use std::fmt;
+use std::ops::Add;
+use ukebox::{PitchClass, Semitones};
+
+/// Number of pitch classes.
+const PITCH_CLASS_COUNT: Semitones = 12;
+
+/// Bit mask with all 12 pitch classes set.
+const FULL_MASK: u16 = 0x0fff;
+
+/// Forte's catalogue of set classes, indexed by ordinal number. Each entry is
+/// (ordinal, Z-related, prime form). Set classes with more than 6 pitch
+/// classes carry the number of their complement, so only the classes up to
+/// hexachords have to be listed.
+/// https://en.wikipedia.org/wiki/List_of_set_classes
+type ForteEntry = (u8, bool, &'static [Semitones]);
+
+const FORTE_TRICHORDS: [ForteEntry; 12] = [
+    (1, false, &[0, 1, 2]),
+    (2, false, &[0, 1, 3]),
+    (3, false, &[0, 1, 4]),
+    (4, false, &[0, 1, 5]),
+    (5, false, &[0, 1, 6]),
+    (6, false, &[0, 2, 4]),
+    (7, false, &[0, 2, 5]),
+    (8, false, &[0, 2, 6]),
+    (9, false, &[0, 2, 7]),
+    (10, false, &[0, 3, 6]),
+    (11, false, &[0, 3, 7]),
+    (12, false, &[0, 4, 8]),
+];
+
+const FORTE_TETRACHORDS: [ForteEntry; 29] = [
+    (1, false, &[0, 1, 2, 3]),
+    (2, false, &[0, 1, 2, 4]),
+    (3, false, &[0, 1, 3, 4]),
+    (4, false, &[0, 1, 2, 5]),
+    (5, false, &[0, 1, 2, 6]),
+    (6, false, &[0, 1, 2, 7]),
+    (7, false, &[0, 1, 4, 5]),
+    (8, false, &[0, 1, 5, 6]),
+    (9, false, &[0, 1, 6, 7]),
+    (10, false, &[0, 2, 3, 5]),
+    (11, false, &[0, 1, 3, 5]),
+    (12, false, &[0, 2, 3, 6]),
+    (13, false, &[0, 1, 3, 6]),
+    (14, false, &[0, 2, 3, 7]),
+    (15, true, &[0, 1, 4, 6]),
+    (16, false, &[0, 1, 5, 7]),
+    (17, false, &[0, 3, 4, 7]),
+    (18, false, &[0, 1, 4, 7]),
+    (19, false, &[0, 1, 4, 8]),
+    (20, false, &[0, 1, 5, 8]),
+    (21, false, &[0, 2, 4, 6]),
+    (22, false, &[0, 2, 4, 7]),
+    (23, false, &[0, 2, 5, 7]),
+    (24, false, &[0, 2, 4, 8]),
+    (25, false, &[0, 2, 6, 8]),
+    (26, false, &[0, 3, 5, 8]),
+    (27, false, &[0, 2, 5, 8]),
+    (28, false, &[0, 3, 6, 9]),
+    (29, true, &[0, 1, 3, 7]),
+];
+
+const FORTE_PENTACHORDS: [ForteEntry; 38] = [
+    (1, false, &[0, 1, 2, 3, 4]),
+    (2, false, &[0, 1, 2, 3, 5]),
+    (3, false, &[0, 1, 2, 4, 5]),
+    (4, false, &[0, 1, 2, 3, 6]),
+    (5, false, &[0, 1, 2, 3, 7]),
+    (6, false, &[0, 1, 2, 5, 6]),
+    (7, false, &[0, 1, 2, 6, 7]),
+    (8, false, &[0, 2, 3, 4, 6]),
+    (9, false, &[0, 1, 2, 4, 6]),
+    (10, false, &[0, 1, 3, 4, 6]),
+    (11, false, &[0, 2, 3, 4, 7]),
+    (12, true, &[0, 1, 3, 5, 6]),
+    (13, false, &[0, 1, 2, 4, 8]),
+    (14, false, &[0, 1, 2, 5, 7]),
+    (15, false, &[0, 1, 2, 6, 8]),
+    (16, false, &[0, 1, 3, 4, 7]),
+    (17, true, &[0, 1, 3, 4, 8]),
+    (18, true, &[0, 1, 4, 5, 7]),
+    (19, false, &[0, 1, 3, 6, 7]),
+    (20, false, &[0, 1, 5, 6, 8]),
+    (21, false, &[0, 1, 4, 5, 8]),
+    (22, false, &[0, 1, 4, 7, 8]),
+    (23, false, &[0, 2, 3, 5, 7]),
+    (24, false, &[0, 1, 3, 5, 7]),
+    (25, false, &[0, 2, 3, 5, 8]),
+    (26, false, &[0, 2, 4, 5, 8]),
+    (27, false, &[0, 1, 3, 5, 8]),
+    (28, false, &[0, 2, 3, 6, 8]),
+    (29, false, &[0, 1, 3, 6, 8]),
+    (30, false, &[0, 1, 4, 6, 8]),
+    (31, false, &[0, 1, 3, 6, 9]),
+    (32, false, &[0, 1, 4, 6, 9]),
+    (33, false, &[0, 2, 4, 6, 8]),
+    (34, false, &[0, 2, 4, 6, 9]),
+    (35, false, &[0, 2, 4, 7, 9]),
+    (36, true, &[0, 1, 2, 4, 7]),
+    (37, true, &[0, 3, 4, 5, 8]),
+    (38, true, &[0, 1, 2, 5, 8]),
+];
+
+const FORTE_HEXACHORDS: [ForteEntry; 50] = [
+    (1, false, &[0, 1, 2, 3, 4, 5]),
+    (2, false, &[0, 1, 2, 3, 4, 6]),
+    (3, true, &[0, 1, 2, 3, 5, 6]),
+    (4, true, &[0, 1, 2, 4, 5, 6]),
+    (5, false, &[0, 1, 2, 3, 6, 7]),
+    (6, true, &[0, 1, 2, 5, 6, 7]),
+    (7, false, &[0, 1, 2, 6, 7, 8]),
+    (8, false, &[0, 2, 3, 4, 5, 7]),
+    (9, false, &[0, 1, 2, 3, 5, 7]),
+    (10, true, &[0, 1, 3, 4, 5, 7]),
+    (11, true, &[0, 1, 2, 4, 5, 7]),
+    (12, true, &[0, 1, 2, 4, 6, 7]),
+    (13, true, &[0, 1, 3, 4, 6, 7]),
+    (14, false, &[0, 1, 3, 4, 5, 8]),
+    (15, false, &[0, 1, 2, 4, 5, 8]),
+    (16, false, &[0, 1, 4, 5, 6, 8]),
+    (17, true, &[0, 1, 2, 4, 7, 8]),
+    (18, false, &[0, 1, 2, 5, 7, 8]),
+    (19, true, &[0, 1, 3, 4, 7, 8]),
+    (20, false, &[0, 1, 4, 5, 8, 9]),
+    (21, false, &[0, 2, 3, 4, 6, 8]),
+    (22, false, &[0, 1, 2, 4, 6, 8]),
+    (23, true, &[0, 2, 3, 5, 6, 8]),
+    (24, true, &[0, 1, 3, 4, 6, 8]),
+    (25, true, &[0, 1, 3, 5, 6, 8]),
+    (26, true, &[0, 1, 3, 5, 7, 8]),
+    (27, false, &[0, 1, 3, 4, 6, 9]),
+    (28, true, &[0, 1, 3, 5, 6, 9]),
+    (29, true, &[0, 1, 3, 6, 8, 9]),
+    (30, false, &[0, 1, 3, 6, 7, 9]),
+    (31, false, &[0, 1, 3, 5, 8, 9]),
+    (32, false, &[0, 2, 4, 5, 7, 9]),
+    (33, false, &[0, 2, 3, 5, 7, 9]),
+    (34, false, &[0, 1, 3, 5, 7, 9]),
+    (35, false, &[0, 2, 4, 6, 8, 10]),
+    (36, true, &[0, 1, 2, 3, 4, 7]),
+    (37, true, &[0, 1, 2, 3, 4, 8]),
+    (38, true, &[0, 1, 2, 3, 7, 8]),
+    (39, true, &[0, 2, 3, 4, 5, 8]),
+    (40, true, &[0, 1, 2, 3, 5, 8]),
+    (41, true, &[0, 1, 2, 3, 6, 8]),
+    (42, true, &[0, 1, 2, 3, 6, 9]),
+    (43, true, &[0, 1, 2, 5, 6, 8]),
+    (44, true, &[0, 1, 2, 5, 6, 9]),
+    (45, true, &[0, 2, 3, 4, 6, 9]),
+    (46, true, &[0, 1, 2, 4, 6, 9]),
+    (47, true, &[0, 1, 2, 4, 7, 9]),
+    (48, true, &[0, 1, 2, 5, 7, 9]),
+    (49, true, &[0, 1, 3, 4, 7, 9]),
+    (50, true, &[0, 1, 4, 6, 7, 9]),
+];
+
+/// Name of a set class in Forte's catalogue, e.g. `7-35` for the diatonic
+/// collection or `6-Z3` for a Z-related hexachord.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ForteNumber {
+    pub cardinality: usize,
+    pub ordinal: u8,
+    pub z: bool,
+}
+
+impl fmt::Display for ForteNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let z = if self.z { "Z" } else { "" };
+        write!(f, "{}-{}{}", self.cardinality, z, self.ordinal)
+    }
+}
+
+/// An unordered set of pitch classes, independent of the octave.
+/// https://en.wikipedia.org/wiki/Set_theory_(music)
+///
+/// The set is stored as a 12 bit mask, bit 0 standing for C, bit 1 for C#
+/// and so on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PitchClassSet {
+    mask: u16,
+}
+
+impl PitchClassSet {
+    /// Build a set from semitones above C. Values > 11 wrap around.
+    pub fn from_semitones(semitones: &[Semitones]) -> Self {
+        let mask = semitones
+            .iter()
+            .fold(0, |m, s| m | 1 << (s % PITCH_CLASS_COUNT));
+        Self { mask }
+    }
+
+    /// Build a set from pitch classes.
+    pub fn from_pitch_classes(pitch_classes: &[PitchClass]) -> Self {
+        let semitones: Vec<Semitones> =
+            pitch_classes.iter().map(|&pc| pc as Semitones).collect();
+        Self::from_semitones(&semitones)
+    }
+
+    /// Return `true` if the pitch class is part of the set.
+    pub fn contains(self, pitch_class: PitchClass) -> bool {
+        self.mask & (1 << pitch_class as Semitones) != 0
+    }
+
+    /// Number of pitch classes in the set.
+    pub fn len(self) -> usize {
+        self.mask.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.mask == 0
+    }
+
+    /// Semitones above C of every pitch class in the set, ascending.
+    pub fn semitones(self) -> Vec<Semitones> {
+        (0..PITCH_CLASS_COUNT)
+            .filter(|s| self.mask & (1 << s) != 0)
+            .collect()
+    }
+
+    /// Pitch classes in the set, ascending from C.
+    pub fn pitch_classes(self) -> Vec<PitchClass> {
+        self.semitones().into_iter().map(PitchClass::from).collect()
+    }
+
+    /// Transpose the set `n` semitones up (Tn).
+    pub fn transpose(self, n: Semitones) -> Self {
+        let n = (n % PITCH_CLASS_COUNT) as u32;
+        let mask = ((self.mask as u32) << n | (self.mask as u32) >> (12 - n))
+            as u16
+            & FULL_MASK;
+        Self { mask }
+    }
+
+    /// Invert the set around C (I).
+    pub fn invert(self) -> Self {
+        let semitones: Vec<Semitones> = self
+            .semitones()
+            .iter()
+            .map(|s| (PITCH_CLASS_COUNT - s) % PITCH_CLASS_COUNT)
+            .collect();
+        Self::from_semitones(&semitones)
+    }
+
+    /// All pitch classes that are not part of the set.
+    pub fn complement(self) -> Self {
+        Self {
+            mask: !self.mask & FULL_MASK,
+        }
+    }
+
+    /// Pitch classes that are part of both sets.
+    pub fn intersection(self, other: Self) -> Self {
+        Self {
+            mask: self.mask & other.mask,
+        }
+    }
+
+    /// Pitch classes that are part of either set.
+    pub fn union(self, other: Self) -> Self {
+        Self {
+            mask: self.mask | other.mask,
+        }
+    }
+
+    /// Most compact ordering of the set, starting on the pitch class that
+    /// gives the smallest span (ties broken packing to the left as in Rahn).
+    pub fn normal_form(self) -> Vec<Semitones> {
+        let semitones = self.semitones();
+        let n = semitones.len();
+        (0..n)
+            .map(|i| {
+                let mut rotation: Vec<Semitones> = semitones[i..].to_vec();
+                rotation.extend(&semitones[..i]);
+                rotation
+            })
+            .min_by_key(|rotation| {
+                // Compare the span first, then the intervals from the
+                // first note to the second last, third last and so on.
+                let first = rotation[0];
+                rotation
+                    .iter()
+                    .rev()
+                    .map(|s| {
+                        (s + PITCH_CLASS_COUNT - first) % PITCH_CLASS_COUNT
+                    })
+                    .collect::<Vec<Semitones>>()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Prime form of the set class (Rahn), the most compact form among all
+    /// transpositions and inversions, starting on 0.
+    pub fn prime_form(self) -> Vec<Semitones> {
+        self.set_class().semitones()
+    }
+
+    /// Representative of the set class, i.e. the transposition or inversion
+    /// with the smallest bit mask. This is Rahn's prime form.
+    fn set_class(self) -> Self {
+        let inversion = self.invert();
+        (0..PITCH_CLASS_COUNT)
+            .flat_map(|n| vec![self.transpose(n), inversion.transpose(n)])
+            .min_by_key(|set| set.mask)
+            .unwrap_or(self)
+    }
+
+    /// Number of each interval class (1 to 6) between all pairs of pitch
+    /// classes.
+    pub fn interval_vector(self) -> [u8; 6] {
+        let semitones = self.semitones();
+        let mut vector = [0; 6];
+        for (i, a) in semitones.iter().enumerate() {
+            for b in &semitones[i + 1..] {
+                let d = b - a;
+                let interval_class = d.min(PITCH_CLASS_COUNT - d);
+                vector[interval_class as usize - 1] += 1;
+            }
+        }
+        vector
+    }
+
+    /// Name of the set class in Forte's catalogue.
+    pub fn forte_number(self) -> ForteNumber {
+        let cardinality = self.len();
+        // The empty set, single notes and their complements are not listed
+        // in the tables, dyads are numbered after their interval class.
+        let (ordinal, z) = match cardinality {
+            0 | 1 | 11 | 12 => (1, false),
+            2 => {
+                let vector = self.interval_vector();
+                let interval_class = vector.iter().position(|&c| c > 0);
+                (interval_class.unwrap_or(0) as u8 + 1, false)
+            },
+            // Larger sets share the number of their complement.
+            7..=10 => {
+                return ForteNumber {
+                    cardinality,
+                    ..self.complement().forte_number()
+                }
+            },
+            _ => {
+                let table: &[ForteEntry] = match cardinality {
+                    3 => &FORTE_TRICHORDS,
+                    4 => &FORTE_TETRACHORDS,
+                    5 => &FORTE_PENTACHORDS,
+                    _ => &FORTE_HEXACHORDS,
+                };
+                let set_class = self.set_class();
+                table
+                    .iter()
+                    .find(|(_, _, prime)| {
+                        Self::from_semitones(prime).set_class() == set_class
+                    })
+                    .map(|&(ordinal, z, _)| (ordinal, z))
+                    // The tables list every set class of 3 to 6 notes.
+                    .unwrap_or_else(|| unreachable!())
+            },
+        };
+        ForteNumber {
+            cardinality,
+            ordinal,
+            z,
+        }
+    }
+
+    /// Number of transpositions (out of 12) that map the set onto itself.
+    /// 1 means the set is not transpositionally symmetric.
+    pub fn transpositional_symmetry(self) -> usize {
+        (0..PITCH_CLASS_COUNT)
+            .filter(|&n| self.transpose(n) == self)
+            .count()
+    }
+
+    /// Number of inversions (TnI) that map the set onto itself.
+    pub fn inversional_symmetry(self) -> usize {
+        let inversion = self.invert();
+        (0..PITCH_CLASS_COUNT)
+            .filter(|&n| inversion.transpose(n) == self)
+            .count()
+    }
+
+    /// Number of distinct transpositions of the set. A mode of limited
+    /// transposition (Messiaen) has less than 12.
+    pub fn transposition_count(self) -> usize {
+        PITCH_CLASS_COUNT as usize / self.transpositional_symmetry()
+    }
+
+    /// Return `true` for a mode of limited transposition.
+    /// https://en.wikipedia.org/wiki/Mode_of_limited_transposition
+    pub fn is_limited_transposition(self) -> bool {
+        self.transposition_count() < PITCH_CLASS_COUNT as usize
+    }
+}
+
+impl Add<Semitones> for PitchClassSet {
+    type Output = Self;
+
+    /// Transpose the set `n` semitones up.
+    fn add(self, n: Semitones) -> Self {
+        self.transpose(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        table,
+        cardinality,
+        case(&FORTE_TRICHORDS, 3),
+        case(&FORTE_TETRACHORDS, 4),
+        case(&FORTE_PENTACHORDS, 5),
+        case(&FORTE_HEXACHORDS, 6)
+    )]
+    fn test_forte_table_is_complete(table: &[ForteEntry], cardinality: u32) {
+        let mut expected: Vec<PitchClassSet> = (0..=FULL_MASK)
+            .filter(|m| m.count_ones() == cardinality)
+            .map(|mask| PitchClassSet { mask }.set_class())
+            .collect();
+        expected.sort_by_key(|s| s.mask);
+        expected.dedup();
+        let mut found: Vec<PitchClassSet> = table
+            .iter()
+            .map(|(_, _, prime)| {
+                PitchClassSet::from_semitones(prime).set_class()
+            })
+            .collect();
+        found.sort_by_key(|s| s.mask);
+        found.dedup();
+        assert_eq!(found.len(), table.len());
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_z_related_share_interval_vector() {
+        for table in [
+            &FORTE_TETRACHORDS[..],
+            &FORTE_PENTACHORDS[..],
+            &FORTE_HEXACHORDS[..],
+        ]
+        .iter()
+        {
+            for &(ordinal, z, prime) in table.iter() {
+                let vector =
+                    PitchClassSet::from_semitones(prime).interval_vector();
+                let partners = table
+                    .iter()
+                    .filter(|(o, _, p)| {
+                        *o != ordinal
+                            && PitchClassSet::from_semitones(p)
+                                .interval_vector()
+                                == vector
+                    })
+                    .count();
+                assert_eq!(partners > 0, z, "ordinal {}", ordinal);
+            }
+        }
+    }
+
+    #[rstest(
+        semitones,
+        forte,
+        case(&[0, 2, 4, 5, 7, 9, 11], "7-35"),
+        case(&[0, 2, 4, 7, 9], "5-35"),
+        case(&[0, 2, 3, 5, 7, 8, 11], "7-32"),
+        case(&[0, 2, 3, 5, 7, 9, 11], "7-34"),
+        case(&[0, 2, 4, 6, 8, 10], "6-35"),
+        case(&[0, 1, 3, 4, 6, 7, 9, 10], "8-28"),
+        case(&[0, 1, 4, 6], "4-Z15"),
+        case(&[0, 1, 2, 3, 5, 6], "6-Z3"),
+        case(&[0, 4, 7], "3-11"),
+        case(&[0, 6], "2-6"),
+        case(&[0, 1, 2, 3, 5, 6, 7, 8, 9, 11], "10-6")
+    )]
+    fn test_forte_number(semitones: &[Semitones], forte: &str) {
+        let set = PitchClassSet::from_semitones(semitones);
+        assert_eq!(set.forte_number().to_string(), forte);
+    }
+
+    #[rstest(
+        semitones,
+        prime,
+        case(&[0, 4, 7], &[0, 3, 7]),
+        case(&[0, 2, 4, 5, 7, 9, 11], &[0, 1, 3, 5, 6, 8, 10]),
+        case(&[0, 3, 5, 7, 10], &[0, 2, 4, 7, 9]),
+        case(&[0, 1, 3, 7, 8], &[0, 1, 5, 6, 8])
+    )]
+    fn test_prime_form(semitones: &[Semitones], prime: &[Semitones]) {
+        let set = PitchClassSet::from_semitones(semitones);
+        assert_eq!(set.prime_form(), prime);
+    }
+
+    #[test]
+    fn test_normal_form() {
+        let set = PitchClassSet::from_semitones(&[0, 4, 7, 11]);
+        assert_eq!(set.normal_form(), vec![11, 0, 4, 7]);
+        let set = PitchClassSet::from_semitones(&[2, 5, 9]);
+        assert_eq!(set.normal_form(), vec![2, 5, 9]);
+    }
+
+    #[test]
+    fn test_interval_vector() {
+        let major = PitchClassSet::from_semitones(&[0, 2, 4, 5, 7, 9, 11]);
+        assert_eq!(major.interval_vector(), [2, 5, 4, 3, 6, 1]);
+    }
+
+    #[rstest(
+        semitones,
+        count,
+        case(&[0, 2, 4, 5, 7, 9, 11], 12),
+        case(&[0, 2, 4, 6, 8, 10], 2),
+        case(&[0, 1, 3, 4, 6, 7, 9, 10], 3),
+        case(&[0, 2, 3, 4, 6, 7, 8, 10, 11], 4),
+        case(&[0, 1, 5, 6, 7, 11], 6)
+    )]
+    fn test_transposition_count(semitones: &[Semitones], count: usize) {
+        let set = PitchClassSet::from_semitones(semitones);
+        assert_eq!(set.transposition_count(), count);
+        assert_eq!(set.is_limited_transposition(), count < 12);
+    }
+
+    #[test]
+    fn test_complement_and_transpose() {
+        let major = PitchClassSet::from_semitones(&[0, 2, 4, 5, 7, 9, 11]);
+        assert_eq!(
+            major.complement(),
+            PitchClassSet::from_semitones(&[1, 3, 6, 8, 10])
+        );
+        assert_eq!(
+            major + 7,
+            PitchClassSet::from_semitones(&[7, 9, 11, 0, 2, 4, 6])
+        );
+        assert!((major + 2).contains(PitchClass::FSharp));
+        assert_eq!(major.inversional_symmetry(), 1);
+    }
+}
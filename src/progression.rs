@@ -0,0 +1,259 @@
+use crate::capo::Capo;
+use crate::chord_scale::{ChordScale, TraitChordScale};
+use crate::note::{Note, TraitNoteSemitones};
+use crate::pitch_class_set::PitchClassSet;
+use crate::scale::{Scale, ScaleType};
+use crate::tuning::Tuning;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use ukebox::{Chord, Semitones};
+
+/// Bonus for each chord whose notes all belong to the key
+const SCORE_DIATONIC_CHORD: u32 = 3;
+/// Bonus for each chord built on the tonic with the third of the key
+const SCORE_TONIC_CHORD: u32 = 2;
+/// Bonus for a tonic chord ending the progression
+const SCORE_LAST_CHORD: u32 = 2;
+/// Bonus for a tonic chord starting the progression
+const SCORE_FIRST_CHORD: u32 = 1;
+/// Bonus for each chord on the fifth resolving to a tonic chord
+const SCORE_RESOLUTION: u32 = 2;
+
+/// Custom error for strings that cannot be parsed into progressions.
+#[derive(Debug)]
+pub struct ParseProgressionError {
+    pub name: String,
+}
+
+impl Error for ParseProgressionError {}
+
+impl fmt::Display for ParseProgressionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Could not parse chord \"{}\" of the progression",
+            self.name
+        )
+    }
+}
+
+/// A chord of the progression and the scale played over it
+#[derive(Debug, Clone)]
+pub struct ProgressionStep {
+    pub chord: Chord,
+    pub chord_scale: ChordScale,
+}
+
+impl ProgressionStep {
+    /// Chord symbol such as "Dm7"
+    pub fn chord_name(&self) -> String {
+        format!("{}{}", self.chord.root, self.chord.chord_type.to_symbol())
+    }
+}
+
+/// A chord progression such as "Dm7 G7 Cmaj7" with its key center
+#[derive(Debug, Clone)]
+pub struct Progression {
+    /// Major or minor scale of the key center
+    pub key: Scale,
+    pub steps: Vec<ProgressionStep>,
+}
+
+impl Progression {
+    pub fn new(chords: &[Chord], tuning: Tuning) -> Self {
+        let key = Self::get_key(chords, tuning);
+        let key_set = key.pitch_class_set();
+        let steps = chords
+            .iter()
+            .map(|chord| {
+                let chord_scale = Self::get_chord_scale(chord, key_set, tuning);
+                ProgressionStep {
+                    chord: *chord,
+                    chord_scale,
+                }
+            })
+            .collect();
+        Self { key, steps }
+    }
+
+    /// Major or minor scale containing most of the chord tones and diatonic
+    /// chords, preferring keys whose tonic chord appears, ends or starts the
+    /// progression, or is reached from its dominant
+    fn get_key(chords: &[Chord], tuning: Tuning) -> Scale {
+        let mut key: Option<(u32, Scale)> = None;
+        for t in 0..12 {
+            // Spell the tonic like the chord built on it, if any
+            let tonic = chords
+                .iter()
+                .find(|c| c.root.pitch_class as Semitones == t)
+                .map(|c| Note::from(c.root))
+                .unwrap_or_else(|| Note::from_semitones(t));
+            for &scale_type in &[ScaleType::Major, ScaleType::Minor] {
+                let scale = Scale {
+                    scale_type,
+                    tuning,
+                    tonic,
+                    capo: Capo::default(),
+                };
+                let set = scale.pitch_class_set();
+                let mut score: u32 = chords
+                    .iter()
+                    .flat_map(|c| c.notes())
+                    .filter(|n| set.contains(n.pitch_class))
+                    .count() as u32;
+                score += SCORE_DIATONIC_CHORD
+                    * chords
+                        .iter()
+                        .filter(|c| {
+                            c.notes().all(|n| set.contains(n.pitch_class))
+                        })
+                        .count() as u32;
+                let third = match scale_type {
+                    ScaleType::Major => 4,
+                    _ => 3,
+                };
+                let is_tonic_chord = |c: &Chord| {
+                    c.root.pitch_class as Semitones == t
+                        && c.notes().any(|n| {
+                            n.pitch_class == c.root.pitch_class + third
+                        })
+                };
+                score += SCORE_TONIC_CHORD
+                    * chords.iter().filter(|c| is_tonic_chord(c)).count()
+                        as u32;
+                if chords.last().map_or(false, is_tonic_chord) {
+                    score += SCORE_LAST_CHORD;
+                }
+                if chords.first().map_or(false, is_tonic_chord) {
+                    score += SCORE_FIRST_CHORD;
+                }
+                let resolutions = chords
+                    .windows(2)
+                    .filter(|w| {
+                        w[0].root.pitch_class == w[1].root.pitch_class + 7
+                            && is_tonic_chord(&w[1])
+                    })
+                    .count() as u32;
+                score += SCORE_RESOLUTION * resolutions;
+                if key.map_or(true, |(best, _)| score > best) {
+                    key = Some((score, scale));
+                }
+            }
+        }
+        // There is always a candidate key
+        key.map(|(_, scale)| scale)
+            .unwrap_or_else(|| unreachable!())
+    }
+
+    /// Scale on the chord root with the fewest notes outside the key
+    /// besides the chord tones, the best chord-scale first on equality
+    fn get_chord_scale(
+        chord: &Chord,
+        key_set: PitchClassSet,
+        tuning: Tuning,
+    ) -> ChordScale {
+        let chord_scales = chord.chord_scales(tuning);
+        let foreign = |c: &ChordScale| {
+            c.tensions
+                .iter()
+                .chain(c.avoid_notes.iter())
+                .filter(|n| !key_set.contains(n.pitch_class))
+                .count()
+        };
+        let mut best: Option<&ChordScale> = None;
+        for chord_scale in chord_scales
+            .iter()
+            .filter(|c| c.scale.tonic.pitch_class == chord.root.pitch_class)
+        {
+            if best.map_or(true, |b| foreign(chord_scale) < foreign(b)) {
+                best = Some(chord_scale);
+            }
+        }
+        // Every chord fits at least one scale on its root
+        best.or_else(|| chord_scales.first())
+            .cloned()
+            .unwrap_or_else(|| unreachable!())
+    }
+
+    /// Notes the scale of step `i` has in common with the scale of the
+    /// previous step (the last step for the first one, as tunes loop)
+    pub fn shared_notes(&self, i: usize) -> PitchClassSet {
+        let n = self.steps.len();
+        if n < 2 || i >= n {
+            return PitchClassSet::default();
+        }
+        let previous = (i + n - 1) % n;
+        let set = |j: usize| self.steps[j].chord_scale.scale.pitch_class_set();
+        set(i).intersection(set(previous))
+    }
+}
+
+impl FromStr for Progression {
+    type Err = ParseProgressionError;
+
+    /// Chord symbols separated by spaces, commas or bars
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chords: Vec<Chord> = Vec::new();
+        for name in s
+            .split(|c: char| c.is_whitespace() || c == '|' || c == ',')
+            .filter(|n| !n.is_empty())
+        {
+            match Chord::from_str(name) {
+                Ok(chord) => chords.push(chord),
+                Err(_) => {
+                    return Err(ParseProgressionError {
+                        name: name.to_string(),
+                    })
+                },
+            }
+        }
+        if chords.is_empty() {
+            return Err(ParseProgressionError {
+                name: s.to_string(),
+            });
+        }
+        Ok(Self::new(&chords, Tuning::E))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ScaleType::*;
+
+    #[test]
+    fn test_ii_v_i() {
+        let progression = Progression::from_str("Dm7 G7 Cmaj7 A7").unwrap();
+        assert_eq!(progression.key.scale_type, Major);
+        assert_eq!(progression.key.tonic, Note::from_str("C").unwrap());
+        let scale_types: Vec<ScaleType> = progression
+            .steps
+            .iter()
+            .map(|s| s.chord_scale.scale.scale_type)
+            .collect();
+        assert_eq!(scale_types, vec![Dorian, Mixolydian, Major, Mixolydian]);
+        assert_eq!(progression.steps[0].chord_name(), "Dm7");
+    }
+
+    #[test]
+    fn test_minor_key() {
+        let progression = Progression::from_str("Am | F | C | G").unwrap();
+        assert_eq!(progression.key.scale_type, Minor);
+        assert_eq!(progression.key.tonic, Note::from_str("A").unwrap());
+    }
+
+    #[test]
+    fn test_shared_notes() {
+        let progression = Progression::from_str("Dm7 G7 Cmaj7 A7").unwrap();
+        assert_eq!(progression.shared_notes(1).len(), 7);
+        assert_eq!(progression.shared_notes(0).len(), 5);
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = Progression::from_str("Dm7 H7").unwrap_err();
+        assert_eq!(error.name, "H7");
+        assert!(Progression::from_str(" ").is_err());
+    }
+}
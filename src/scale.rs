@@ -2,15 +2,27 @@ use ukebox::pitch_class::PitchClass;
 use ukebox::Semitones;
 use crate::Degree;
 use crate::NUMBER_STRING;
+use std::error::Error;
 use std::fmt;
 use std::str::FromStr;
 use strum::IntoEnumIterator;
 use crate::note::{TraitNoteSemitones, Note};
 use crate::tuning::Tuning;
-use crate::interval::Interval;
+use crate::capo::Capo;
+use crate::exercise::Direction;
+use crate::fingering::{FingeringSettings, ScaleBox};
+use crate::midi::{
+    MidiFile, MidiNote, MidiSettings, KEY_MAX, VELOCITY_ACCENT,
+    VELOCITY_DEFAULT,
+};
+use crate::pitch::Pitch;
+use std::ops::{Add, Sub};
+use crate::pitch_class_set::PitchClassSet;
 
 /// Number of pitch classes.
 const PITCH_CLASS_COUNT: Semitones = 12;
+/// Frets of one octave, so that the boxes begin to repeat
+const OCTAVE_FRETS: usize = 12;
 
 // Custom error for strings that cannot be parsed into notes.
 #[derive(Debug)]
@@ -18,6 +30,8 @@ pub struct ParseScaleError {
     pub name: String,
 }
 
+impl Error for ParseScaleError {}
+
 impl fmt::Display for ParseScaleError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Could not parse scale \"{}\"", self.name)
@@ -25,6 +39,7 @@ impl fmt::Display for ParseScaleError {
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DegreeAllStrings {
     pub string_number: usize,
     pub degree_single_string: Vec<DegreeSingleString>,
@@ -32,14 +47,19 @@ pub struct DegreeAllStrings {
 
 /// Degree on the fret
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DegreeSingleString {
     pub position: usize, // 0..19
     pub sw_tonic: bool,
     pub note: Note,
+    /// Pitch with its octave, from E2 on the open low string
+    pub pitch: Pitch,
+    /// Between the nut and the capo, so it cannot be played
+    pub sw_behind_capo: bool,
 }
 
 /// The type of scale
-#[derive(Debug, Clone, Copy, PartialEq, EnumIter)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
 pub enum ScaleType {
     Major,
     Minor,
@@ -77,8 +97,24 @@ pub enum ScaleType {
     Messiaen5,
     Messiaen6,
     Messiaen7,
+    BebopDominant,
+    BebopMajor,
+    BebopMinor,
+    NeapolitanMajor,
+    NeapolitanMinor,
+    Enigmatic,
+    Prometheus,
+    Persian,
+    HungarianMajor,
+    Augmented,
+    Tritone,
+    Yo,
+    EgyptianPentatonic,
+    MaqamSaba,
+    MaqamAtharKurd,
 }
 
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ScaleTypeSelect {
     pub short: String,
     pub long: String,
@@ -113,8 +149,8 @@ impl ScaleType {
             HalfWholeDiminished => vec![1, 2, 4, 5, 7, 8, 10, 11],
             WholeHalfDiminished => vec![1, 3, 4, 6, 7, 9, 10, 12],
             MinorBlues => vec![1, 4, 6, 7, 8, 11],
-            MinorPentagonic => vec![1, 4, 6, 7, 8, 11],
-            MajorPentagonic => vec![1, 4, 6, 8, 11],
+            MinorPentagonic => vec![1, 4, 6, 8, 11],
+            MajorPentagonic => vec![1, 3, 5, 8, 10],
             HarmonicMinor => vec![1, 3, 4, 6, 8, 9, 12],
             HarmonicMajor => vec![1, 3, 5, 6, 8, 9, 12],
             Dorian4 => vec![1, 3, 4, 7, 8, 10, 11],
@@ -126,7 +162,7 @@ impl ScaleType {
             _8TonesSpanish => vec![1, 2, 4, 5, 6, 7, 9, 11],
             Bhairav => vec![1, 2, 5, 6, 8, 9, 12],
             HungarianMinor => vec![1, 3, 4, 7, 8, 9, 12],
-            Hirajoshi => vec![1, 3, 4, 8, 9, 12],
+            Hirajoshi => vec![1, 3, 4, 8, 9],
             InSen => vec![1, 2, 6, 8, 11],
             Iwato => vec![1, 2, 6, 7, 11],
             Kumoi => vec![1, 3, 4, 8, 10],
@@ -137,9 +173,40 @@ impl ScaleType {
             Messiaen5 => vec![1, 2, 6, 7, 8, 12],
             Messiaen6 => vec![1, 3, 5, 6, 7, 9, 11, 12],
             Messiaen7 => vec![1, 2, 3, 4, 6, 7, 8, 9, 10, 12],
+            BebopDominant => vec![1, 3, 5, 6, 8, 10, 11, 12],
+            BebopMajor => vec![1, 3, 5, 6, 8, 9, 10, 12],
+            BebopMinor => vec![1, 3, 4, 5, 6, 8, 10, 11],
+            NeapolitanMajor => vec![1, 2, 4, 6, 8, 10, 12],
+            NeapolitanMinor => vec![1, 2, 4, 6, 8, 9, 12],
+            Enigmatic => vec![1, 2, 5, 7, 9, 11, 12],
+            Prometheus => vec![1, 3, 5, 7, 10, 11],
+            Persian => vec![1, 2, 5, 6, 7, 9, 12],
+            HungarianMajor => vec![1, 4, 5, 7, 8, 10, 11],
+            Augmented => vec![1, 4, 5, 8, 9, 12],
+            Tritone => vec![1, 2, 5, 7, 8, 11],
+            Yo => vec![1, 3, 6, 8, 10],
+            EgyptianPentatonic => vec![1, 3, 6, 8, 11],
+            MaqamSaba => vec![1, 2, 4, 5, 8, 9, 11],
+            MaqamAtharKurd => vec![1, 2, 4, 7, 8, 9, 12],
         }
     }
 
+    /// Pitch classes of the scale built on C
+    pub fn pitch_class_set(self) -> PitchClassSet {
+        let semitones: Vec<Semitones> = self
+            .get_degree_from_tonic()
+            .iter()
+            .map(|d| d - 1)
+            .collect();
+        PitchClassSet::from_semitones(&semitones)
+    }
+
+    /// Number of distinct transpositions of the scale, 12 for most scales
+    /// and less for the modes of limited transposition (Messiaen)
+    pub fn transposition_count(self) -> usize {
+        self.pitch_class_set().transposition_count()
+    }
+
     /// Name short
     pub fn get_name_short(self) -> String {
         use ScaleType::*;
@@ -180,12 +247,27 @@ impl ScaleType {
             Messiaen5 => "messiaen_5",
             Messiaen6 => "messiaen_6",
             Messiaen7 => "messiaen_7",
+            BebopDominant => "bebop_dominant",
+            BebopMajor => "bebop_major",
+            BebopMinor => "bebop_minor",
+            NeapolitanMajor => "neapolitan_major",
+            NeapolitanMinor => "neapolitan_minor",
+            Enigmatic => "enigmatic",
+            Prometheus => "prometheus",
+            Persian => "persian",
+            HungarianMajor => "hungarian_major",
+            Augmented => "augmented",
+            Tritone => "tritone",
+            Yo => "yo",
+            EgyptianPentatonic => "egyptian_pentatonic",
+            MaqamSaba => "maqam_saba",
+            MaqamAtharKurd => "maqam_athar_kurd",
         };
         s.to_string()
     }
 
     /// Name
-    fn get_name_long(self) -> String {
+    pub fn get_name_long(self) -> String {
         use ScaleType::*;
         let s = match self {
             Major => "Major",
@@ -200,8 +282,8 @@ impl ScaleType {
             HalfWholeDiminished => "Half-whole Diminished",
             WholeHalfDiminished => "Whole-half Diminished",
             MinorBlues => "Minor Blues",
-            MinorPentagonic => "Minor Pentagonic",
-            MajorPentagonic => "Major Pentagonic",
+            MinorPentagonic => "Minor Pentatonic",
+            MajorPentagonic => "Major Pentatonic",
             HarmonicMinor => "Harmonic Minor",
             HarmonicMajor => "Harmonic Major",
             Dorian4 => "Dorian #4",
@@ -224,6 +306,21 @@ impl ScaleType {
             Messiaen5 => "Messiaen 5",
             Messiaen6 => "Messiaen 6",
             Messiaen7 => "Messiaen 7",
+            BebopDominant => "Bebop Dominant",
+            BebopMajor => "Bebop Major",
+            BebopMinor => "Bebop Minor",
+            NeapolitanMajor => "Neapolitan Major",
+            NeapolitanMinor => "Neapolitan Minor",
+            Enigmatic => "Enigmatic",
+            Prometheus => "Prometheus",
+            Persian => "Persian",
+            HungarianMajor => "Hungarian Major",
+            Augmented => "Augmented",
+            Tritone => "Tritone",
+            Yo => "Yo",
+            EgyptianPentatonic => "Egyptian Pentatonic",
+            MaqamSaba => "Maqam Saba",
+            MaqamAtharKurd => "Maqam Athar Kurd",
         };
         s.to_string()
     }
@@ -232,9 +329,9 @@ impl ScaleType {
     pub fn get_all_scale(self, tuning: Tuning) -> Vec<ScaleTypeSelect> {
         let mut vec_scale: Vec<ScaleTypeSelect> = Vec::new();
         for c in ScaleType::iter() {
-            let short = c.clone().get_name_short();
-            let long = c.clone().get_name_long();
-            let degree: Vec<Degree> = c.clone().get_degree_from_tonic();
+            let short = c.get_name_short();
+            let long = c.get_name_long();
+            let degree: Vec<Degree> = c.get_degree_from_tonic();
 
             let semitones = tuning.get_semitones();
 
@@ -331,67 +428,144 @@ impl ScaleType {
     }
 }
 
+impl fmt::Display for ScaleType {
+    /// Short name of the scale, e.g. "phrygian_dominant"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_name_short())
+    }
+}
+
+#[cfg(feature = "serde")]
+serde_string!(ScaleType);
+
 impl FromStr for ScaleType {
     type Err = ParseScaleError;
 
+    /// Scale from its short name, long name or any alias, ignoring case,
+    /// spaces, dashes and underscores
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use ScaleType::*;
-
         let name = s.to_string();
 
-        let scale: Self = match s {
-            "major" => Major,
-            "minor" => Minor,
-            "dorian" => Dorian,
-            "mixolydian" => Mixolydian,
-            "lydian" => Lydian,
-            "phrygian_min" => PhrygianMinor,
-            "phrygian" => Phrygian,
-            "locrian" => Locrian,
-            "whole_tone" => WholeTone,
-            "half_whole_diminished" => HalfWholeDiminished,
-            "whole_half_diminished" => WholeHalfDiminished,
-            "minor_blues" => MinorBlues,
-            "minor_pentagonic" => MinorPentagonic,
-            "maj_pentagonic" => MajorPentagonic,
-            "harmonic_minor" => HarmonicMinor,
-            "harmonic_major" => HarmonicMajor,
-            "dorian_4" => Dorian4,
-            "phrygian_dominant" => PhrygianDominant,
-            "melodic_minor" => MelodicMinor,
-            "lydian_augmented" => LydianAugumented,
-            "lydian_dominant" => LydianDominant,
-            "super_locrian" => SuperLocrian,
-            "8_tones_spanish" => _8TonesSpanish,
-            "bhairav" => Bhairav,
-            "hungarian_minor" => HungarianMinor,
-            "hirajoshi" => Hirajoshi,
-            "in_sen" => InSen,
-            "iwato" => Iwato,
-            "kumoi" => Kumoi,
-            "pelog_selisir" => PelogSelisir,
-            "pelog_tembung" => PelogTembung,
-            "messiaen_3" => Messiaen3,
-            "messiaen_4" => Messiaen4,
-            "messiaen_5" => Messiaen5,
-            "messiaen_6" => Messiaen6,
-            "messiaen_7" => Messiaen7,
-            _ => return Err(ParseScaleError { name }),
-        };
-
-        Ok(scale)
+        match ScaleType::iter().find(|scale| scale.is_named(s)) {
+            Some(scale) => Ok(scale),
+            None => Err(ParseScaleError { name }),
+        }
     }
 }
 
 /// Only one octave, the Vector begin on the tonic
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Scale {
     pub scale_type: ScaleType,
     pub tuning: Tuning,
     pub tonic: Note, // .pitch_class: PitchClass
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub capo: Capo,
 }
 
 impl Scale {
+    /// Parse the tonic, the scale type and the tuning of a scale, such as
+    /// `Scale::from_names("Bb", "dorian", "E")`
+    pub fn from_names(
+        tonic: &str,
+        scale_type: &str,
+        tuning: &str,
+    ) -> Result<Self, crate::error::Error> {
+        Ok(Self {
+            scale_type: ScaleType::from_str(scale_type)?,
+            tuning: Tuning::from_str(tuning)?,
+            tonic: Note::from_str(tonic)?,
+            capo: Capo::default(),
+        })
+    }
+
+    /// Same scale with the tonic `semitones` higher (negative for lower),
+    /// like the `--transpose` option of ukebox
+    pub fn transpose(self, semitones: i8) -> Self {
+        let n = semitones.unsigned_abs() % PITCH_CLASS_COUNT;
+        if semitones < 0 {
+            self - n
+        } else {
+            self + n
+        }
+    }
+
+    /// Same scale in the key of `tonic`
+    pub fn transpose_to(self, tonic: Note) -> Self {
+        Self { tonic, ..self }
+    }
+
+    /// One box for each note of the scale on the lowest string, in the
+    /// first octave from the nut (or the capo)
+    pub fn get_boxes(self, settings: FingeringSettings) -> Vec<ScaleBox> {
+        let open = self.capo.get_fret(1) as usize;
+        self.get_string_combination()
+            .first()
+            .map(|s| {
+                s.degree_single_string
+                    .iter()
+                    .filter(|v| v.position >= open)
+                    .filter(|v| v.position < open + OCTAVE_FRETS)
+                    .map(|v| ScaleBox::new(self, v.position, settings))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// MIDI file of the scale run up and/or down `settings.octaves` octaves,
+    /// from the first tonic at or above `settings.position` on the lowest
+    /// string
+    pub fn to_midi(self, settings: MidiSettings) -> MidiFile {
+        let open = self.capo.get_fret(1) as usize;
+        let low = self.get_open_strings()[0].midi() as usize
+            + settings.position.saturating_sub(open);
+        let tonic = self.tonic.pitch_class;
+        let set = self.pitch_class_set();
+        let first = (low..low + PITCH_CLASS_COUNT as usize)
+            .find(|&m| PitchClass::from(m as Semitones) == tonic)
+            .unwrap_or(low);
+        let last = first + PITCH_CLASS_COUNT as usize * settings.octaves;
+        let up: Vec<Semitones> = (first..=last.min(KEY_MAX as usize))
+            .map(|m| m as Semitones)
+            .filter(|&m| set.contains(PitchClass::from(m)))
+            .collect();
+        // The highest note is not repeated on the way down
+        let down = up.iter().rev().copied();
+        let keys: Vec<Semitones> = match settings.direction {
+            Direction::Ascending => up,
+            Direction::Descending => down.collect(),
+            Direction::AscendingDescending => {
+                up.iter().copied().chain(down.skip(1)).collect()
+            },
+        };
+        let duration = settings.note_value.ticks();
+        let mut midi = MidiFile::new(settings.tempo);
+        midi.notes = keys
+            .into_iter()
+            .enumerate()
+            .map(|(i, key)| MidiNote {
+                start: i as u32 * duration,
+                duration,
+                key,
+                velocity: if settings.accent_tonic
+                    && PitchClass::from(key) == tonic
+                {
+                    VELOCITY_ACCENT
+                } else {
+                    VELOCITY_DEFAULT
+                },
+            })
+            .collect();
+        midi
+    }
+
+    /// Pitches of the strings played at the capo (open strings without
+    /// capo), from the lowest string
+    pub fn get_open_strings(self) -> Vec<Pitch> {
+        get_open_strings(self.tuning, self.capo)
+    }
+
     /// Get semitones from tonic in this Struct
     /// Semitones + bool if tonic
     fn get_degree(self) -> Vec<(Semitones, bool)> {
@@ -449,95 +623,424 @@ impl Scale {
             vec_pitch_class.push(temp);
         }
 
-        let mut vec_semitones: Vec<(Semitones, bool)> = Vec::new();
-        for n in 0..255 {
-            let n_pitch_class = PitchClass::from(n as Semitones);
-            let sw_tonic = n_pitch_class == self.tonic.pitch_class;
-            for vpc in vec_pitch_class.clone() {
-                // Found
-                if vpc == n_pitch_class {
-                    vec_semitones.push((n, sw_tonic));
-                }
-            }
-        }
-        vec_semitones
+        get_degree(&vec_pitch_class, self.tonic.pitch_class)
+    }
+
+    /// Pitch classes of the scale built on the tonic
+    pub fn pitch_class_set(self) -> PitchClassSet {
+        self.scale_type.pitch_class_set() + self.tonic.pitch_class as Semitones
+    }
+
+    /// Notes of one octave of the scale, beginning on the tonic
+    pub fn notes(self) -> Vec<Note> {
+        let tonic = self.tonic.pitch_class as Semitones;
+        self.scale_type
+            .get_degree_from_tonic()
+            .iter()
+            .map(|d| Note::from_semitones(tonic + (d - 1)))
+            .collect()
     }
 
     /// Get note for a specific string (begin at 1 (not 0))
     pub fn get_string_combination(self) -> Vec<DegreeAllStrings> {
-        use PitchClass::*;
-        let interval = self.tuning.get_interval();
-        let roots = [
-            Note::from_str("E").unwrap() + interval,
-            Note::from_str("A").unwrap() + interval,
-            Note::from_str("D").unwrap() + interval + Interval::PlusOctave,
-            Note::from_str("G").unwrap() + interval + Interval::PlusOctave,
-            Note::from_str("B").unwrap() + interval + Interval::PlusOctave,
-            Note::from_str("E").unwrap() + interval + Interval::PlusPlusOctave,
-        ];
-        let mut vec_degree_all_strings: Vec<DegreeAllStrings> = Vec::new();
-        let mut string_number = 0;
+        get_string_combination(self.tuning, self.capo, &self.get_degree())
+    }
+}
+
+/// Pitches of the strings played at the capo, from the lowest string
+pub(crate) fn get_open_strings(tuning: Tuning, capo: Capo) -> Vec<Pitch> {
+    tuning
+        .get_open_strings()
+        .iter()
+        .enumerate()
+        .map(|(i, &pitch)| pitch + capo.get_fret(i + 1))
+        .collect()
+}
+
+/// Get note for a specific string (begin at 1 (not 0)) from the semitones of
+/// `get_degree`
+pub(crate) fn get_string_combination(
+    tuning: Tuning,
+    capo: Capo,
+    degree: &[(Semitones, bool)],
+) -> Vec<DegreeAllStrings> {
+    use PitchClass::*;
+    let roots = tuning.get_open_strings();
+    let mut vec_degree_all_strings: Vec<DegreeAllStrings> = Vec::new();
+    let mut string_number = 0;
+    loop {
+        string_number += 1;
+        if string_number > NUMBER_STRING as usize {
+            break;
+        }
+        let i = string_number - 1;
+        let p_class: PitchClass = roots[i].note.pitch_class;
+
+        let pos_begin: usize = match &p_class {
+            C => 0,
+            CSharp => 1,
+            D => 2,
+            DSharp => 3,
+            E => 4,
+            F => 5,
+            FSharp => 6,
+            G => 7,
+            GSharp => 8,
+            A => 9,
+            ASharp => 10,
+            B => 11,
+        };
+        let pos_end = &pos_begin + 19;
+        let capo_fret = capo.get_fret(string_number) as usize;
+
+        let mut vec_degree_single_string: Vec<DegreeSingleString> = Vec::new();
+
+        let mut count: usize = pos_begin;
+        let mut sw_begin = true;
         loop {
-            string_number += 1;
-            if string_number > NUMBER_STRING as usize {
+            if sw_begin {
+                sw_begin = false
+            } else {
+                count += 1;
+            }
+            if count > pos_end {
                 break;
             }
-            let i = string_number - 1;
-            let p_class: PitchClass = roots[i].pitch_class;
-
-            let pos_begin: usize = match &p_class {
-                C => 0,
-                CSharp => 1,
-                D => 2,
-                DSharp => 3,
-                E => 4,
-                F => 5,
-                FSharp => 6,
-                G => 7,
-                GSharp => 8,
-                A => 9,
-                ASharp => 10,
-                B => 11,
-            };
-            let pos_end = &pos_begin + 19;
+            for &(d, sw_bool) in degree {
+                // Position in scale
+                if d as usize == count {
+                    let position = count - pos_begin;
+                    let sw_tonic = sw_bool;
+                    let note = Note::from_semitones(count as Semitones);
+                    let pitch = roots[i] + position as Semitones;
+                    let sw_behind_capo = position < capo_fret;
+                    let degree_single_string = DegreeSingleString {
+                        position,
+                        sw_tonic,
+                        note,
+                        pitch,
+                        sw_behind_capo,
+                    };
+                    vec_degree_single_string.push(degree_single_string);
+                }
+            }
+        }
 
-            let mut vec_degree_single_string: Vec<DegreeSingleString> =
-                Vec::new();
+        let degree_all_strings = DegreeAllStrings {
+            string_number,
+            degree_single_string: vec_degree_single_string,
+        };
+        vec_degree_all_strings.push(degree_all_strings);
+    }
+    vec_degree_all_strings
+}
 
-            let mut count: usize = pos_begin;
-            let mut sw_begin = true;
-            loop {
-                if sw_begin {
-                    sw_begin = false
-                } else {
-                    count += 1;
-                }
-                if count > pos_end {
-                    break;
-                }
-                for (d, sw_bool) in self.get_degree() {
-                    // Position in scale
-                    if d as usize == count {
-                        let position = count - pos_begin;
-                        let sw_tonic = sw_bool;
-                        let note =
-                            Note::from_semitones(count as Semitones);
-                        let degree_single_string = DegreeSingleString {
-                            position,
-                            sw_tonic,
-                            note,
-                        };
-                        vec_degree_single_string.push(degree_single_string);
-                    }
-                }
+/// Semitones from C of the pitch classes on the whole fretboard, with
+/// `true` for the tonic
+pub(crate) fn get_degree(
+    pitch_classes: &[PitchClass],
+    tonic: PitchClass,
+) -> Vec<(Semitones, bool)> {
+    let mut vec_semitones: Vec<(Semitones, bool)> = Vec::new();
+    for n in 0..255 {
+        let n_pitch_class = PitchClass::from(n as Semitones);
+        let sw_tonic = n_pitch_class == tonic;
+        for &vpc in pitch_classes {
+            // Found
+            if vpc == n_pitch_class {
+                vec_semitones.push((n, sw_tonic));
+            }
+        }
+    }
+    vec_semitones
+}
+
+impl Add<Semitones> for Scale {
+    type Output = Self;
+
+    /// Transpose the scale `n` semitones higher
+    fn add(self, n: Semitones) -> Self {
+        Self {
+            tonic: self.tonic + n,
+            ..self
+        }
+    }
+}
+
+impl Sub<Semitones> for Scale {
+    type Output = Self;
+
+    /// Transpose the scale `n` semitones lower
+    fn sub(self, n: Semitones) -> Self {
+        Self {
+            tonic: self.tonic - n,
+            ..self
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::NoteValue;
+    use rstest::rstest;
+    use ScaleType::*;
+
+    #[rstest(
+        scale_type,
+        forte,
+        case(Major, "7-35"),
+        case(MelodicMinor, "7-34"),
+        case(HarmonicMinor, "7-32"),
+        case(MinorPentagonic, "5-35"),
+        case(MajorPentagonic, "5-35"),
+        case(MinorBlues, "6-Z47"),
+        case(WholeTone, "6-35"),
+        case(HalfWholeDiminished, "8-28"),
+        case(Messiaen3, "9-12"),
+        case(Messiaen7, "10-6")
+    )]
+    fn test_forte_number(scale_type: ScaleType, forte: &str) {
+        let forte_number = scale_type.pitch_class_set().forte_number();
+        assert_eq!(forte_number.to_string(), forte);
+    }
+
+    #[rstest(
+        scale_type,
+        count,
+        case(Major, 12),
+        case(WholeTone, 2),
+        case(WholeHalfDiminished, 3),
+        case(Messiaen3, 4),
+        case(Messiaen4, 6),
+        case(Messiaen5, 6),
+        case(Messiaen6, 6),
+        case(Messiaen7, 6)
+    )]
+    fn test_transposition_count(scale_type: ScaleType, count: usize) {
+        assert_eq!(scale_type.transposition_count(), count);
+    }
+
+    #[test]
+    fn test_scale_types_are_distinct() {
+        for a in ScaleType::iter() {
+            for b in ScaleType::iter().filter(|&b| b != a) {
+                assert_ne!(a.pitch_class_set(), b.pitch_class_set(), "{:?}", a);
+            }
+        }
+    }
+
+    #[rstest(
+        scale_type,
+        notes,
+        case(BebopDominant, "C D E F G A Bb B"),
+        case(BebopMajor, "C D E F G G# A B"),
+        case(NeapolitanMinor, "C C# D# F G G# B"),
+        case(Enigmatic, "C C# E F# G# A# B"),
+        case(Prometheus, "C D E F# A A#"),
+        case(Persian, "C C# E F F# G# B"),
+        case(HungarianMajor, "C D# E F# G A A#"),
+        case(Augmented, "C D# E G G# B"),
+        case(Tritone, "C C# E F# G A#"),
+        case(Yo, "C D F G A"),
+        case(EgyptianPentatonic, "C D F G A#"),
+        case(MaqamSaba, "C C# D# E G G# A#")
+    )]
+    fn test_extended_scales(scale_type: ScaleType, notes: &str) {
+        let expected: Vec<PitchClass> = notes
+            .split(' ')
+            .map(|s| Note::from_str(s).unwrap().pitch_class)
+            .collect();
+        assert_eq!(
+            scale_type.pitch_class_set(),
+            PitchClassSet::from_pitch_classes(&expected)
+        );
+    }
+
+    #[test]
+    fn test_scale_pitch_class_set() {
+        let scale = Scale {
+            scale_type: ScaleType::MinorPentagonic,
+            tuning: Tuning::E,
+            tonic: Note::from_str("A").unwrap(),
+            capo: Capo::default(),
+        };
+        let expected = ["A", "C", "D", "E", "G"]
+            .iter()
+            .map(|s| Note::from_str(s).unwrap().pitch_class)
+            .collect::<Vec<PitchClass>>();
+        assert_eq!(
+            scale.pitch_class_set(),
+            PitchClassSet::from_pitch_classes(&expected)
+        );
+    }
+
+    #[test]
+    fn test_string_combination_pitch() {
+        let scale = Scale {
+            scale_type: ScaleType::Minor,
+            tuning: Tuning::E,
+            tonic: Note::from_str("E").unwrap(),
+            capo: Capo::default(),
+        };
+        let strings = scale.get_string_combination();
+        let low = &strings[0].degree_single_string;
+        let high = &strings[5].degree_single_string;
+        assert_eq!(low[0].pitch.to_string(), "E2");
+        assert_eq!(strings[2].degree_single_string[0].pitch.to_string(), "D3");
+        assert_eq!(high[0].pitch.to_string(), "E4");
+        assert_eq!(high.last().unwrap().pitch.to_string(), "B5");
+        for string in strings {
+            for degree in string.degree_single_string {
+                assert_eq!(degree.pitch.note, degree.note);
             }
+        }
+    }
+
+    #[rstest(
+        tonic,
+        semitones,
+        result,
+        case("C", 2, "D"),
+        case("C", -1, "B"),
+        case("D", -1, "Db"),
+        case("A", 3, "C"),
+        case("Bb", 12, "Bb"),
+        case("Bb", -24, "Bb"),
+        case("E", -13, "Eb")
+    )]
+    fn test_transpose(tonic: &str, semitones: i8, result: &str) {
+        let scale = Scale::from_names(tonic, "dorian", "E").unwrap();
+        let transposed = scale.transpose(semitones);
+        assert_eq!(transposed.tonic.to_string(), result);
+        assert_eq!(transposed.scale_type, Dorian);
+    }
+
+    #[test]
+    fn test_transpose_to() {
+        let scale = Scale::from_names("A", "minor", "E").unwrap();
+        let scale = scale.transpose_to(Note::from_str("F#").unwrap());
+        assert_eq!(scale.tonic.to_string(), "F#");
+        assert_eq!(scale.scale_type, Minor);
+        assert_eq!((scale + 1).tonic.to_string(), "G");
+        assert_eq!((scale - 1).tonic.to_string(), "F");
+    }
 
-            let degree_all_strings = DegreeAllStrings {
-                string_number,
-                degree_single_string: vec_degree_single_string,
+    #[test]
+    fn test_capo() {
+        let mut scale = Scale::from_names("A", "minor", "E").unwrap();
+        scale.capo = Capo::new(3).unwrap();
+        for degree_all_strings in scale.get_string_combination() {
+            for v in degree_all_strings.degree_single_string {
+                assert_eq!(v.sw_behind_capo, v.position < 3);
+            }
+        }
+        let open: Vec<String> = scale
+            .get_open_strings()
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(open, vec!["G2", "C3", "F3", "A#3", "D4", "G4"]);
+    }
+
+    #[test]
+    fn test_partial_capo() {
+        // Esus cut capo on the A, D and G strings
+        let mut scale = Scale::from_names("E", "major", "E").unwrap();
+        scale.capo = Capo::partial(2, &[2, 3, 4]).unwrap();
+        for degree_all_strings in scale.get_string_combination() {
+            let capo_fret = match degree_all_strings.string_number {
+                2..=4 => 2,
+                _ => 0,
             };
-            vec_degree_all_strings.push(degree_all_strings);
+            for v in degree_all_strings.degree_single_string {
+                assert_eq!(v.sw_behind_capo, v.position < capo_fret);
+            }
         }
-        vec_degree_all_strings
+        let open: Vec<String> = scale
+            .get_open_strings()
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert_eq!(open, vec!["E2", "B2", "E3", "A3", "B3", "E4"]);
+    }
+
+    #[test]
+    fn test_to_midi() {
+        let scale = Scale::from_names("A", "minor pentatonic", "E").unwrap();
+        let midi = scale.to_midi(MidiSettings::default());
+        let keys: Vec<Semitones> = midi.notes.iter().map(|n| n.key).collect();
+        // Up two octaves from A2 and down without repeating A4
+        assert_eq!(keys.len(), 2 * 11 - 1);
+        assert_eq!(keys[..6], [45, 48, 50, 52, 55, 57]);
+        assert_eq!(keys[10], 69);
+        assert_eq!(keys.last(), Some(&45));
+        assert_eq!(midi.notes[0].velocity, VELOCITY_ACCENT);
+        assert_eq!(midi.notes[1].velocity, VELOCITY_DEFAULT);
+        assert_eq!(midi.notes[1].start, 240);
     }
-}
\ No newline at end of file
+
+    #[rstest(
+        position,
+        first,
+        case(0, 45),
+        case(5, 45),
+        case(6, 57),
+        case(17, 57)
+    )]
+    fn test_to_midi_position(position: usize, first: Semitones) {
+        let scale = Scale::from_names("A", "minor pentatonic", "E").unwrap();
+        let settings = MidiSettings {
+            position,
+            direction: Direction::Ascending,
+            ..MidiSettings::default()
+        };
+        assert_eq!(scale.to_midi(settings).notes[0].key, first);
+    }
+
+    #[test]
+    fn test_to_midi_descending() {
+        let mut scale = Scale::from_names("G", "major", "E").unwrap();
+        scale.capo = Capo::new(2).unwrap();
+        let settings = MidiSettings {
+            note_value: NoteValue::Quarter,
+            octaves: 1,
+            direction: Direction::Descending,
+            accent_tonic: false,
+            ..MidiSettings::default()
+        };
+        let midi = scale.to_midi(settings);
+        let keys: Vec<Semitones> = midi.notes.iter().map(|n| n.key).collect();
+        // From G2, the first G above F#2 at the capo
+        assert_eq!(keys, vec![55, 54, 52, 50, 48, 47, 45, 43]);
+        assert!(midi.notes.iter().all(|n| n.velocity == VELOCITY_DEFAULT));
+        assert_eq!(midi.notes[1].duration, 480);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_scale() {
+        let scale = Scale::from_names("Bb", "phrygian dominant", "E").unwrap();
+        let json = serde_json::to_string(&scale).unwrap();
+        assert_eq!(
+            json,
+            r#"{"scale_type":"phrygian_dominant","tuning":"E","tonic":"Bb","capo":{"frets":[0,0,0,0,0,0]}}"#
+        );
+        let back: Scale = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.scale_type, scale.scale_type);
+        assert_eq!(back.tonic, scale.tonic);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_degree() {
+        let scale = Scale::from_names("E", "minor", "E").unwrap();
+        let strings = scale.get_string_combination();
+        let json = serde_json::to_string(&strings[0]).unwrap();
+        assert!(json.starts_with(
+            r#"{"string_number":1,"degree_single_string":[{"position":0,"sw_tonic":true,"note":"E","pitch":"E2","sw_behind_capo":false}"#
+        ));
+        let back: DegreeAllStrings = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.degree_single_string, strings[0].degree_single_string);
+    }
+}
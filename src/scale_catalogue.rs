@@ -0,0 +1,274 @@
+use crate::scale::ScaleType;
+use std::fmt;
+use strum::IntoEnumIterator;
+
+/// Family or origin of a scale, used to group and filter the catalogue
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
+pub enum ScaleFamily {
+    Diatonic,
+    MelodicMinor,
+    HarmonicMinor,
+    HarmonicMajor,
+    DoubleHarmonic,
+    Pentatonic,
+    Blues,
+    Bebop,
+    Symmetric,
+    Messiaen,
+    Spanish,
+    Japanese,
+    Indonesian,
+    MiddleEastern,
+    EasternEuropean,
+    Other,
+}
+
+impl ScaleFamily {
+    /// Scales of the family, from the brightest to the darkest
+    pub fn scale_types(self) -> Vec<ScaleType> {
+        let scale_types: Vec<ScaleType> =
+            ScaleType::iter().filter(|s| s.family() == self).collect();
+        ScaleType::sort_by_brightness(&scale_types)
+    }
+}
+
+impl fmt::Display for ScaleFamily {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use ScaleFamily::*;
+        let s = match self {
+            Diatonic => "Diatonic",
+            MelodicMinor => "Melodic Minor",
+            HarmonicMinor => "Harmonic Minor",
+            HarmonicMajor => "Harmonic Major",
+            DoubleHarmonic => "Double Harmonic",
+            Pentatonic => "Pentatonic",
+            Blues => "Blues",
+            Bebop => "Bebop",
+            Symmetric => "Symmetric",
+            Messiaen => "Messiaen",
+            Spanish => "Spanish",
+            Japanese => "Japanese",
+            Indonesian => "Indonesian",
+            MiddleEastern => "Middle Eastern",
+            EasternEuropean => "Eastern European",
+            Other => "Other",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl ScaleType {
+    /// Other names the scale is known by. Scales that are the same in
+    /// 12-TET, like the maqam Hijaz and the phrygian dominant, share one
+    /// scale type and are told apart by their aliases only.
+    pub fn aliases(self) -> Vec<&'static str> {
+        use ScaleType::*;
+        match self {
+            Major => vec!["ionian", "maqam rast"],
+            Minor => vec!["aeolian", "natural minor"],
+            Phrygian => vec!["maqam kurd", "maqam bayati"],
+            PhrygianMinor => vec!["phrygian_min"],
+            HalfWholeDiminished => vec!["dominant diminished"],
+            WholeHalfDiminished => vec!["diminished"],
+            MinorBlues => vec!["blues"],
+            MinorPentagonic => vec!["minor pentatonic"],
+            MajorPentagonic => vec!["major pentatonic"],
+            HarmonicMinor => vec!["maqam nahawand"],
+            HarmonicMajor => vec!["maqam suznak"],
+            Dorian4 => {
+                vec!["ukrainian dorian", "romanian minor", "maqam nikriz"]
+            },
+            PhrygianDominant => {
+                vec!["spanish phrygian", "freygish", "maqam hijaz"]
+            },
+            MelodicMinor => vec!["jazz minor"],
+            LydianAugumented => vec!["lydian augmented"],
+            LydianDominant => vec!["overtone", "acoustic"],
+            SuperLocrian => vec!["altered", "altered dominant"],
+            Bhairav => vec!["double harmonic", "byzantine", "maqam hijaz kar"],
+            HungarianMinor => vec!["gypsy minor", "maqam nawa athar"],
+            BebopMinor => vec!["bebop dorian"],
+            EgyptianPentatonic => vec!["suspended pentatonic"],
+            _ => vec![],
+        }
+    }
+
+    /// Family or origin of the scale
+    pub fn family(self) -> ScaleFamily {
+        use ScaleFamily as F;
+        use ScaleType::*;
+        match self {
+            Major | Minor | Dorian | Mixolydian | Lydian | Phrygian
+            | Locrian => F::Diatonic,
+            MelodicMinor | LydianAugumented | LydianDominant | SuperLocrian => {
+                F::MelodicMinor
+            },
+            HarmonicMinor | Dorian4 | PhrygianDominant => F::HarmonicMinor,
+            HarmonicMajor => F::HarmonicMajor,
+            Bhairav | HungarianMinor => F::DoubleHarmonic,
+            MinorPentagonic | MajorPentagonic | EgyptianPentatonic => {
+                F::Pentatonic
+            },
+            MinorBlues => F::Blues,
+            BebopDominant | BebopMajor | BebopMinor => F::Bebop,
+            WholeTone | HalfWholeDiminished | WholeHalfDiminished
+            | Augmented | Tritone => F::Symmetric,
+            Messiaen3 | Messiaen4 | Messiaen5 | Messiaen6 | Messiaen7 => {
+                F::Messiaen
+            },
+            _8TonesSpanish => F::Spanish,
+            Hirajoshi | InSen | Iwato | Kumoi | Yo => F::Japanese,
+            PelogSelisir | PelogTembung => F::Indonesian,
+            Persian | MaqamSaba | MaqamAtharKurd => F::MiddleEastern,
+            HungarianMajor => F::EasternEuropean,
+            PhrygianMinor | NeapolitanMajor | NeapolitanMinor | Enigmatic
+            | Prometheus => F::Other,
+        }
+    }
+
+    /// Number of notes in one octave of the scale
+    pub fn note_count(self) -> usize {
+        self.pitch_class_set().len()
+    }
+
+    /// Sum of the semitones from the tonic to every degree. The more raised
+    /// degrees, the brighter the mode: Lydian is the brightest diatonic mode
+    /// and Locrian the darkest.
+    pub fn brightness(self) -> u16 {
+        let semitones = self.pitch_class_set().semitones();
+        semitones.iter().map(|&s| s as u16).sum()
+    }
+
+    /// Sort scales from the brightest to the darkest, scales with more notes
+    /// first when they cannot be compared.
+    pub fn sort_by_brightness(scale_types: &[ScaleType]) -> Vec<ScaleType> {
+        let mut sorted = scale_types.to_vec();
+        sorted.sort_by_key(|s| {
+            (
+                std::cmp::Reverse(s.note_count()),
+                std::cmp::Reverse(s.brightness()),
+            )
+        });
+        sorted
+    }
+
+    /// Every name the scale answers to: the short name, the long name and
+    /// the aliases
+    pub fn names(self) -> Vec<String> {
+        let mut names = vec![self.get_name_short(), self.get_name_long()];
+        names.extend(self.aliases().iter().map(|a| a.to_string()));
+        names
+    }
+
+    /// Return `true` if `name` is one of the names of the scale, ignoring
+    /// case, spaces, dashes and underscores
+    pub fn is_named(self, name: &str) -> bool {
+        let name = normalize_name(name);
+        self.names().iter().any(|n| normalize_name(n) == name)
+    }
+
+    /// All scales with a name containing `query`, ignoring case, spaces,
+    /// dashes and underscores
+    pub fn search(query: &str) -> Vec<ScaleType> {
+        let query = normalize_name(query);
+        ScaleType::iter()
+            .filter(|s| {
+                s.names().iter().any(|n| normalize_name(n).contains(&query))
+            })
+            .collect()
+    }
+}
+
+/// Lower case and keep only letters and digits, so that "Dorian #4",
+/// "dorian_4" and "DORIAN-4" compare equal
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::str::FromStr;
+    use ScaleType::*;
+
+    #[test]
+    fn test_names_round_trip() {
+        for scale_type in ScaleType::iter() {
+            for name in scale_type.names() {
+                assert_eq!(ScaleType::from_str(&name).unwrap(), scale_type);
+            }
+        }
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        for a in ScaleType::iter() {
+            for b in ScaleType::iter().filter(|&b| b != a) {
+                for name in a.names() {
+                    assert!(!b.is_named(&name), "{} is ambiguous", name);
+                }
+            }
+        }
+    }
+
+    #[rstest(
+        name,
+        scale_type,
+        case("phrygian_minor", PhrygianMinor),
+        case("Natural Minor", Minor),
+        case("aeolian", Minor),
+        case("IONIAN", Major),
+        case("Dorian #4", Dorian4),
+        case("8-tones spanish", _8TonesSpanish),
+        case("minor pentatonic", MinorPentagonic)
+    )]
+    fn test_tolerant_from_str(name: &str, scale_type: ScaleType) {
+        assert_eq!(ScaleType::from_str(name).unwrap(), scale_type);
+    }
+
+    #[test]
+    fn test_diatonic_brightness() {
+        assert_eq!(
+            ScaleFamily::Diatonic.scale_types(),
+            vec![Lydian, Major, Mixolydian, Dorian, Minor, Phrygian, Locrian]
+        );
+    }
+
+    #[rstest(
+        scale_type,
+        count,
+        case(Major, 7),
+        case(MinorPentagonic, 5),
+        case(MinorBlues, 6),
+        case(HalfWholeDiminished, 8),
+        case(Messiaen7, 10)
+    )]
+    fn test_note_count(scale_type: ScaleType, count: usize) {
+        assert_eq!(scale_type.note_count(), count);
+    }
+
+    #[rstest(
+        name,
+        scale_type,
+        case("double harmonic", Bhairav),
+        case("Byzantine", Bhairav),
+        case("ukrainian_dorian", Dorian4),
+        case("Romanian Minor", Dorian4),
+        case("maqam hijaz", PhrygianDominant),
+        case("bebop_dominant", BebopDominant),
+        case("maqam_saba", MaqamSaba)
+    )]
+    fn test_extended_names(name: &str, scale_type: ScaleType) {
+        assert_eq!(ScaleType::from_str(name).unwrap(), scale_type);
+    }
+
+    #[test]
+    fn test_search() {
+        let found = ScaleType::search("locrian");
+        assert_eq!(found, vec![Locrian, SuperLocrian]);
+    }
+}
@@ -0,0 +1,97 @@
+extern crate svg;
+use svg::Document;
+use crate::arpeggio::{Arpeggio, ArpeggioShape};
+use crate::fretboard::{Fretboard, Orientation};
+use crate::pitch_class_set::PitchClassSet;
+use crate::svg_draw::scale::draw_fretboard;
+use crate::svg_draw::settings::Theme;
+use crate::error::Result;
+use std::path;
+use std::str::FromStr;
+
+pub struct DrawArpeggio {
+    pub arpeggio: Arpeggio,
+    theme: Theme,
+    /// Shape to draw alone, the whole neck if `None`
+    pub shape: Option<ArpeggioShape>,
+}
+
+impl DrawArpeggio {
+    pub fn new(arpeggio: Arpeggio, theme: Theme) -> Self {
+        Self {
+            arpeggio,
+            theme,
+            shape: None,
+        }
+    }
+
+    /// Parse the chord (e.g. "Am7"), the tuning and the theme ("L" or
+    /// "light", "D" or "dark")
+    pub fn from_names(chord: &str, tuning: &str, theme: &str) -> Result<Self> {
+        let arpeggio = Arpeggio::from_names(chord, tuning)?;
+        Ok(Self::new(arpeggio, Theme::from_str(theme)?))
+    }
+
+    /// Write the horizontal svg to `path`
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        svg::save(path, &self.draw_base())?;
+        Ok(())
+    }
+
+    /// Write the vertical svg to `path`
+    pub fn save_vertical<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        svg::save(path, &self.draw_base_vertical())?;
+        Ok(())
+    }
+
+    /// Layout of the arpeggio, to draw it with another backend. The tone in
+    /// the bass of an inversion is circled with a ring.
+    pub fn fretboard(&self, orientation: Orientation) -> Fretboard {
+        let highlight = if self.arpeggio.inversion == 0 {
+            PitchClassSet::default()
+        } else {
+            let bass = self.arpeggio.bass().pitch_class;
+            PitchClassSet::from_pitch_classes(&[bass])
+        };
+        Fretboard::from_strings(
+            &self.arpeggio.get_string_combination(),
+            &self.arpeggio.get_open_strings(),
+            self.arpeggio.capo,
+            orientation,
+            highlight,
+        )
+        .with_chord_tones(&self.arpeggio, self.shape)
+    }
+
+    /// Draw the svg
+    pub fn draw_base(&self) -> Document {
+        self.draw(&self.fretboard(Orientation::Horizontal))
+    }
+
+    /// Draw the svg
+    pub fn draw_base_vertical(&self) -> Document {
+        self.draw(&self.fretboard(Orientation::Vertical))
+    }
+
+    /// Title of the svg, e.g. "Arpeggio of C E G, standard tuning"
+    pub fn title(&self) -> String {
+        let notes: Vec<String> =
+            self.arpeggio.notes.iter().map(|n| n.to_string()).collect();
+        format!(
+            "Arpeggio of {}, {}",
+            notes.join(" "),
+            self.arpeggio.tuning.get_name_long()
+        )
+    }
+
+    /// Draw the svg of a fretboard
+    fn draw(&self, fretboard: &Fretboard) -> Document {
+        draw_fretboard(
+            fretboard,
+            &self.theme,
+            "arpeggio-chart",
+            &self.title(),
+            "",
+        )
+    }
+}
@@ -0,0 +1,299 @@
+use crate::audio::Pluck;
+use crate::capo::Capo;
+use crate::error::Result;
+use crate::fingering::FingeringSettings;
+use crate::fretboard::{circle_id, Fretboard, Orientation};
+use crate::note::{Note, TraitNoteSemitones};
+use crate::pitch_class_set::PitchClassSet;
+use crate::scale::{
+    get_degree, get_open_strings, get_string_combination, Scale, ScaleType,
+};
+use crate::svg_draw::scale::{
+    draw_fretboard, THEME_BG_DARK, THEME_BG_LIGHT, THEME_ITEM_DARK,
+    THEME_ITEM_LIGHT,
+};
+use crate::svg_draw::settings::Theme;
+use crate::tuning::Tuning;
+use std::fs;
+use std::path;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use ukebox::{PitchClass, Semitones};
+
+/// Page of `DrawHtml`, `{{...}}` being replaced by the data
+const PAGE: &str = include_str!("page.html");
+
+/// A standalone html page to browse every scale in every key: the page
+/// draws the fretboard and plays the notes clicked by itself, without
+/// server
+pub struct DrawHtml {
+    pub tuning: Tuning,
+    pub capo: Capo,
+}
+
+impl DrawHtml {
+    pub fn new(tuning: Tuning) -> Self {
+        Self {
+            tuning,
+            capo: Capo::default(),
+        }
+    }
+
+    /// Write the page to `path`
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.draw())?;
+        Ok(())
+    }
+
+    /// Draw the page
+    pub fn draw(&self) -> String {
+        PAGE.replace("{{bg_light}}", THEME_BG_LIGHT)
+            .replace("{{bg_dark}}", THEME_BG_DARK)
+            .replace("{{item_light}}", THEME_ITEM_LIGHT)
+            .replace("{{item_dark}}", THEME_ITEM_DARK)
+            .replace("{{data}}", &self.data())
+    }
+
+    /// Every note of the neck, to draw the notes of any scale
+    fn chromatic(&self) -> Fretboard {
+        let pitch_classes: Vec<PitchClass> =
+            (0..12).map(PitchClass::from).collect();
+        let degree = get_degree(&pitch_classes, PitchClass::C);
+        Fretboard::from_strings(
+            &get_string_combination(self.tuning, self.capo, &degree),
+            &get_open_strings(self.tuning, self.capo),
+            self.capo,
+            Orientation::Horizontal,
+            PitchClassSet::default(),
+        )
+    }
+
+    /// Data of the page in json: the fretboards without notes in both
+    /// themes, where to draw each note, and the notes and the fingers of
+    /// each scale in each key
+    fn data(&self) -> String {
+        let chromatic = self.chromatic();
+        let mut empty = chromatic.clone();
+        empty.dots.clear();
+        let base = |theme: Theme| {
+            json_string(
+                &draw_fretboard(&empty, &theme, "scale-chart", "", "")
+                    .to_string(),
+            )
+        };
+        let offset = chromatic
+            .dots
+            .iter()
+            .find_map(|d| d.label.dx)
+            .unwrap_or_default();
+        let dots: Vec<String> = chromatic
+            .dots
+            .iter()
+            .map(|d| {
+                format!(
+                    "{{\"id\":{},\"string_number\":{},\"position\":{},\
+                     \"fret\":{},\"pc\":{},\"midi\":{},\"cx\":{},\
+                     \"cy\":{},\"r\":{},\"x\":{},\"y\":{},\
+                     \"behind_capo\":{}}}",
+                    json_string(&d.id()),
+                    d.string_number,
+                    d.position,
+                    json_string(&d.fret_name()),
+                    d.note.pitch_class as Semitones,
+                    d.pitch.midi(),
+                    d.cx,
+                    d.cy,
+                    d.r,
+                    d.label.x,
+                    d.label.y,
+                    d.sw_behind_capo
+                )
+            })
+            .collect();
+        let tonics: Vec<Note> = (0..12).map(Note::from_semitones).collect();
+        let scales: Vec<String> = ScaleType::iter()
+            .zip(ScaleType::Major.get_all_scale(self.tuning))
+            .map(|(scale_type, select)| {
+                let notes = [
+                    &select.note_scale_c,
+                    &select.note_scale_c_sharp,
+                    &select.note_scale_d,
+                    &select.note_scale_d_sharp,
+                    &select.note_scale_e,
+                    &select.note_scale_f,
+                    &select.note_scale_f_sharp,
+                    &select.note_scale_g,
+                    &select.note_scale_g_sharp,
+                    &select.note_scale_a,
+                    &select.note_scale_a_sharp,
+                    &select.note_scale_b,
+                ];
+                let notes: Vec<String> = notes
+                    .iter()
+                    .map(|names| {
+                        let pitch_classes: Vec<String> = names
+                            .iter()
+                            .filter_map(|n| Note::from_str(n).ok())
+                            .map(|n| (n.pitch_class as Semitones).to_string())
+                            .collect();
+                        format!("[{}]", pitch_classes.join(","))
+                    })
+                    .collect();
+                let fingers: Vec<String> = tonics
+                    .iter()
+                    .map(|&tonic| {
+                        let scale = Scale {
+                            scale_type,
+                            tuning: self.tuning,
+                            tonic,
+                            capo: self.capo,
+                        };
+                        self.fingers(scale)
+                    })
+                    .collect();
+                format!(
+                    "{{\"short\":{},\"long\":{},\"notes\":[{}],\
+                     \"fingers\":[{}]}}",
+                    json_string(&select.short),
+                    json_string(&select.long),
+                    notes.join(","),
+                    fingers.join(",")
+                )
+            })
+            .collect();
+        let tonics: Vec<String> =
+            tonics.iter().map(|n| json_string(&n.to_string())).collect();
+        let strings: Vec<String> = chromatic
+            .string_labels
+            .iter()
+            .map(|l| json_string(&l.text))
+            .collect();
+        format!(
+            "{{\"base\":{{\"light\":{},\"dark\":{}}},\"offset\":{},\
+             \"decay\":{},\"tuning\":{},\"strings\":[{}],\"names\":[{}],\
+             \"tonics\":[{}],\"dots\":[{}],\"scales\":[{}]}}",
+            base(Theme::Light),
+            base(Theme::Dark),
+            offset,
+            Pluck::default().decay,
+            json_string(&self.tuning.get_name_long()),
+            strings.join(","),
+            tonics.join(","),
+            tonics.join(","),
+            dots.join(","),
+            scales.join(",")
+        )
+    }
+
+    /// Fingers of the first box of a scale, by circle id
+    fn fingers(&self, scale: Scale) -> String {
+        let boxes = scale.get_boxes(FingeringSettings::default());
+        let fingers: Vec<String> = boxes
+            .first()
+            .map(|b| {
+                b.notes
+                    .iter()
+                    .map(|n| {
+                        format!(
+                            "{}:{}",
+                            json_string(&circle_id(
+                                n.string_number,
+                                n.position
+                            )),
+                            json_string(&n.finger.to_string())
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        format!("{{{}}}", fingers.join(","))
+    }
+}
+
+/// Json string of `s`, with `<` escaped too so that the string cannot end
+/// the script of the page, and the line separators that older browsers
+/// refuse in scripts
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            '<' => json.push_str("\\u003c"),
+            '\u{2028}' => json.push_str("\\u2028"),
+            '\u{2029}' => json.push_str("\\u2029"),
+            c if c < ' ' => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw() {
+        let page = DrawHtml::new(Tuning::E).draw();
+        assert!(!page.contains("{{"));
+        assert!(page.contains(r#""long":"Minor Pentatonic""#));
+        assert_eq!(
+            page.matches(r#""short":"#).count(),
+            ScaleType::iter().count()
+        );
+        // One circle for each note of the drawing
+        assert_eq!(page.matches(r#"{"id":"s"#).count(), 6 * 19);
+        // The svg cannot end the script
+        let script = &page[page.find("<script>").unwrap()..];
+        assert_eq!(script.matches("</script>").count(), 1);
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("C#"), r#""C#""#);
+        assert_eq!(json_string("a \"b\" \\ c"), r#""a \"b\" \\ c""#);
+        assert_eq!(json_string("é\n\u{1}"), r#""é\n\u0001""#);
+        assert_eq!(json_string("</script>"), r#""\u003c/script>""#);
+        // Escaped by `{:?}` for Rust, but not needed in json
+        assert_eq!(json_string("'\u{7f}"), "\"'\u{7f}\"");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_json_string_serde() {
+        let s = "a\"\\\n\t\u{1}</\u{2028}'é\u{7f}";
+        let json = json_string(s);
+        assert_eq!(serde_json::from_str::<String>(&json).unwrap(), s);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_data() {
+        let draw = DrawHtml::new(Tuning::E);
+        let data: serde_json::Value =
+            serde_json::from_str(&draw.data()).unwrap();
+        let pentatonic = data["scales"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|s| s["short"] == "minor_pentagonic")
+            .unwrap();
+        // E minor pentatonic, from the tonic, and its open box
+        assert_eq!(pentatonic["notes"][4], serde_json::json!([4, 7, 9, 11, 2]));
+        assert_eq!(pentatonic["fingers"][4]["s1-f3"], "3");
+        assert!(pentatonic["fingers"][4]["s1-f5"].is_null());
+        assert_eq!(data["dots"][0]["id"], "s1-f0");
+        assert_eq!(data["dots"][0]["midi"], 40);
+        assert_eq!(data["dots"][0]["fret"], "fret 0");
+        assert!(data["base"]["dark"]
+            .as_str()
+            .unwrap()
+            .contains(THEME_BG_DARK));
+    }
+}
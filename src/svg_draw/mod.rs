@@ -1,6 +1,11 @@
+mod arpeggio;
+mod html;
+mod progression;
 mod scale;
 mod settings;
 
+pub use self::arpeggio::DrawArpeggio;
+pub use self::html::DrawHtml;
+pub use self::progression::DrawProgression;
 pub use self::scale::DrawScale;
-pub use self::settings::Theme;
-
+pub use self::settings::{LabelMode, ParseThemeError, Theme};
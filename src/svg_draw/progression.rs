@@ -0,0 +1,116 @@
+use crate::progression::Progression;
+use crate::fretboard::{
+    HEIGHT, HEIGHT_BOTTOM, HEIGHT_TOP, WIDTH, WIDTH_LEFT, WIDTH_RIGHT,
+};
+use crate::svg_draw::scale::{
+    DrawScale, THEME_BG_DARK, THEME_BG_LIGHT, THEME_ITEM_DARK,
+    THEME_ITEM_LIGHT,
+};
+use crate::svg_draw::settings::Theme;
+use svg::node;
+use svg::node::element::{Group, Text};
+use svg::Document;
+
+pub const PANEL_TITLE_HEIGHT: u16 = 30;
+pub const PANEL_WIDTH: u16 = WIDTH_LEFT + WIDTH + WIDTH_RIGHT;
+pub const PANEL_HEIGHT: u16 =
+    PANEL_TITLE_HEIGHT + HEIGHT_TOP + HEIGHT + HEIGHT_BOTTOM;
+
+/// One horizontal fretboard per chord of the progression, the notes shared
+/// with the scale of the previous chord circled
+pub struct DrawProgression {
+    pub progression: Progression,
+    theme: Theme,
+}
+
+impl DrawProgression {
+    pub fn new(progression: Progression, theme: Theme) -> Self {
+        Self { progression, theme }
+    }
+
+    /// Draw the svg
+    pub fn draw_base(&self) -> Document {
+        let style = match self.theme {
+            Theme::Light => {
+                format!("font-family: Verdana; fill: {};", THEME_ITEM_LIGHT)
+            },
+            Theme::Dark => {
+                format!("font-family: Verdana; fill: {};", THEME_ITEM_DARK)
+            },
+        };
+        let mut group_title: Group = Group::new()
+            .set("class", "title")
+            .set("style", style)
+            .set("text-anchor", "start");
+        let mut vec_panel: Vec<Document> = Vec::new();
+        for (i, step) in self.progression.steps.iter().enumerate() {
+            let y = PANEL_HEIGHT as usize * i;
+            let scale = step.chord_scale.scale;
+            let title = format!(
+                "{} - {} {}",
+                step.chord_name(),
+                scale.tonic,
+                scale.scale_type.get_name_long()
+            );
+            group_title = group_title.add(
+                Text::new()
+                    .set("dominant-baseline", "middle")
+                    .set("x", WIDTH_LEFT)
+                    .set("y", y + PANEL_TITLE_HEIGHT as usize / 2)
+                    .add(node::Text::new(title)),
+            );
+            let mut draw = DrawScale::new(scale, self.theme.clone());
+            draw.highlight = self.progression.shared_notes(i);
+            // Ids unique in the document, e.g. `p1-s3-f5` in the second panel
+            draw.id_prefix = format!("p{}-", i);
+            vec_panel.push(
+                draw.draw_base()
+                    .set("x", 0)
+                    .set("y", y + PANEL_TITLE_HEIGHT as usize),
+            );
+        }
+        // End
+        let height = PANEL_HEIGHT as usize * self.progression.steps.len();
+        let style = match self.theme {
+            Theme::Light => format!("background: {};", THEME_BG_LIGHT),
+            Theme::Dark => format!("background: {};", THEME_BG_DARK),
+        };
+        let mut document = Document::new()
+            .set("class", "progression-chart")
+            .set("xmlns", "http://www.w3.org/2000/svg")
+            .set("width", PANEL_WIDTH)
+            .set("height", height)
+            .set("font-size", 16.0)
+            .set("style", style)
+            .set("viewBox", (0, 0, PANEL_WIDTH as usize, height))
+            .add(group_title);
+        for panel in vec_panel {
+            document = document.add(panel);
+        }
+        document
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_unique_ids() {
+        let progression = Progression::from_str("Dm7 G7 Cmaj7 A7").unwrap();
+        let svg = DrawProgression::new(progression, Theme::Light)
+            .draw_base()
+            .to_string();
+        let ids: Vec<&str> = svg
+            .split(" id=\"")
+            .skip(1)
+            .filter_map(|s| s.split('"').next())
+            .collect();
+        let unique: BTreeSet<&str> = ids.iter().copied().collect();
+        assert_eq!(ids.len(), unique.len());
+        assert!(unique.contains("p0-s1-f0"));
+        assert!(unique.contains("p3-s1-f0"));
+    }
+}
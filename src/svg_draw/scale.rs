@@ -1,24 +1,19 @@
 extern crate svg;
-use std::str::FromStr;
 use svg::node;
-use svg::node::element::path::{Data, Number};
-use svg::node::element::{Circle, Group, Path, Text};
+use svg::node::element::path::Data;
+use svg::node::element::{
+    Animate, Circle, Description, Group, Path, Rectangle, Text, Title,
+};
 use svg::Document;
-use crate::scale::{Scale, DegreeAllStrings};
-use crate::NUMBER_STRING;
-use ukebox::PitchClass;
-use crate::svg_draw::settings::Theme;
-use crate::note::Note;
-use crate::interval::Interval;
+use crate::fretboard::{Fretboard, Label, Line, Number, Orientation};
+use crate::scale::Scale;
+use crate::svg_draw::settings::{LabelMode, Theme};
+use crate::pitch_class_set::PitchClassSet;
+use crate::timeline::Timeline;
+use crate::error::Result;
+use std::path;
+use std::str::FromStr;
 
-pub const WIDTH_LEFT: u16 = 50; // Place for root note (reverse A E C G)
-pub const WIDTH_RIGHT: u16 = 25; // Blank
-pub const WIDTH: u16 = 900 - WIDTH_LEFT - WIDTH_RIGHT;
-pub const HEIGHT_TOP: u16 = 25;
-pub const HEIGHT_BOTTOM: u16 = 45;
-pub const HEIGHT: u16 = 250 - HEIGHT_TOP - HEIGHT_BOTTOM;
-pub const NUMBER_POSITION: u16 = 19;
-pub const OFFSET_TEXT_BOTTOM: Number = -1.0;
 pub const THEME_BG_LIGHT: &str = "white";
 pub const THEME_BG_DARK: &str = "#282c34";
 pub const THEME_ITEM_LIGHT: &str = "black";
@@ -26,741 +21,441 @@ pub const THEME_ITEM_DARK: &str = "white";
 pub const THEME_TEXT_COLOR_LIGHT: &str= "white";
 pub const THEME_TEXT_COLOR_DARK: &str= "black";
 
-pub const VER_HEIGHT_TOP: u16 = 50;
-pub const VER_HEIGHT_BOTTOM: u16 = 25;
-pub const VER_HEIGHT: u16 = 1100 - VER_HEIGHT_TOP - VER_HEIGHT_BOTTOM;
-pub const VER_WIDTH_LEFT: u16 = 50;
-pub const VER_WIDTH_RIGHT: u16 = 50;
-pub const VER_WIDTH: u16 = 300 - VER_WIDTH_LEFT - VER_WIDTH_RIGHT;
-pub const VER_OFFSET_WIDTH: f32 = 200.0 / 5.0;
-pub const VER_FLUTTER_OFFSET_NOTE_NAME: Number = 4.75;
-
-pub const SWIFT_WIDTH_OFFSET_NOTE_NAME: Number = -3.5;
-pub const SWIFT_HEIGHT_OFFSET_NOTE_NAME: Number = 3.0;
+pub const HIGHLIGHT_COLOR: &str = "#1e90ff";
+pub const HIGHLIGHT_STROKE_WIDTH: Number = 3.0;
 
-pub const SWIFT_VERTICAL_WIDTH_OFFSET: Number = -3.5;
+pub const CAPO_COLOR: &str = "#808080";
+pub const CAPO_STROKE_WIDTH: Number = 8.0;
+pub const BEHIND_CAPO_COLOR: &str = "#c0c0c0";
+pub const BEHIND_CAPO_OPACITY: Number = 0.4;
 
 pub struct DrawScale {
     pub scale: Scale,
     theme: Theme,
+    /// Notes circled with a ring, e.g. the notes shared with another scale
+    pub highlight: PitchClassSet,
+    pub label_mode: LabelMode,
+    /// Prefix of the circle ids, to keep them unique when several drawings
+    /// are nested in one document
+    pub id_prefix: String,
 }
 
 impl DrawScale {
     pub fn new(scale: Scale, theme: Theme) -> Self {
-        Self { scale, theme }
+        Self {
+            scale,
+            theme,
+            highlight: PitchClassSet::default(),
+            label_mode: LabelMode::default(),
+            id_prefix: String::new(),
+        }
+    }
+
+    /// Parse the scale and the theme ("L" or "light", "D" or "dark")
+    pub fn from_names(
+        tonic: &str,
+        scale_type: &str,
+        tuning: &str,
+        theme: &str,
+    ) -> Result<Self> {
+        let scale = Scale::from_names(tonic, scale_type, tuning)?;
+        Ok(Self::new(scale, Theme::from_str(theme)?))
+    }
+
+    /// Write the horizontal svg to `path`
+    pub fn save<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        svg::save(path, &self.draw_base())?;
+        Ok(())
+    }
+
+    /// Write the vertical svg to `path`
+    pub fn save_vertical<P: AsRef<path::Path>>(&self, path: P) -> Result<()> {
+        svg::save(path, &self.draw_base_vertical())?;
+        Ok(())
+    }
+
+    /// Write the horizontal svg animated along `timeline` to `path`
+    pub fn save_animated<P: AsRef<path::Path>>(
+        &self,
+        path: P,
+        timeline: &Timeline,
+    ) -> Result<()> {
+        svg::save(path, &self.draw_animated(timeline))?;
+        Ok(())
+    }
+
+    /// Layout of the scale, to draw it with another backend
+    pub fn fretboard(&self, orientation: Orientation) -> Fretboard {
+        let fretboard = Fretboard::new(self.scale, orientation, self.highlight);
+        match &self.label_mode {
+            LabelMode::NoteName => fretboard,
+            LabelMode::Finger(scale_box) => fretboard.with_fingering(scale_box),
+            LabelMode::Order(exercise) => fretboard.with_order(exercise),
+        }
     }
 
     /// Draw the svg
     pub fn draw_base(&self) -> Document {
-        // Grid
-        let style = match self.theme {
-            Theme::Light => format!(
-                "background: {}; stroke: {}; stroke-linecap: round;",
-                THEME_BG_LIGHT, THEME_ITEM_LIGHT
-            ),
-            Theme::Dark => format!(
-                "background: {}; stroke: {}; stroke-linecap: round;",
-                THEME_BG_DARK, THEME_ITEM_DARK
-            ),
-        };
-        let mut group_grid: Group =
-            Group::new().set("style", style).set("class", "grid");
-        let mut d: Data = Data::new();
-        let width_pos: Number = WIDTH_LEFT as f32;
-        let height_pos: Number = HEIGHT_TOP as f32;
-        // All singles strings
-        for n in 0..NUMBER_STRING {
-            let h = (HEIGHT as Number / NUMBER_STRING as Number)
-                * ((n + 1) as Number);
-            d = d.move_to((width_pos, height_pos + h as Number)); // m
-            d = d.horizontal_line_by((WIDTH, 0.0));
-        }
-        group_grid =
-            group_grid.add(Path::new().set("class", "grid").set("d", d));
-        d = Data::new();
-        // Grid strings
-        for n in 0..NUMBER_POSITION {
-            let w: Number = (WIDTH as Number / NUMBER_POSITION as Number)
-                * ((n + 1) as Number);
-            let h = HEIGHT / NUMBER_STRING as u16;
-            d = d.move_to((width_pos + w as Number, height_pos + h as Number)); // m
-            d = d.vertical_line_by((HEIGHT - h, 0.0)); // v
-        }
-        group_grid =
-            group_grid.add(Path::new().set("class", "grid").set("d", d));
-        // Bottom point on fret
-        let width_pos: Number = WIDTH_LEFT as f32;
-        let height_pos: Number = HEIGHT_TOP as f32 + HEIGHT as f32;
-        let mut group_text: Group = Group::new();
-        let style = match self.theme {
-            Theme::Light => {
-                format!("font-family: Verdana; fill: {}", THEME_ITEM_LIGHT)
-            },
-            Theme::Dark => {
-                format!("font-family: Verdana; fill: {}", THEME_ITEM_DARK)
-            },
-        };
-        group_text = group_text
-            .set("class", "text")
-            .set("style", style.clone())
-            .set("text-anchor", "middle");
-        let vec_n = vec![1, 3, 5, 7, 10, 12, 15];
-        for n in vec_n {
-            let mut w: Number = WIDTH as f32 / NUMBER_POSITION as f32;
-            w *= n as f32 + 0.5;
-            let h = HEIGHT_BOTTOM / 2;
-            if n.to_string().len() > 1 {
-                group_text = group_text.add(
-                    Text::new()
-                        .set("class", "header")
-                        .set("dominant-baseline", "middle")
-                        .set("style", style.clone())
-                        .set("x", width_pos + w as Number + SWIFT_WIDTH_OFFSET_NOTE_NAME)
-                        .set("y", height_pos + h as Number + SWIFT_HEIGHT_OFFSET_NOTE_NAME)
-                        .set("dx", OFFSET_TEXT_BOTTOM)
-                        .add(node::Text::new(n.to_string())),
-                );
-            } else {
-                group_text = group_text.add(
-                    Text::new()
-                        .set("class", "header")
-                        .set("dominant-baseline", "middle")
-                        .set("style", style.clone())
-                        .set("x", width_pos + w as Number + SWIFT_WIDTH_OFFSET_NOTE_NAME)
-                        .set("y", height_pos + h as Number + SWIFT_HEIGHT_OFFSET_NOTE_NAME)
-                        .add(node::Text::new(n.to_string())),
-                );
-            }
-        }
-        // Circle note
-        let mut group_circle_tonic: Group = Group::new();
-        group_circle_tonic = group_circle_tonic
-            .set("class", "circle_tonic")
-            .set("fill", "coral");
-        let mut group_circle_in_scale: Group = Group::new();
-        let style = match self.theme {
-            Theme::Light => THEME_ITEM_LIGHT,
-            Theme::Dark => THEME_ITEM_DARK,
-        };
-        group_circle_in_scale = group_circle_in_scale
-            .set("class", "circle_in_scale")
-            .set("fill", style);
-        let mut w: Number = WIDTH as f32 / NUMBER_POSITION as f32;
-        let note_r: Number = w / 5.0;
-        // Vector
-        let vec_all_strings: Vec<DegreeAllStrings> =
-            self.scale.get_string_combination();
-        // Tonic
-        for i in 0..NUMBER_STRING {
-            let single_string = vec_all_strings[i as usize].clone();
-            let height_pos: Number = HEIGHT_TOP as f32
-                + ((HEIGHT as f32 / NUMBER_STRING as f32)
-                * (self.guitar_string_convert(i) as f32 + 1.0));
-            for j in 0..NUMBER_POSITION {
-                for v in &single_string.degree_single_string {
-                    if v.position == j as usize && v.sw_tonic {
-                        let width_pos: Number = WIDTH_LEFT as f32;
-                        w = WIDTH as f32 / NUMBER_POSITION as f32;
-                        w *= j as f32 + 0.5;
-                        group_circle_tonic = group_circle_tonic.add(
-                            Circle::new()
-                                .set("cx", width_pos + w as Number)
-                                .set("cy", height_pos)
-                                .set("r", note_r),
-                        );
-                        break;
-                    }
-                }
-            }
-        }
-        // In Scale
-        for i in 0..NUMBER_STRING {
-            let single_string = vec_all_strings[i as usize].clone();
-            let height_pos: Number = HEIGHT_TOP as f32
-                + ((HEIGHT as f32 / NUMBER_STRING as f32)
-                * (self.guitar_string_convert(i) as f32 + 1.0));
-            for j in 0..NUMBER_POSITION {
-                for v in &single_string.degree_single_string {
-                    if v.position == j as usize && !v.sw_tonic {
-                        let width_pos: Number = WIDTH_LEFT as f32;
-                        w = WIDTH as f32 / NUMBER_POSITION as f32;
-                        w *= j as f32 + 0.5;
-                        group_circle_in_scale = group_circle_in_scale.add(
-                            Circle::new()
-                                .set("cx", width_pos + w as Number)
-                                .set("cy", height_pos)
-                                .set("r", note_r),
-                        );
-                        break;
-                    }
-                }
-            }
-        }
-        // Text Tonic + In Scale
-        let mut group_text_circle: Group = Group::new();
-        /*let style = match self.theme {
-            Theme::Light => THEME_BG_LIGHT,
-            Theme::Dark => THEME_BG_DARK,
-        };*/
-        let style_color = match self.theme {
-            Theme::Light => THEME_TEXT_COLOR_LIGHT,
-            Theme::Dark => THEME_TEXT_COLOR_DARK,
-        };
-        group_text_circle = group_text_circle
-            .set("class", "text_circle")
-            .set("style", "font-family: Verdana;")
-            .set("font-size", "9")
-            .set("fill", style_color)
-            .set("text-anchor", "middle");
-        for i in 0..NUMBER_STRING {
-            let single_string = vec_all_strings[i as usize].clone();
-            let height_pos: Number = HEIGHT_TOP as f32
-                + ((HEIGHT as f32 / NUMBER_STRING as f32)
-                * (self.guitar_string_convert(i) as f32 + 1.0));
-            for j in 0..NUMBER_POSITION {
-                for v in &single_string.degree_single_string {
-                    if v.position == j as usize {
-                        let note = match v.note.pitch_class {
-                            PitchClass::C => "C",
-                            PitchClass::CSharp => "C#",
-                            PitchClass::D => "D",
-                            PitchClass::DSharp => "D#",
-                            PitchClass::E => "E",
-                            PitchClass::F => "F",
-                            PitchClass::FSharp => "F#",
-                            PitchClass::G => "G",
-                            PitchClass::GSharp => "G#",
-                            PitchClass::A => "A",
-                            PitchClass::ASharp => "A#",
-                            PitchClass::B => "B",
-                        };
-                        let width_pos: Number = WIDTH_LEFT as f32;
-                        w = WIDTH as f32 / NUMBER_POSITION as f32;
-                        w *= j as f32 + 0.5;
-                        let style = match self.theme {
-                            Theme::Light => {
-                                format!("font-family: Verdana; fill: {}", THEME_TEXT_COLOR_LIGHT)
-                            },
-                            Theme::Dark => {
-                                format!("font-family: Verdana; fill: {}", THEME_TEXT_COLOR_DARK)
-                            },
-                        };
-                        if note.len() > 1 {
-                            group_text_circle = group_text_circle.add(
-                                Text::new()
-                                    .set("dominant-baseline", "middle")
-                                    .set("x", width_pos + w as Number + SWIFT_WIDTH_OFFSET_NOTE_NAME)
-                                    .set("y", height_pos as Number + SWIFT_HEIGHT_OFFSET_NOTE_NAME)
-                                    .set("dx", OFFSET_TEXT_BOTTOM)
-                                    .set("style", style)
-                                    .add(node::Text::new(note.to_string())),
-                            );
-                        } else {
-                            group_text_circle = group_text_circle.add(
-                                Text::new()
-                                    .set("dominant-baseline", "middle")
-                                    .set("x", width_pos + w as Number + SWIFT_WIDTH_OFFSET_NOTE_NAME)
-                                    .set("y", height_pos as Number + SWIFT_HEIGHT_OFFSET_NOTE_NAME)
-                                    .set("style", style)
-                                    .add(node::Text::new(note.to_string())),
-                            );
-                        }
-                    }
-                }
-            }
-        }
-        let mut group_text_left: Group = Group::new();
-        let style = match self.theme {
-            Theme::Light => {
-                format!("font-family: Verdana; fill: {};", THEME_ITEM_LIGHT)
-            },
-            Theme::Dark => {
-                format!("font-family: Verdana; fill: {};", THEME_ITEM_DARK)
-            },
-        };
-        group_text_left = group_text_left
-            .set("class", "text_circle")
-            .set("style", style.clone())
-            .set("text-anchor", "middle");
-        let interval = self.scale.tuning.get_interval();
-        let roots = [
-            Note::from_str("E").unwrap() + interval,
-            Note::from_str("A").unwrap() + interval,
-            Note::from_str("D").unwrap() + interval + Interval::PlusOctave,
-            Note::from_str("G").unwrap() + interval + Interval::PlusOctave,
-            Note::from_str("B").unwrap() + interval + Interval::PlusOctave,
-            Note::from_str("E").unwrap() + interval + Interval::PlusPlusOctave,
-        ];
-        for i in 0..NUMBER_STRING {
-            let height_pos: Number = HEIGHT_TOP as f32
-                + ((HEIGHT as f32 / NUMBER_STRING as f32)
-                * (self.guitar_string_convert(i) as f32 + 1.0));
-            let note = match roots[i as usize].pitch_class {
-                PitchClass::C => "C",
-                PitchClass::CSharp => "C#",
-                PitchClass::D => "D",
-                PitchClass::DSharp => "D#",
-                PitchClass::E => "E",
-                PitchClass::F => "F",
-                PitchClass::FSharp => "F#",
-                PitchClass::G => "G",
-                PitchClass::GSharp => "G#",
-                PitchClass::A => "A",
-                PitchClass::ASharp => "A#",
-                PitchClass::B => "B",
-            };
-            let width_pos: Number = WIDTH_LEFT as f32 / 2.0;
-            if note.len() > 1 {
-                group_text_left = group_text_left.add(
-                    Text::new()
-                        .set("dominant-baseline", "middle")
-                        .set("x", width_pos as Number)
-                        .set("y", height_pos as Number)
-                        .set("dx", OFFSET_TEXT_BOTTOM)
-                        .set("style", style.clone())
-                        .add(node::Text::new(note.to_string())),
-                );
-            } else {
-                group_text_left = group_text_left.add(
-                    Text::new()
-                        .set("dominant-baseline", "middle")
-                        .set("x", width_pos as Number)
-                        .set("y", height_pos as Number)
-                        .set("style", style.clone())
-                        .add(node::Text::new(note.to_string())),
-                );
-            }
-        }
-        // End
-        let style = match self.theme {
-            Theme::Light => format!("background: {};", THEME_BG_LIGHT),
-            Theme::Dark => format!("background: {};", THEME_BG_DARK),
-        };
-        Document::new()
-            .set("class", "chord-chart")
-            .set("xmlns", "http://www.w3.org/2000/svg")
-            .set("width", WIDTH_LEFT + WIDTH + WIDTH_RIGHT)
-            .set("height", HEIGHT_TOP + HEIGHT + HEIGHT_BOTTOM)
-            .set("preserveAspectRatio", "xMidYMid mee    t")
-            .set("font-size", 16.0)
-            .set("style", style)
-            .set(
-                "viewBox",
-                (
-                    0,
-                    0,
-                    WIDTH as i32 + WIDTH_LEFT as i32 + WIDTH_RIGHT as i32,
-                    HEIGHT as i32 + HEIGHT_TOP as i32 + HEIGHT_BOTTOM as i32,
-                ),
-            )
-            .add(group_grid)
-            .add(group_text)
-            .add(group_circle_tonic)
-            .add(group_circle_in_scale)
-            .add(group_text_circle)
-            .add(group_text_left)
+        self.draw(&self.fretboard(Orientation::Horizontal))
     }
 
     /// Draw the svg
     pub fn draw_base_vertical(&self) -> Document {
-        // Grid
-        let style = match self.theme {
-            Theme::Light => format!(
-                "background: {}; stroke: {}; stroke-linecap: round;",
-                THEME_BG_LIGHT, THEME_ITEM_LIGHT
-            ),
-            Theme::Dark => format!(
-                "background: {}; stroke: {}; stroke-linecap: round;",
-                THEME_BG_DARK, THEME_ITEM_DARK
-            ),
-        };
-        let mut group_grid: Group = Group::new()
-            .set("style", style)
-            .set("class", "grid");
-        let mut d: Data = Data::new();
-        let width_pos: Number = VER_WIDTH_LEFT as f32;
-        let height_pos: Number = VER_HEIGHT_TOP as f32;
-        // let width_pos: Number = WIDTH_LEFT as f32;
-        // let height_pos: Number = HEIGHT_TOP as f32;
-        // All singles strings
-        for n in 0..NUMBER_STRING {
-            let nn: Number = (n) as Number;
-            let w = VER_OFFSET_WIDTH as Number * nn;
-            d = d.move_to((width_pos + w, height_pos as Number)); // m
-            d = d.vertical_line_by((VER_HEIGHT, 0.0));
-            //let h = (HEIGHT as Number / NUMBER_STRING as Number)
-            //    * ((n + 1) as Number);
-            //d = d.move_to((width_pos, height_pos + h as Number)); // m
-            //d = d.horizontal_line_by((WIDTH, 0.0));
-        }
-        group_grid =
-            group_grid.add(Path::new().set("class", "grid").set("d", d));
-        d = Data::new();
-        // Grid strings
-        for n in 0..NUMBER_POSITION {
-            let h: Number = (VER_HEIGHT as Number / NUMBER_POSITION as Number)
-                * ((n + 1) as Number);
-            let w = VER_WIDTH;
-            d = d.move_to((width_pos, height_pos + h as Number)); // m
-            d = d.horizontal_line_by((w as f32, 0.0)); // v
+        self.draw(&self.fretboard(Orientation::Vertical))
+    }
 
-            //let w: Number = (WIDTH as Number / NUMBER_POSITION as Number)
-            //    * ((n + 1) as Number);
-            //let h = HEIGHT / NUMBER_STRING as u16;
-            //d = d.move_to((width_pos + w as Number, height_pos + h as Number)); // m
-            //d = d.vertical_line_by((HEIGHT - h, 0.0)); // v
-        }
-        group_grid =
-            group_grid.add(Path::new().set("class", "grid").set("d", d));
+    /// Draw the horizontal svg, each dot lighting up in turn as its note
+    /// of `timeline` is played, in a loop
+    pub fn draw_animated(&self, timeline: &Timeline) -> Document {
+        let fretboard = self.fretboard(Orientation::Horizontal);
+        self.draw(&fretboard).add(playback(&fretboard, timeline))
+    }
 
-        // Bottom point on fret
-        let width_pos: Number = VER_WIDTH_LEFT as f32 + VER_WIDTH as f32;
-        let height_pos: Number = VER_HEIGHT_TOP as f32;
-        //let width_pos: Number = WIDTH_LEFT as f32;
-        //let height_pos: Number = HEIGHT_TOP as f32 + HEIGHT as f32;
-        let mut group_text: Group = Group::new();
-        let style = match self.theme {
-            Theme::Light => {
-                format!("font-family: Verdana; fill: {};", THEME_ITEM_LIGHT)
-            },
-            Theme::Dark => {
-                format!("font-family: Verdana; fill: {};", THEME_ITEM_DARK)
-            },
-        };
-        group_text = group_text
-            .set("class", "text")
-            .set("style", style.clone())
-            .set("text-anchor", "middle");
-        let vec_n = vec![1, 3, 5, 7, 10, 12, 15];
-        for n in vec_n {
-            let mut h: Number = VER_HEIGHT as f32 / NUMBER_POSITION as f32;
-            h *= n as f32 + 0.5;
-            let w = VER_WIDTH_RIGHT / 2;
+    /// Title of the svg, e.g. "A Minor Pentatonic, standard tuning"
+    pub fn title(&self) -> String {
+        format!(
+            "{} {}, {}",
+            self.scale.tonic,
+            self.scale.scale_type.get_name_long(),
+            self.scale.tuning.get_name_long()
+        )
+    }
 
-            //let mut w: Number = WIDTH as f32 / NUMBER_POSITION as f32;
-            //w = w * (n as f32 + 0.5);
-            //let h = HEIGHT_BOTTOM / 2;
-            if n.to_string().len() > 1 {
-                group_text = group_text.add(
-                    Text::new()
-                        .set("class", "header")
-                        .set("dominant-baseline", "middle")
-                        .set("style", style.clone())
-                        .set("x", width_pos + w as Number)
-                        .set(
-                            "y",
-                            height_pos
-                                + h as Number
-                                + VER_FLUTTER_OFFSET_NOTE_NAME,
-                        )
-                        .set("dx", OFFSET_TEXT_BOTTOM)
-                        .add(node::Text::new(n.to_string())),
-                );
-            } else {
-                group_text = group_text.add(
-                    Text::new()
-                        .set("class", "header")
-                        .set("dominant-baseline", "middle")
-                        .set("style", style.clone())
-                        .set("x", width_pos + w as Number)
-                        .set(
-                            "y",
-                            height_pos
-                                + h as Number
-                                + VER_FLUTTER_OFFSET_NOTE_NAME,
-                        )
-                        .add(node::Text::new(n.to_string())),
-                );
-            }
-        }
+    /// Draw the svg of a fretboard
+    fn draw(&self, fretboard: &Fretboard) -> Document {
+        draw_fretboard(
+            fretboard,
+            &self.theme,
+            "scale-chart",
+            &self.title(),
+            &self.id_prefix,
+        )
+    }
+}
 
-        // Circle note
-        let mut group_circle_tonic: Group = Group::new();
-        group_circle_tonic = group_circle_tonic
-            .set("class", "circle_tonic")
-            .set("fill", "coral");
-        let mut group_circle_in_scale: Group = Group::new();
-        let style = match self.theme {
-            Theme::Light => THEME_ITEM_LIGHT,
-            Theme::Dark => THEME_ITEM_DARK,
-        };
-        group_circle_in_scale = group_circle_in_scale
-            .set("class", "circle_in_scale")
-            .set("fill", style);
-        let mut h: Number = VER_HEIGHT as f32 / NUMBER_POSITION as f32;
-        // let mut w: Number = WIDTH as f32 / NUMBER_POSITION as f32;
-        let note_r: Number = h / 3.5;
-        //let note_r: Number = w / 5.0;
-        // Vector
-        let vec_all_strings: Vec<DegreeAllStrings> =
-            self.scale.get_string_combination();
-        // Tonic
-        for i in 0..NUMBER_STRING {
-            let single_string = vec_all_strings[i as usize].clone();
-            let width_pos: Number = VER_WIDTH_LEFT as f32
-                + ((VER_WIDTH as f32 / (NUMBER_STRING - 1) as f32) * i as f32
-                + 0.0);
-            //let height_pos: Number = HEIGHT_TOP as f32
-            //    + ((HEIGHT as f32 / NUMBER_STRING as f32)
-            //        * (self.ukulele_string_convert(i) as f32 + 1.0));
-            for j in 0..NUMBER_POSITION {
-                for v in &single_string.degree_single_string {
-                    if v.position == j as usize && v.sw_tonic {
-                        let height_pos: Number = VER_HEIGHT_TOP as f32;
-                        h = VER_HEIGHT as f32 / NUMBER_POSITION as f32;
-                        h *= j as f32 + 0.5;
-                        // let width_pos: Number = WIDTH_LEFT as f32;
-                        // w = WIDTH as f32 / NUMBER_POSITION as f32;
-                        // w = w * (j as f32 + 0.5);
-                        group_circle_tonic = group_circle_tonic.add(
-                            Circle::new()
-                                .set("cx", width_pos)
-                                .set("cy", height_pos + h as Number)
-                                .set("r", note_r),
-                        );
-                        break;
-                    }
-                }
-            }
-        }
-        // In Scale
-        for i in 0..NUMBER_STRING {
-            let single_string = vec_all_strings[i as usize].clone();
-            let width_pos: Number = VER_WIDTH_LEFT as f32
-                + ((VER_WIDTH as f32 / (NUMBER_STRING - 1) as f32) * i as f32
-                + 0.0);
-            //let height_pos: Number = HEIGHT_TOP as f32
-            //    + ((HEIGHT as f32 / NUMBER_STRING as f32)
-            //        * (self.ukulele_string_convert(i) as f32 + 1.0));
-            for j in 0..NUMBER_POSITION {
-                for v in &single_string.degree_single_string {
-                    if v.position == j as usize && !v.sw_tonic {
-                        let height_pos: Number = VER_HEIGHT_TOP as f32;
-                        h = VER_HEIGHT as f32 / NUMBER_POSITION as f32;
-                        h *= j as f32 + 0.5;
-                        //let width_pos: Number = WIDTH_LEFT as f32;
-                        //w = WIDTH as f32 / NUMBER_POSITION as f32;
-                        //w = w * (j as f32 + 0.5);
-                        group_circle_in_scale = group_circle_in_scale.add(
-                            Circle::new()
-                                .set("cx", width_pos)
-                                .set("cy", height_pos + h as Number)
-                                .set("r", note_r),
-                        );
-                        break;
-                    }
-                }
-            }
-        }
-        // Text Tonic + In Scale
-        let mut group_text_circle: Group = Group::new();
-        /*let style = match self.theme {
-            Theme::Light => THEME_BG_LIGHT,
-            Theme::Dark => THEME_BG_DARK,
-        };*/
-        let style_color = match self.theme {
-            Theme::Light => THEME_TEXT_COLOR_LIGHT,
-            Theme::Dark => THEME_TEXT_COLOR_DARK,
-        };
-        group_text_circle = group_text_circle
-            .set("class", "text_circle")
-            .set("style", "font-family: Verdana;")
-            .set("font-size", "14")
-            .set("fill", style_color)
-            .set("text-anchor", "middle");
-        for i in 0..NUMBER_STRING {
-            let single_string = vec_all_strings[i as usize].clone();
-            let width_pos: Number = VER_WIDTH_LEFT as f32
-                + ((VER_OFFSET_WIDTH as f32)
-//                + ((VER_WIDTH as f32 / NUMBER_STRING as f32)
-                * i as f32
-                + 0.0);
-            // let height_pos: Number = HEIGHT_TOP as f32
-            //    + ((HEIGHT as f32 / NUMBER_STRING as f32)
-            //        * (self.ukulele_string_convert(i) as f32 + 1.0));
-            for j in 0..NUMBER_POSITION {
-                for v in &single_string.degree_single_string {
-                    if v.position == j as usize {
-                        let note = match v.note.pitch_class {
-                            PitchClass::C => "C",
-                            PitchClass::CSharp => "C#",
-                            PitchClass::D => "D",
-                            PitchClass::DSharp => "D#",
-                            PitchClass::E => "E",
-                            PitchClass::F => "F",
-                            PitchClass::FSharp => "F#",
-                            PitchClass::G => "G",
-                            PitchClass::GSharp => "G#",
-                            PitchClass::A => "A",
-                            PitchClass::ASharp => "A#",
-                            PitchClass::B => "B",
-                        };
-                        let height_pos: Number = VER_HEIGHT_TOP as f32;
-                        h = VER_HEIGHT as f32 / NUMBER_POSITION as f32;
-                        h *= j as f32 + 0.5;
-                        //let width_pos: Number = WIDTH_LEFT as f32;
-                        //w = WIDTH as f32 / NUMBER_POSITION as f32;
-                        //w = w * (j as f32 + 0.5);
-                        let style = match self.theme {
-                            Theme::Light => {
-                                format!("font-family: Verdana; fill: {}", THEME_TEXT_COLOR_LIGHT)
-                            },
-                            Theme::Dark => {
-                                format!("font-family: Verdana; fill: {}", THEME_TEXT_COLOR_DARK)
-                            },
-                        };
-                        if note.len() > 1 {
-                            group_text_circle = group_text_circle.add(
-                                Text::new()
-                                    .set("dominant-baseline", "middle")
-                                    .set("x", width_pos + SWIFT_VERTICAL_WIDTH_OFFSET)
-                                    .set(
-                                        "y",
-                                        height_pos
-                                            + h as Number
-                                            + VER_FLUTTER_OFFSET_NOTE_NAME
-                                            as Number,
-                                    )
-                                    .set("dx", OFFSET_TEXT_BOTTOM)
-                                    .set("style", style)
-                                    .add(node::Text::new(note.to_string())),
-                            );
-                        } else {
-                            group_text_circle = group_text_circle.add(
-                                Text::new()
-                                    .set("dominant-baseline", "middle")
-                                    .set("x", width_pos +  SWIFT_VERTICAL_WIDTH_OFFSET)
-                                    .set(
-                                        "y",
-                                        height_pos
-                                            + h as Number
-                                            + VER_FLUTTER_OFFSET_NOTE_NAME
-                                            as Number,
-                                    )
-                                    .set("style", style)
-                                    .add(node::Text::new(note.to_string())),
-                            );
-                        }
-                    }
-                }
-            }
+/// Draw the svg of a fretboard, `class` being the class of the root and
+/// `id_prefix` the prefix of the circle ids. Screen readers get the title,
+/// the notes of each string in the description and a label on each circle,
+/// the rest of the drawing being hidden from them.
+pub(crate) fn draw_fretboard(
+    fretboard: &Fretboard,
+    theme: &Theme,
+    class: &str,
+    title: &str,
+    id_prefix: &str,
+) -> Document {
+    // Grid
+    let style = match theme {
+        Theme::Light => format!(
+            "background: {}; stroke: {}; stroke-linecap: round;",
+            THEME_BG_LIGHT, THEME_ITEM_LIGHT
+        ),
+        Theme::Dark => format!(
+            "background: {}; stroke: {}; stroke-linecap: round;",
+            THEME_BG_DARK, THEME_ITEM_DARK
+        ),
+    };
+    let group_grid: Group = Group::new()
+        .set("style", style)
+        .set("class", "grid")
+        .set("aria-hidden", "true")
+        .add(grid(&fretboard.strings))
+        .add(grid(&fretboard.frets));
+    // Frets behind the capo
+    let mut group_behind_capo: Group = Group::new()
+        .set("class", "behind_capo")
+        .set("aria-hidden", "true")
+        .set("fill", BEHIND_CAPO_COLOR)
+        .set("fill-opacity", BEHIND_CAPO_OPACITY);
+    for area in &fretboard.behind_capo {
+        group_behind_capo = group_behind_capo.add(
+            Rectangle::new()
+                .set("x", area.x)
+                .set("y", area.y)
+                .set("width", area.width)
+                .set("height", area.height),
+        );
+    }
+    // Fret numbers
+    let color = match theme {
+        Theme::Light => THEME_ITEM_LIGHT,
+        Theme::Dark => THEME_ITEM_DARK,
+    };
+    let style = match fretboard.orientation {
+        Orientation::Horizontal => {
+            format!("font-family: Verdana; fill: {}", color)
+        },
+        Orientation::Vertical => {
+            format!("font-family: Verdana; fill: {};", color)
+        },
+    };
+    let mut group_text: Group = Group::new()
+        .set("class", "text")
+        .set("style", style.clone())
+        .set("text-anchor", "middle")
+        .set("aria-hidden", "true");
+    for label in &fretboard.markers {
+        group_text = group_text.add(
+            text(label)
+                .set("class", "header")
+                .set("style", style.clone()),
+        );
+    }
+    // Circle note
+    let mut group_circle_tonic: Group = Group::new()
+        .set("class", "circle_tonic")
+        .set("fill", "coral");
+    let mut group_circle_in_scale: Group = Group::new()
+        .set("class", "circle_in_scale")
+        .set("fill", color);
+    let mut group_circle_behind_capo: Group = Group::new()
+        .set("class", "circle_behind_capo")
+        .set("fill", BEHIND_CAPO_COLOR);
+    for dot in &fretboard.dots {
+        let circle = Circle::new()
+            .set("id", format!("{}{}", id_prefix, dot.id()))
+            .set("role", "img")
+            .set("aria-label", dot.aria_label())
+            .set("cx", dot.cx)
+            .set("cy", dot.cy)
+            .set("r", dot.r);
+        if dot.sw_behind_capo {
+            group_circle_behind_capo = group_circle_behind_capo.add(circle);
+        } else if dot.sw_tonic {
+            group_circle_tonic = group_circle_tonic.add(circle);
+        } else {
+            group_circle_in_scale = group_circle_in_scale.add(circle);
         }
-        let mut group_text_left: Group = Group::new();
-        let style = match self.theme {
-            Theme::Light => {
-                format!("font-family: Verdana; fill: {};", THEME_ITEM_LIGHT)
-            },
-            Theme::Dark => {
-                format!("font-family: Verdana; fill: {};", THEME_ITEM_DARK)
-            },
-        };
-        group_text_left = group_text_left
-            .set("class", "text_circle")
-            .set("style", style.clone())
-            .set("text-anchor", "middle");
+    }
+    // Capo
+    let group_capo: Group = Group::new()
+        .set("class", "capo")
+        .set("stroke", CAPO_COLOR)
+        .set("stroke-width", CAPO_STROKE_WIDTH)
+        .set("stroke-linecap", "round")
+        .set("aria-hidden", "true")
+        .add(grid(&fretboard.capo).set("class", "capo"));
+    // Highlight
+    let mut group_circle_highlight: Group = Group::new()
+        .set("class", "circle_highlight")
+        .set("aria-hidden", "true")
+        .set("fill", "none")
+        .set("stroke", HIGHLIGHT_COLOR)
+        .set("stroke-width", HIGHLIGHT_STROKE_WIDTH);
+    for dot in fretboard.dots.iter().filter(|d| d.highlight) {
+        group_circle_highlight = group_circle_highlight.add(
+            Circle::new()
+                .set("cx", dot.cx)
+                .set("cy", dot.cy)
+                .set("r", dot.r + HIGHLIGHT_STROKE_WIDTH),
+        );
+    }
+    // Text Tonic + In Scale
+    let style_color = match theme {
+        Theme::Light => THEME_TEXT_COLOR_LIGHT,
+        Theme::Dark => THEME_TEXT_COLOR_DARK,
+    };
+    let mut group_text_circle: Group = Group::new()
+        .set("class", "text_circle")
+        .set("style", "font-family: Verdana;")
+        .set("font-size", fretboard.note_font_size.to_string())
+        .set("fill", style_color)
+        .set("text-anchor", "middle")
+        .set("aria-hidden", "true");
+    let style = format!("font-family: Verdana; fill: {}", style_color);
+    for dot in fretboard.dots.iter().filter(|d| !d.label.text.is_empty()) {
+        group_text_circle =
+            group_text_circle.add(text(&dot.label).set("style", style.clone()));
+    }
+    // Single strings title
+    let style = format!("font-family: Verdana; fill: {};", color);
+    let mut group_text_left: Group = Group::new()
+        .set("class", "text_circle")
+        .set("style", style.clone())
+        .set("text-anchor", "middle")
+        .set("aria-hidden", "true");
+    for label in &fretboard.string_labels {
+        group_text_left =
+            group_text_left.add(text(label).set("style", style.clone()));
+    }
+    // End
+    let style = match theme {
+        Theme::Light => format!("background: {};", THEME_BG_LIGHT),
+        Theme::Dark => format!("background: {};", THEME_BG_DARK),
+    };
+    let mut document = Document::new()
+        .set("class", class)
+        .set("role", "group")
+        .set("xmlns", "http://www.w3.org/2000/svg")
+        .set("width", fretboard.width)
+        .set("height", fretboard.height)
+        .set("preserveAspectRatio", "xMidYMid mee    t")
+        .set("font-size", 16.0)
+        .set("style", style)
+        .set("viewBox", (0, 0, fretboard.width, fretboard.height))
+        .add(Title::new().add(node::Text::new(title)))
+        .add(Description::new().add(node::Text::new(fretboard.summary())))
+        .add(group_grid);
+    if !fretboard.behind_capo.is_empty() {
+        document = document.add(group_behind_capo);
+    }
+    document = document
+        .add(group_text)
+        .add(group_circle_tonic)
+        .add(group_circle_in_scale);
+    if !fretboard.capo.is_empty() {
+        document = document.add(group_circle_behind_capo).add(group_capo);
+    }
+    if fretboard.dots.iter().any(|d| d.highlight) {
+        document = document.add(group_circle_highlight);
+    }
+    document.add(group_text_circle).add(group_text_left)
+}
 
-        // Single strings title
-        let interval = self.scale.tuning.get_interval();
-        let roots = [
-            Note::from_str("E").unwrap() + interval,
-            Note::from_str("A").unwrap() + interval,
-            Note::from_str("D").unwrap() + interval + Interval::PlusOctave,
-            Note::from_str("G").unwrap() + interval + Interval::PlusOctave,
-            Note::from_str("B").unwrap() + interval + Interval::PlusOctave,
-            Note::from_str("E").unwrap() + interval + Interval::PlusPlusOctave,
-        ];
-        for i in 0..NUMBER_STRING {
-            let width_pos: Number = VER_WIDTH_LEFT as f32
-                + ((VER_OFFSET_WIDTH as f32)
-                //  + ((VER_WIDTH as f32 / NUMBER_STRING as f32)
-                * i as f32
-                + 0.0);
-            // let height_pos: Number = HEIGHT_TOP as f32
-            //    + ((HEIGHT as f32 / NUMBER_STRING as f32)
-            //        * (self.ukulele_string_convert(i.clone()) as f32 + 1.0));
-            let note = match roots[i as usize].pitch_class {
-                PitchClass::C => "C",
-                PitchClass::CSharp => "C#",
-                PitchClass::D => "D",
-                PitchClass::DSharp => "D#",
-                PitchClass::E => "E",
-                PitchClass::F => "F",
-                PitchClass::FSharp => "F#",
-                PitchClass::G => "G",
-                PitchClass::GSharp => "G#",
-                PitchClass::A => "A",
-                PitchClass::ASharp => "A#",
-                PitchClass::B => "B",
-            };
-            let height_pos: Number = VER_HEIGHT_TOP as f32 / 2.0;
-            // let width_pos: Number = WIDTH_LEFT as f32 / 2.0;
-            if note.len() > 1 {
-                group_text_left = group_text_left.add(
-                    Text::new()
-                        .set("dominant-baseline", "middle")
-                        .set("x", width_pos as Number + SWIFT_VERTICAL_WIDTH_OFFSET)
-                        .set("y", height_pos as Number)
-                        .set("dx", OFFSET_TEXT_BOTTOM)
-                        .set("style", style.clone())
-                        .add(node::Text::new(note.to_string())),
-                );
-            } else {
-                group_text_left = group_text_left.add(
-                    Text::new()
-                        .set("dominant-baseline", "middle")
-                        .set("x", width_pos as Number + SWIFT_VERTICAL_WIDTH_OFFSET)
-                        .set("y", height_pos as Number)
-                        .set("style", style.clone())
-                        .add(node::Text::new(note.to_string())),
-                );
-            }
-        }
-        // End
-        let style = match self.theme {
-            Theme::Light => format!("background: {};", THEME_BG_LIGHT),
-            Theme::Dark => format!("background: {};", THEME_BG_DARK),
+/// Rings around the dots, shown while their notes are played. They follow
+/// a clock restarting at the end of the timeline.
+fn playback(fretboard: &Fretboard, timeline: &Timeline) -> Group {
+    let clock = Animate::new()
+        .set("id", "clock")
+        .set("attributeName", "visibility")
+        .set("values", "visible")
+        .set("begin", "0s;clock.end")
+        .set("dur", format!("{}s", timeline.duration().max(0.001)));
+    let mut group = Group::new()
+        .set("class", "playback")
+        .set("aria-hidden", "true")
+        .set("fill", HIGHLIGHT_COLOR)
+        .set("fill-opacity", 0.4)
+        .set("stroke", HIGHLIGHT_COLOR)
+        .set("stroke-width", HIGHLIGHT_STROKE_WIDTH)
+        .add(clock);
+    for event in &timeline.events {
+        let dot = match fretboard.dots.iter().find(|d| d.id() == event.id) {
+            Some(dot) => dot,
+            None => continue,
         };
-        Document::new()
-            .set("class", "chord-chart")
-            .set("xmlns", "http://www.w3.org/2000/svg")
-            .set("width", VER_WIDTH_LEFT + VER_WIDTH + VER_WIDTH_RIGHT)
-            .set("height", VER_HEIGHT_TOP + VER_HEIGHT + VER_HEIGHT_BOTTOM)
-            .set("preserveAspectRatio", "xMidYMid mee    t")
-            .set("font-size", 16.0)
-            .set("style", style)
-            .set(
-                "viewBox",
-                (
-                    0,
-                    0,
-                    VER_WIDTH as i32
-                        + VER_WIDTH_LEFT as i32
-                        + VER_WIDTH_RIGHT as i32,
-                    VER_HEIGHT as i32
-                        + VER_HEIGHT_TOP as i32
-                        + VER_HEIGHT_BOTTOM as i32,
-                ),
-            )
-            .add(group_grid)
-            .add(group_text)
-            .add(group_circle_tonic)
-            .add(group_circle_in_scale)
-            .add(group_text_circle)
-            .add(group_text_left)
+        let animate = Animate::new()
+            .set("attributeName", "opacity")
+            .set("values", "1")
+            .set("begin", format!("clock.begin+{}s", event.time))
+            .set("dur", format!("{}s", event.duration));
+        group = group.add(
+            Circle::new()
+                .set("cx", dot.cx)
+                .set("cy", dot.cy)
+                .set("r", dot.r + HIGHLIGHT_STROKE_WIDTH)
+                .set("opacity", 0)
+                .add(animate),
+        );
     }
-    ///
-    /// # Arguments
-    ///
-    /// * `string` - ukulele string to invert
-    fn guitar_string_convert(&self, string: u8) -> u8 {
-        match string {
-            0 => 5,
-            1 => 4,
-            2 => 3,
-            3 => 2,
-            4 => 1,
-            5 => 0,
-            _ => 0,
+    group
+}
+
+/// Relative path of horizontal and vertical lines
+fn grid(lines: &[Line]) -> Path {
+    let mut d: Data = Data::new();
+    for line in lines {
+        d = d.move_to((line.x, line.y)); // m
+        if line.dy == 0.0 {
+            d = d.horizontal_line_by((line.dx, 0.0)); // h
+        } else {
+            d = d.vertical_line_by((line.dy, 0.0)); // v
         }
     }
-}
\ No newline at end of file
+    Path::new().set("class", "grid").set("d", d)
+}
+
+/// Text element of a label
+fn text(label: &Label) -> Text {
+    let mut text = Text::new()
+        .set("dominant-baseline", "middle")
+        .set("x", label.x)
+        .set("y", label.y);
+    if let Some(dx) = label.dx {
+        text = text.set("dx", dx);
+    }
+    text.add(node::Text::new(label.text.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capo::Capo;
+    use crate::fretboard::FRET_MARKERS;
+    use rstest::rstest;
+
+    #[test]
+    fn test_accessible() {
+        let draw = DrawScale::from_names("A", "minor pentatonic", "E", "L")
+            .unwrap();
+        assert_eq!(draw.title(), "A Minor Pentatonic, standard tuning");
+        let svg = draw.draw_base().to_string();
+        assert!(svg.contains(r#"class="scale-chart""#));
+        assert!(svg.contains(r#"role="group""#));
+        assert!(svg.contains("<title>\nA Minor Pentatonic, standard tuning"));
+        assert!(svg.contains("<desc>\nstring 1 (E): fret 0 E, fret 3 G,"));
+        assert!(svg.contains(r#"aria-label="string 5, fret 3, C""#));
+        assert!(svg.contains(r#"aria-label="string 5, fret 0, A, tonic""#));
+        // One label for each circle, the texts hidden
+        assert_eq!(
+            svg.matches("aria-label=").count(),
+            draw.fretboard(Orientation::Horizontal).dots.len()
+        );
+        assert_eq!(svg.matches(r#"aria-hidden="true""#).count(), 4);
+    }
+
+    #[test]
+    fn test_capo() {
+        let mut draw = DrawScale::from_names("G", "major", "E", "L").unwrap();
+        assert!(!draw.draw_base().to_string().contains("behind_capo"));
+        draw.scale.capo = Capo::new(3).unwrap();
+        let svg = draw.draw_base().to_string();
+        assert_eq!(svg.matches("<rect").count(), 1);
+        assert!(svg.contains(r#"class="behind_capo""#));
+        assert!(svg.contains(r#"class="circle_behind_capo""#));
+        assert!(svg.contains(
+            r#"aria-label="string 1, fret 0 from the capo, G, tonic""#
+        ));
+    }
+
+    /// Fret numbers of the svg, with their places
+    fn markers(svg: &str) -> Vec<String> {
+        let lines: Vec<&str> = svg.lines().collect();
+        lines
+            .windows(2)
+            .filter(|w| w[0].contains(r#"class="header""#))
+            .map(|w| w.concat())
+            .collect()
+    }
+
+    #[rstest(
+        capo,
+        uncapoed,
+        capoed,
+        case(
+            Capo::partial(2, &[2, 3, 4]).unwrap(),
+            "string 6, fret 3, G",
+            "string 5, fret 1 from the capo, C"
+        ),
+        case(
+            Capo::per_string([0, 2, 2, 2, 0, 4]).unwrap(),
+            "string 2, fret 3, D",
+            "string 1, fret 1 from the capo, A"
+        )
+    )]
+    fn test_partial_capo(capo: Capo, uncapoed: &str, capoed: &str) {
+        let mut draw =
+            DrawScale::from_names("E", "phrygian", "E", "L").unwrap();
+        let without_capo = markers(&draw.draw_base().to_string());
+        draw.scale.capo = capo;
+        let svg = draw.draw_base().to_string();
+        // The uncapoed strings sound from the nut, the fret numbers too
+        assert_eq!(markers(&svg), without_capo);
+        assert_eq!(without_capo.len(), FRET_MARKERS.len());
+        assert!(svg.contains(&format!(r#"aria-label="{}""#, uncapoed)));
+        assert!(svg.contains(&format!(r#"aria-label="{}""#, capoed)));
+        draw.scale.capo = Capo::new(2).unwrap();
+        let svg = draw.draw_base().to_string();
+        assert_ne!(markers(&svg), without_capo);
+    }
+}
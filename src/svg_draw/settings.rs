@@ -1,9 +1,21 @@
+use crate::exercise::Exercise;
+use crate::fingering::ScaleBox;
+use std::error::Error;
+use std::fmt;
 use std::str::FromStr;
 
-/// Custom error for strings that cannot be parsed into chords.
+/// Custom error for strings that cannot be parsed into themes.
 #[derive(Debug)]
 pub struct ParseThemeError {
-    name: String,
+    pub name: String,
+}
+
+impl Error for ParseThemeError {}
+
+impl fmt::Display for ParseThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Could not parse theme \"{}\"", self.name)
+    }
 }
 
 #[derive(Clone)]
@@ -12,20 +24,81 @@ pub enum Theme {
     Dark,
 }
 
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(feature = "serde")]
+serde_string!(Theme);
+
 impl FromStr for Theme {
     type Err = ParseThemeError;
 
+    /// Theme from its name or its initial, e.g. "light" or "L"
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use Theme::*;
 
         let name = s.to_string();
 
-        if s == "L" {
-            Ok(Light)
-        } else if s == "D" {
-            Ok(Dark)
-        } else {
-            Err(ParseThemeError { name })
+        match s {
+            "L" | "light" => Ok(Light),
+            "D" | "dark" => Ok(Dark),
+            _ => Err(ParseThemeError { name }),
         }
     }
-}
\ No newline at end of file
+}
+
+/// What is written in the circles of the notes
+#[derive(Clone, Default)]
+pub enum LabelMode {
+    /// Name of the note, spelled with a sharp
+    #[default]
+    NoteName,
+    /// Finger playing the note in a box, the notes out of the box are left
+    /// blank
+    Finger(ScaleBox),
+    /// When the note is first played in an exercise, the notes out of it
+    /// are left blank
+    Order(Exercise),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest(
+        s,
+        name,
+        case("L", "light"),
+        case("light", "light"),
+        case("D", "dark"),
+        case("dark", "dark")
+    )]
+    fn test_from_str(s: &str, name: &str) {
+        assert_eq!(Theme::from_str(s).unwrap().to_string(), name);
+    }
+
+    #[test]
+    fn test_from_str_error() {
+        assert!(Theme::from_str("l").is_err());
+        assert!(Theme::from_str("Light").is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let json = serde_json::to_string(&Theme::Dark).unwrap();
+        assert_eq!(json, "\"dark\"");
+        let theme: Theme = serde_json::from_str(&json).unwrap();
+        assert_eq!(theme.to_string(), "dark");
+        let theme: Theme = serde_json::from_str("\"L\"").unwrap();
+        assert_eq!(theme.to_string(), "light");
+    }
+}
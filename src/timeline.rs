@@ -0,0 +1,236 @@
+use crate::exercise::Exercise;
+use crate::fingering::HAND_SPAN;
+use crate::fretboard::circle_id;
+use crate::midi::{MidiFile, MidiSettings, TEMPO_MIN, TICKS_PER_BEAT};
+use crate::pitch::Pitch;
+use crate::scale::{DegreeAllStrings, Scale};
+use ukebox::Semitones;
+
+/// A note played, in seconds from the beginning of the audio
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimelineEvent {
+    pub time: f64,
+    pub duration: f64,
+    /// MIDI note number, 60 being C4
+    pub key: Semitones,
+    pub pitch: Pitch,
+    /// Begin at 1 for the lowest string
+    pub string_number: usize,
+    pub position: usize,
+    /// Id of the circle of the note in the svg, e.g. `s3-f5`
+    pub id: String,
+}
+
+/// When each dot of the fretboard lights up while the audio of a scale or
+/// an exercise plays
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Timeline {
+    pub events: Vec<TimelineEvent>,
+}
+
+impl Timeline {
+    /// Timeline of the notes of a MIDI file, each one played on the string
+    /// whose fret is the nearest to the hand. The index of the hand begins
+    /// on `position` and shifts only to reach a note out of its frets.
+    pub fn new(
+        midi: &MidiFile,
+        strings: &[DegreeAllStrings],
+        position: usize,
+    ) -> Self {
+        let mut hand = position;
+        let events = midi
+            .notes
+            .iter()
+            .filter_map(|note| {
+                let (string_number, fret) = strings
+                    .iter()
+                    .flat_map(|s| {
+                        s.degree_single_string
+                            .iter()
+                            .filter(|v| {
+                                !v.sw_behind_capo && v.pitch.midi() == note.key
+                            })
+                            .map(move |v| (s.string_number, v.position))
+                    })
+                    .min_by_key(|&(_, fret)| frets_out(fret, hand))?;
+                // Open strings leave the hand where it is
+                if fret > 0 && frets_out(fret, hand) > 0 {
+                    hand = if fret < hand {
+                        fret
+                    } else {
+                        fret + 1 - HAND_SPAN
+                    };
+                }
+                Some(TimelineEvent {
+                    time: seconds(midi, note.start),
+                    duration: seconds(midi, note.duration),
+                    key: note.key,
+                    pitch: Pitch::from_midi(note.key),
+                    string_number,
+                    position: fret,
+                    id: circle_id(string_number, fret),
+                })
+            })
+            .collect();
+        Self { events }
+    }
+
+    /// Timeline of an exercise at `tempo` beats per minute, as rendered by
+    /// `Exercise::to_midi`
+    pub fn from_exercise(exercise: &Exercise, tempo: u16) -> Self {
+        let midi = exercise.to_midi(tempo);
+        let events = midi
+            .notes
+            .iter()
+            .zip(&exercise.events)
+            .map(|(note, event)| TimelineEvent {
+                time: seconds(&midi, note.start),
+                duration: seconds(&midi, note.duration),
+                key: note.key,
+                pitch: event.pitch,
+                string_number: event.string_number,
+                position: event.position,
+                id: circle_id(event.string_number, event.position),
+            })
+            .collect();
+        Self { events }
+    }
+
+    /// Length in seconds, to the end of the last note
+    pub fn duration(&self) -> f64 {
+        self.events
+            .iter()
+            .map(|e| e.time + e.duration)
+            .fold(0.0, f64::max)
+    }
+}
+
+impl Scale {
+    /// Timeline of the MIDI file of `to_midi`
+    pub fn to_timeline(self, settings: MidiSettings) -> Timeline {
+        Timeline::new(
+            &self.to_midi(settings),
+            &self.get_string_combination(),
+            settings.position,
+        )
+    }
+}
+
+/// Frets between `fret` and the frets covered by the hand with the index
+/// on `hand`
+fn frets_out(fret: usize, hand: usize) -> usize {
+    if fret < hand {
+        hand - fret
+    } else {
+        (fret + 1).saturating_sub(hand + HAND_SPAN)
+    }
+}
+
+/// Seconds of `ticks` at the tempo of `midi`
+fn seconds(midi: &MidiFile, ticks: u32) -> f64 {
+    let tempo = midi.tempo.max(TEMPO_MIN) as f64;
+    ticks as f64 * 60.0 / (tempo * TICKS_PER_BEAT as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capo::Capo;
+    use crate::exercise::{Direction, Pattern};
+    use crate::fingering::FingeringSettings;
+    use crate::fretboard::{Fretboard, Orientation};
+    use crate::pitch_class_set::PitchClassSet;
+    use crate::svg_draw::{DrawScale, Theme};
+
+    #[test]
+    fn test_scale() {
+        let scale = Scale::from_names("A", "minor pentatonic", "E").unwrap();
+        let settings = MidiSettings {
+            position: 5,
+            ..MidiSettings::default()
+        };
+        let timeline = scale.to_timeline(settings);
+        let midi = scale.to_midi(settings);
+        assert_eq!(timeline.events.len(), midi.notes.len());
+        // Eighth notes at 120 beats per minute
+        assert_eq!(timeline.events[1].time, 0.25);
+        assert_eq!(timeline.duration(), 0.25 * midi.notes.len() as f64);
+        let ids: Vec<&str> =
+            timeline.events[..6].iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec!["s1-f5", "s1-f8", "s2-f5", "s2-f7", "s3-f5", "s3-f7"]
+        );
+        // Every id is a circle of the drawing
+        let fretboard = Fretboard::new(
+            scale,
+            Orientation::Horizontal,
+            PitchClassSet::default(),
+        );
+        for event in &timeline.events {
+            assert!(fretboard.dots.iter().any(|d| d.id() == event.id));
+        }
+    }
+
+    #[test]
+    fn test_capo() {
+        let mut scale = Scale::from_names("E", "major", "E").unwrap();
+        scale.capo = Capo::new(3).unwrap();
+        let timeline = scale.to_timeline(MidiSettings::default());
+        assert!(timeline.events.iter().all(|e| e.position >= 3));
+    }
+
+    #[test]
+    fn test_exercise() {
+        let scale = Scale::from_names("G", "major", "E").unwrap();
+        let scale_box =
+            scale.get_boxes(FingeringSettings::default())[0].clone();
+        let exercise = Exercise::new(
+            scale,
+            &scale_box,
+            Pattern::Intervals(2),
+            Direction::Ascending,
+        );
+        let timeline = Timeline::from_exercise(&exercise, 90);
+        assert_eq!(timeline.events.len(), exercise.events.len());
+        let first = &exercise.events[0];
+        assert_eq!(
+            timeline.events[0].id,
+            circle_id(first.string_number, first.position)
+        );
+        // Eighth notes at 90 beats per minute
+        assert!((timeline.events[1].time - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_animated() {
+        let scale = Scale::from_names("E", "minor pentatonic", "E").unwrap();
+        let timeline = scale.to_timeline(MidiSettings::default());
+        let svg = DrawScale::new(scale, Theme::Light)
+            .draw_animated(&timeline)
+            .to_string();
+        assert!(svg.contains(r#"id="s1-f0""#));
+        assert!(svg.contains(r#"begin="0s;clock.end""#));
+        assert!(svg.contains(r#"begin="clock.begin+0.25s""#));
+        let count = svg.matches("clock.begin+").count();
+        assert_eq!(count, timeline.events.len());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let scale = Scale::from_names("E", "minor pentatonic", "E").unwrap();
+        let timeline = scale.to_timeline(MidiSettings::default());
+        let json = serde_json::to_string(&timeline.events[0]).unwrap();
+        assert_eq!(
+            json,
+            r#"{"time":0.0,"duration":0.25,"key":40,"pitch":"E2","string_number":1,"position":0,"id":"s1-f0"}"#
+        );
+        let back: Timeline =
+            serde_json::from_str(&serde_json::to_string(&timeline).unwrap())
+                .unwrap();
+        assert_eq!(back, timeline);
+    }
+}
@@ -1,7 +1,9 @@
 use ukebox::{Semitones};
+use std::error::Error;
 use std::fmt;
 use std::str::FromStr;
 use crate::interval::Interval;
+use crate::pitch::Pitch;
 
 // Custom error for strings that cannot be parsed into notes.
 #[derive(Debug)]
@@ -9,6 +11,8 @@ pub struct ParseTuningError {
     pub name: String,
 }
 
+impl Error for ParseTuningError {}
+
 impl fmt::Display for ParseTuningError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Could not parse tuning name \"{}\"", self.name)
@@ -17,6 +21,7 @@ impl fmt::Display for ParseTuningError {
 
 /// Tuning
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Tuning {
     E,
 }
@@ -29,6 +34,13 @@ impl Tuning {
         }
     }
 
+    /// Name of the tuning, e.g. "standard tuning"
+    pub fn get_name_long(self) -> String {
+        match self {
+            Self::E => "standard tuning".to_string(),
+        }
+    }
+
     /// Interval tuning
     /// See more detail on project ukebox: https://github.com/noeddl/ukebox/blob/master/src/tuning.rs
     /// I have no idea of other tuning with a guitar
@@ -37,6 +49,26 @@ impl Tuning {
             Self::E => Interval::PerfectUnison,
         }
     }
+
+    /// Pitches of the open strings, from the lowest string (E2) to the
+    /// highest (E4)
+    pub fn get_open_strings(self) -> Vec<Pitch> {
+        let interval = self.get_interval();
+        // MIDI note numbers of E2 A2 D3 G3 B3 E4
+        [40, 45, 50, 55, 59, 64]
+            .iter()
+            .map(|&midi| Pitch::from_midi(midi) + interval)
+            .collect()
+    }
+
+    /// String playing a MIDI note nearest to the nut, the highest string
+    /// reaching it (begin at 1 for the lowest string)
+    pub fn get_string_number(self, midi: Semitones) -> usize {
+        self.get_open_strings()
+            .iter()
+            .rposition(|p| p.midi() <= midi)
+            .map_or(1, |i| i + 1)
+    }
 }
 
 impl FromStr for Tuning {
@@ -1,2 +1,2 @@
-!#bin/sh
+#!/bin/sh
 for file in *.wav; do lame -V2 "$file" "${file%.wav}".mp3; done
@@ -1,3 +1,4 @@
+use std::error::Error;
 use std::fmt;
 use std::str::FromStr;
 
@@ -9,6 +10,8 @@ pub struct ParseIntervalError {
     name: String,
 }
 
+impl Error for ParseIntervalError {}
+
 impl fmt::Display for ParseIntervalError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Could not parse interval name \"{}\"", self.name)
@@ -17,7 +20,8 @@ impl fmt::Display for ParseIntervalError {
 
 /// An interval is the difference between two notes.
 /// https://en.wikipedia.org/wiki/Interval_(music)
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Interval {
     PerfectUnison,
     MajorSecond,
@@ -73,8 +77,8 @@ impl Interval {
             DiminishedSeventh => 7,
             MinorSeventh => 7,
             MajorSeventh => 7,
-            PlusOctave => 12,
-            PlusPlusOctave => 24,
+            PlusOctave => 8,
+            PlusPlusOctave => 15,
         }
     }
 }
@@ -99,9 +103,40 @@ impl FromStr for Interval {
             "d7" => DiminishedSeventh,
             "m7" => MinorSeventh,
             "M7" => MajorSeventh,
+            "P8" => PlusOctave,
+            "P15" => PlusPlusOctave,
             _ => return Err(ParseIntervalError { name }),
         };
 
         Ok(interval)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest(
+        s,
+        semitones,
+        number,
+        case("P1", 0, 1),
+        case("m3", 3, 3),
+        case("d5", 6, 5),
+        case("M7", 11, 7),
+        case("P8", 12, 8),
+        case("P15", 24, 15)
+    )]
+    fn test_semitones_and_number(s: &str, semitones: Semitones, number: StaffSteps) {
+        let interval = Interval::from_str(s).unwrap();
+        assert_eq!(interval.to_semitones(), semitones);
+        assert_eq!(interval.to_number(), number);
+    }
+
+    #[rstest(s, case(""), case("M4"), case("P9"))]
+    fn test_from_str_error(s: &str) {
+        assert!(Interval::from_str(s).is_err());
+    }
+}
@@ -31,6 +31,9 @@ pub type FretID = u8;
 /// to move from one note or pitch class to another.
 pub type Semitones = u8;
 
+/// The number of sharps (positive) or flats (negative) of a note.
+pub type Accidental = i8;
+
 /// The number of steps in a staff to move from one staff position
 /// to another.
 pub type StaffSteps = u8;
@@ -2,7 +2,19 @@ use std::fmt;
 use std::ops::{Add, Sub};
 use std::str::FromStr;
 
-use crate::{Interval, PitchClass, Semitones, StaffPosition};
+use crate::{Accidental, Interval, PitchClass, Semitones, StaffPosition, StaffSteps};
+
+/// Number of pitch classes.
+const PITCH_CLASS_COUNT: Semitones = 12;
+
+/// Semitones from C of the natural note on each staff position.
+const NATURAL_SEMITONES: [Semitones; 7] = [0, 2, 4, 5, 7, 9, 11];
+
+/// Letter of each staff position.
+const LETTERS: [char; 7] = ['C', 'D', 'E', 'F', 'G', 'A', 'B'];
+
+/// Highest number of sharps or flats accepted when parsing a note.
+const MAX_ACCIDENTALS: usize = 2;
 
 /// Custom error for strings that cannot be parsed into notes.
 #[derive(Debug)]
@@ -38,6 +50,57 @@ impl Note {
 
         matches!(self.pitch_class, C | D | E | F | G | A | B)
     }
+
+    /// Return the staff position of the note, which decides how it is spelled.
+    pub fn staff_position(&self) -> StaffPosition {
+        self.staff_position
+    }
+
+    /// Return the sharps (positive) or flats (negative) between the natural
+    /// note of the staff position and the pitch class, from -6 to 5, so that
+    /// any combination of staff position and pitch class can be spelled.
+    pub fn accidental(&self) -> Accidental {
+        let natural = NATURAL_SEMITONES[self.staff_position as usize];
+        let diff =
+            (self.pitch_class as Semitones + PITCH_CLASS_COUNT - natural) % PITCH_CLASS_COUNT;
+        (diff as Accidental + 6) % PITCH_CLASS_COUNT as Accidental - 6
+    }
+
+    /// Return the same note spelled with at most one accidental, the way it
+    /// is casually called: `B#` is `C`, `Fbb` is `Eb` and `E##` is `F#`.
+    pub fn simplify(&self) -> Self {
+        let sharp = Self::from(self.pitch_class);
+        if sharp.accidental() == 0 {
+            return sharp;
+        }
+
+        match self.accidental() {
+            -1 | 1 => *self,
+            a if a < 0 => Self::new(self.pitch_class, sharp.staff_position + 1),
+            _ => sharp,
+        }
+    }
+
+    /// Return the number of octaves crossed between the letter and the pitch
+    /// class, e.g. 1 for `B#` which sounds as the `C` of the next octave.
+    pub fn octave_shift(&self) -> i8 {
+        let natural = NATURAL_SEMITONES[self.staff_position as usize] as Accidental;
+        (natural + self.accidental()).div_euclid(PITCH_CLASS_COUNT as Accidental)
+    }
+
+    /// Return the name of the note as written on the staff, with as many
+    /// sharps or flats as needed (e.g. `E#` or `Bbb`).
+    pub fn written_name(&self) -> String {
+        let accidental = self.accidental();
+        let symbol = if accidental < 0 { "b" } else { "#" };
+        let count = accidental.unsigned_abs() as usize;
+
+        format!(
+            "{}{}",
+            LETTERS[self.staff_position as usize],
+            symbol.repeat(count)
+        )
+    }
 }
 
 impl PartialEq for Note {
@@ -49,91 +112,49 @@ impl PartialEq for Note {
 }
 
 impl fmt::Display for Note {
+    /// Write the note the way it is casually called, with at most one
+    /// sharp or flat (e.g. `B#` is written `C`).
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        use PitchClass::*;
-        use StaffPosition::*;
-
-        let s = match (self.staff_position, self.pitch_class) {
-            // Notes on staff position for C.
-            (CPos, ASharp) => "Bb", // C double flat
-            (CPos, B) => "B",       // Cb
-            (CPos, C) => "C",
-            (CPos, CSharp) => "C#",
-            (CPos, D) => "D", // C double sharp
-            // Notes on staff position for D.
-            (DPos, C) => "C", // D double flat
-            (DPos, CSharp) => "Db",
-            (DPos, D) => "D",
-            (DPos, DSharp) => "D#",
-            (DPos, E) => "E", // D double sharp
-            // Notes on staff position for E.
-            (EPos, D) => "D", // E double flat
-            (EPos, DSharp) => "Eb",
-            (EPos, E) => "E",
-            (EPos, F) => "F",       // E#
-            (EPos, FSharp) => "F#", // E double sharp
-            // Notes on staff position for F.
-            (FPos, DSharp) => "Eb", // F double flat
-            (FPos, E) => "E",       // Fb
-            (FPos, F) => "F",
-            (FPos, FSharp) => "F#",
-            (FPos, G) => "G", // F double sharp
-            // Notes on staff position for G.
-            (GPos, F) => "F", // G double flat
-            (GPos, FSharp) => "Gb",
-            (GPos, G) => "G",
-            (GPos, GSharp) => "G#",
-            (GPos, A) => "A", // G double sharp
-            // Notes on staff position for A.
-            (APos, G) => "G", // A double flat
-            (APos, GSharp) => "Ab",
-            (APos, A) => "A",
-            (APos, ASharp) => "A#",
-            (APos, B) => "B", // A double sharp
-            // Notes on staff position for B.
-            (BPos, A) => "A", // B double flat
-            (BPos, ASharp) => "Bb",
-            (BPos, B) => "B",
-            (BPos, C) => "C",       // B#
-            (BPos, CSharp) => "C#", // B double sharp
-            _ => panic!("Impossible combination of PitchClass and StaffPosition"),
-        };
-
-        write!(f, "{}", s)
+        write!(f, "{}", self.simplify().written_name())
     }
 }
 
 impl FromStr for Note {
     type Err = ParseNoteError;
 
+    /// Parse a letter followed by up to two sharps or two flats, such as
+    /// `C`, `Eb`, `F##` or `Bbb`.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use PitchClass::*;
-        use StaffPosition::*;
-
         let name = s.to_string();
 
-        let (pitch_class, staff_position) = match s {
-            "C" => (C, CPos),
-            "C#" => (CSharp, CPos),
-            "Db" => (CSharp, DPos),
-            "D" => (D, DPos),
-            "D#" => (DSharp, DPos),
-            "Eb" => (DSharp, EPos),
-            "E" => (E, EPos),
-            "F" => (F, FPos),
-            "F#" => (FSharp, FPos),
-            "Gb" => (FSharp, GPos),
-            "G" => (G, GPos),
-            "G#" => (GSharp, GPos),
-            "Ab" => (GSharp, APos),
-            "A" => (A, APos),
-            "A#" => (ASharp, APos),
-            "Bb" => (ASharp, BPos),
-            "B" => (B, BPos),
-            _ => return Err(ParseNoteError { name }),
+        let mut chars = s.chars();
+        let staff_position = match chars
+            .next()
+            .and_then(|c| LETTERS.iter().position(|&l| l == c))
+        {
+            Some(i) => StaffPosition::from(i as StaffSteps),
+            None => return Err(ParseNoteError { name }),
+        };
+
+        let accidentals = chars.as_str();
+        let count = accidentals.len();
+        let accidental = if count > MAX_ACCIDENTALS {
+            return Err(ParseNoteError { name });
+        } else if accidentals.chars().all(|c| c == '#') {
+            count as Accidental
+        } else if accidentals.chars().all(|c| c == 'b') {
+            -(count as Accidental)
+        } else {
+            return Err(ParseNoteError { name });
         };
 
-        Ok(Self::new(pitch_class, staff_position))
+        let natural = NATURAL_SEMITONES[staff_position as usize] as Accidental;
+        let semitones = (natural + accidental).rem_euclid(PITCH_CLASS_COUNT as Accidental);
+
+        Ok(Self::new(
+            PitchClass::from(semitones as Semitones),
+            staff_position,
+        ))
     }
 }
 
@@ -347,4 +368,69 @@ mod tests {
         let note = Note::from_str(note_name).unwrap();
         assert_eq!(note - n, Note::from_str(result_name).unwrap());
     }
+
+    #[rstest(
+        s,
+        accidental,
+        written_name,
+        case("Cb", -1, "B"),
+        case("E#", 1, "F"),
+        case("Fb", -1, "E"),
+        case("B#", 1, "C"),
+        case("F##", 2, "G"),
+        case("Bbb", -2, "A"),
+        case("E##", 2, "F#"),
+        case("Fbb", -2, "Eb")
+    )]
+    fn test_double_accidentals(s: &str, accidental: Accidental, written_name: &str) {
+        let note = Note::from_str(s).unwrap();
+        assert_eq!(note.accidental(), accidental);
+        assert_eq!(note.written_name(), s);
+        assert_eq!(format!("{}", note), written_name);
+    }
+
+    #[rstest(s, case(""), case("H"), case("c"), case("C#b"), case("C###"))]
+    fn test_from_str_error(s: &str) {
+        assert!(Note::from_str(s).is_err());
+    }
+
+    #[rstest(
+        s,
+        octave_shift,
+        case("C", 0),
+        case("B#", 1),
+        case("B##", 1),
+        case("Cb", -1),
+        case("Cbb", -1),
+        case("E#", 0)
+    )]
+    fn test_octave_shift(s: &str, octave_shift: i8) {
+        assert_eq!(Note::from_str(s).unwrap().octave_shift(), octave_shift);
+    }
+
+    #[test]
+    fn test_every_combination() {
+        for p in 0..7 {
+            for n in 0..12 {
+                let note = Note::new(PitchClass::from(n), StaffPosition::from(p));
+                assert!(note.written_name().starts_with(LETTERS[p as usize]));
+                assert!(note.accidental().abs() <= 6);
+                assert!(format!("{}", note).len() <= 2);
+            }
+        }
+    }
+
+    #[rstest(
+        note_name,
+        interval,
+        result_name,
+        case("C", PlusOctave, "C"),
+        case("C", PlusPlusOctave, "C"),
+        case("Db", PlusOctave, "Db"),
+        case("C#", MajorThird, "E#")
+    )]
+    fn test_add_interval_spelling(note_name: &str, interval: Interval, result_name: &str) {
+        let note = Note::from_str(note_name).unwrap();
+        assert_eq!((note + interval).written_name(), result_name);
+    }
 }